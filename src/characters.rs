@@ -1,3 +1,9 @@
 /// characters module for the crate.
+pub(crate) mod animation;
+pub(crate) mod appearance;
 pub(crate) mod components;
+pub(crate) mod control;
+pub(crate) mod npc;
 pub(crate) mod player;
+pub(crate) mod profession;
+pub(crate) mod tuning;
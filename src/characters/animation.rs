@@ -0,0 +1,5 @@
+/// animation module for the characters module. A state machine that picks the
+/// animation of every character from how it moves and what it is doing.
+pub(crate) mod components;
+pub(crate) mod control;
+pub(crate) mod start_up;
@@ -0,0 +1,75 @@
+use std::collections::HashMap;
+
+use bevy::prelude::{AnimationClip, Component, Entity, Handle, Resource};
+use serde::{Deserialize, Serialize};
+
+/// The animation states of a character.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum AnimationState {
+    Idle,
+    Walk,
+    Run,
+    UseTool,
+    Carry,
+    Sit,
+}
+
+/// A component with what a character is doing apart from moving. Gameplay systems set
+/// it, and it takes priority over the movement when picking the animation state.
+#[derive(Component, Clone, Copy, Default, PartialEq)]
+pub enum CharacterActivity {
+    #[default]
+    None,
+    /// The character is using a tool until the seconds run out.
+    UsingTool {
+        seconds_left: f32,
+    },
+    /// The character carries the harvest selected on their hotbar in their arms.
+    Carrying,
+    Sitting,
+}
+
+/// A component that animates a character. The state is picked from the speed of the
+/// character and its activity. Characters assembled from glTF parts play the clip of
+/// the state on their animation player, and characters made of procedural parts move
+/// their parts instead.
+#[derive(Component)]
+pub struct AnimationController {
+    pub state: AnimationState,
+    /// The speed from which the character walks instead of standing idle.
+    pub walk_speed_threshold: f32,
+    /// The speed from which the character runs instead of walks.
+    pub run_speed_threshold: f32,
+    /// The seconds the clips blend over when the state changes.
+    pub transition_seconds: f32,
+    /// The animation player of the glTF scene of the character, once it has spawned.
+    pub animation_player: Option<Entity>,
+    /// The state the animation player is playing.
+    pub playing: Option<AnimationState>,
+    /// The cycle of the procedural animation, in radians.
+    pub procedural_phase: f32,
+    /// The current height offset and forward lean of the procedural animation, blended
+    /// toward the pose of the state.
+    pub procedural_height: f32,
+    pub procedural_lean: f32,
+}
+
+impl Default for AnimationController {
+    fn default() -> Self {
+        return AnimationController {
+            state: AnimationState::Idle,
+            walk_speed_threshold: 0.2,
+            run_speed_threshold: 5.0,
+            transition_seconds: 0.2,
+            animation_player: None,
+            playing: None,
+            procedural_phase: 0.0,
+            procedural_height: 0.0,
+            procedural_lean: 0.0,
+        };
+    }
+}
+
+/// A Bevy Engine resource with the animation clip of every state.
+#[derive(Resource, Default)]
+pub struct AnimationClipLibrary(pub HashMap<AnimationState, Handle<AnimationClip>>);
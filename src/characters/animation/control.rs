@@ -0,0 +1,182 @@
+use std::time::Duration;
+
+use bevy::prelude::{
+    AnimationPlayer, Children, Entity, HierarchyQueryExt, Quat, Query, Res, Time, Transform, Vec3,
+    With,
+};
+
+use super::super::appearance::components::{CharacterParts, ProceduralPart};
+use super::super::components::MovementModel;
+use super::components::{
+    AnimationClipLibrary, AnimationController, AnimationState, CharacterActivity,
+};
+
+/// How fast the procedural pose blends toward the pose of the state.
+const PROCEDURAL_POSE_DAMPING: f32 = 12.0;
+
+/// Returns the animation state for the speed and activity of a character.
+fn pick_animation_state(
+    controller: &AnimationController,
+    speed: f32,
+    activity: CharacterActivity,
+) -> AnimationState {
+    return match activity {
+        CharacterActivity::Sitting => AnimationState::Sit,
+        CharacterActivity::UsingTool { .. } => AnimationState::UseTool,
+        CharacterActivity::Carrying => AnimationState::Carry,
+        CharacterActivity::None => {
+            if speed >= controller.run_speed_threshold {
+                AnimationState::Run
+            } else if speed >= controller.walk_speed_threshold {
+                AnimationState::Walk
+            } else {
+                AnimationState::Idle
+            }
+        }
+    };
+}
+
+/// A system that counts down the tool use of every character and clears the activity
+/// once the tool use is over.
+pub fn update_character_activities(
+    timer: Res<Time>,
+    mut characters: Query<&mut CharacterActivity>,
+) {
+    for mut activity in characters.iter_mut() {
+        if let CharacterActivity::UsingTool { seconds_left } = *activity {
+            let seconds_left = seconds_left - timer.delta_seconds();
+            *activity = if seconds_left <= 0.0 {
+                CharacterActivity::None
+            } else {
+                CharacterActivity::UsingTool {
+                    seconds_left: seconds_left,
+                }
+            };
+        }
+    }
+}
+
+/// A system that picks the animation state of every character from the speed of its
+/// movement model and its activity.
+pub fn update_animation_states(
+    mut characters: Query<(
+        &mut AnimationController,
+        Option<&MovementModel>,
+        Option<&CharacterActivity>,
+    )>,
+) {
+    for (mut controller, movement_model, activity) in characters.iter_mut() {
+        let speed = movement_model.map_or(0.0, |movement_model| movement_model.velocity.length());
+        let state = pick_animation_state(&controller, speed, activity.copied().unwrap_or_default());
+        if controller.state != state {
+            controller.state = state;
+        }
+    }
+}
+
+/// A system that links every animated character to the animation player in its glTF
+/// scene. The link is searched again when the parts of the character are replaced.
+pub fn link_animation_players(
+    mut characters: Query<(Entity, &mut AnimationController)>,
+    children: Query<&Children>,
+    animation_players: Query<(), With<AnimationPlayer>>,
+) {
+    for (character_entity, mut controller) in characters.iter_mut() {
+        if controller
+            .animation_player
+            .is_some_and(|player| animation_players.get(player).is_ok())
+        {
+            continue;
+        }
+        let animation_player = children
+            .iter_descendants(character_entity)
+            .find(|descendant| animation_players.get(*descendant).is_ok());
+        if controller.animation_player != animation_player {
+            controller.animation_player = animation_player;
+            controller.playing = None;
+        }
+    }
+}
+
+/// A system that plays the clip of the state of every character linked to an animation
+/// player, blending from the clip it played before.
+pub fn play_animation_clips(
+    library: Res<AnimationClipLibrary>,
+    mut characters: Query<&mut AnimationController>,
+    mut animation_players: Query<&mut AnimationPlayer>,
+) {
+    for mut controller in characters.iter_mut() {
+        if controller.playing == Some(controller.state) {
+            continue;
+        }
+        let Some(player_entity) = controller.animation_player else {
+            continue;
+        };
+        let (Ok(mut animation_player), Some(clip)) = (
+            animation_players.get_mut(player_entity),
+            library.0.get(&controller.state),
+        ) else {
+            continue;
+        };
+        animation_player
+            .play_with_transition(
+                clip.clone(),
+                Duration::from_secs_f32(controller.transition_seconds),
+            )
+            .repeat();
+        controller.playing = Some(controller.state);
+    }
+}
+
+/// Returns the height offset, forward lean, bob height and cycle rate of the procedural
+/// pose of the state.
+fn procedural_pose(state: AnimationState, speed: f32, phase: f32) -> (f32, f32, f32, f32) {
+    return match state {
+        AnimationState::Idle => (0.0, 0.0, 0.015, 2.0),
+        AnimationState::Walk => (0.0, 0.05, 0.05, speed * 2.5),
+        AnimationState::Run => (0.0, 0.2, 0.09, speed * 2.0),
+        AnimationState::UseTool => (0.0, 0.35 * phase.sin().max(0.0), 0.0, 8.0),
+        AnimationState::Carry => (0.0, -0.1, 0.04, 2.0 + speed * 2.5),
+        AnimationState::Sit => (-0.45, 0.0, 0.0, 1.0),
+    };
+}
+
+/// A system that animates the characters made of procedural parts, which have no
+/// skeleton to play clips on. The parts bob with the steps of the character, lean
+/// forward while running and using a tool, and drop down while sitting.
+pub fn animate_procedural_parts(
+    timer: Res<Time>,
+    mut characters: Query<(
+        &mut AnimationController,
+        &CharacterParts,
+        Option<&MovementModel>,
+    )>,
+    mut parts: Query<(&ProceduralPart, &mut Transform)>,
+) {
+    let delta_seconds = timer.delta_seconds();
+    let blend = 1.0 - (-PROCEDURAL_POSE_DAMPING * delta_seconds).exp();
+    for (mut controller, character_parts, movement_model) in characters.iter_mut() {
+        if controller.animation_player.is_some() {
+            continue;
+        }
+        let speed = movement_model.map_or(0.0, |movement_model| movement_model.velocity.length());
+        let (height, lean, bob_height, cycle_rate) =
+            procedural_pose(controller.state, speed, controller.procedural_phase);
+        controller.procedural_phase =
+            (controller.procedural_phase + cycle_rate * delta_seconds) % std::f32::consts::TAU;
+        controller.procedural_height += (height - controller.procedural_height) * blend;
+        controller.procedural_lean += (lean - controller.procedural_lean) * blend;
+        let bob = controller.procedural_phase.sin().abs() * bob_height;
+        // The character faces the negative z axis, so leaning forward turns the top of
+        // the parts toward it.
+        let lean_rotation = Quat::from_rotation_x(-controller.procedural_lean);
+        for part_entity in character_parts.entities.iter() {
+            if let Ok((procedural_part, mut transform)) = parts.get_mut(*part_entity) {
+                let rest_transform = procedural_part.rest_transform;
+                transform.translation = lean_rotation * rest_transform.translation
+                    + Vec3::Y * (controller.procedural_height + bob);
+                transform.rotation = lean_rotation * rest_transform.rotation;
+            }
+        }
+    }
+}
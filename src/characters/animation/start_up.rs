@@ -0,0 +1,31 @@
+use std::collections::HashMap;
+use std::path::Path;
+
+use bevy::prelude::{info, AssetServer, Commands, Res};
+
+use crate::storage::load_ron_or_default;
+
+use super::components::{AnimationClipLibrary, AnimationState};
+
+/// The file with the asset path of the animation clip of every state.
+const ANIMATION_CLIPS_PATH: &str = "assets/characters/animations.ron";
+/// The directory the asset paths of the clips are relative to.
+const ASSET_DIRECTORY: &str = "assets";
+
+/// Initial system to load the animation clips of the states. Clips whose glTF file does
+/// not exist are left out, and characters fall back to procedural animation.
+pub fn add_animation_clip_library(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let clip_paths: HashMap<AnimationState, String> =
+        load_ron_or_default(Path::new(ANIMATION_CLIPS_PATH), "the animation clips");
+    let mut library = AnimationClipLibrary::default();
+    for (state, clip_path) in clip_paths {
+        let file_path = clip_path.split('#').next().unwrap_or(&clip_path);
+        if Path::new(ASSET_DIRECTORY).join(file_path).exists() {
+            library
+                .0
+                .insert(state, asset_server.load(clip_path.as_str()));
+        }
+    }
+    info!("Loaded {} character animation clips", library.0.len());
+    commands.insert_resource(library);
+}
@@ -0,0 +1,5 @@
+/// appearance module for the characters module. Assembles the look of characters from
+/// parts, loaded as glTF scenes or built from procedural meshes when there is no art.
+pub(crate) mod components;
+pub(crate) mod control;
+pub(crate) mod start_up;
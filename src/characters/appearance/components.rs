@@ -0,0 +1,144 @@
+use std::collections::HashMap;
+
+use bevy::prelude::{Color, Component, Entity, Handle, Mesh, Resource, Transform};
+use serde::{Deserialize, Serialize};
+
+/// The slots a character is assembled from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PartSlot {
+    Body,
+    Hair,
+    Clothing,
+}
+
+/// The procedural meshes used for the parts that have no glTF scene.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ProceduralShape {
+    Body,
+    ShortHair,
+    LongHair,
+    Shirt,
+    Overalls,
+}
+
+/// A part a character can be assembled from.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CharacterPart {
+    pub id: String,
+    /// The asset path of the glTF scene of the part, relative to the assets directory.
+    pub scene: Option<String>,
+    /// The procedural mesh used when the part has no scene or the scene file is missing.
+    pub fallback: ProceduralShape,
+}
+
+/// A Bevy Engine resource with every part characters can be assembled from, by slot.
+/// Hair and clothing can be left out, so a character always has a body.
+#[derive(Resource, Clone, Serialize, Deserialize)]
+pub struct CharacterPartCatalog {
+    pub bodies: Vec<CharacterPart>,
+    pub hair: Vec<CharacterPart>,
+    pub clothing: Vec<CharacterPart>,
+}
+
+impl Default for CharacterPartCatalog {
+    fn default() -> Self {
+        let procedural_part = |id: &str, fallback: ProceduralShape| CharacterPart {
+            id: id.to_string(),
+            scene: None,
+            fallback: fallback,
+        };
+        return CharacterPartCatalog {
+            bodies: vec![procedural_part("body", ProceduralShape::Body)],
+            hair: vec![
+                procedural_part("short_hair", ProceduralShape::ShortHair),
+                procedural_part("long_hair", ProceduralShape::LongHair),
+            ],
+            clothing: vec![
+                procedural_part("shirt", ProceduralShape::Shirt),
+                procedural_part("overalls", ProceduralShape::Overalls),
+            ],
+        };
+    }
+}
+
+impl CharacterPartCatalog {
+    /// Returns the parts of the slot.
+    pub fn parts(&self, slot: PartSlot) -> &[CharacterPart] {
+        return match slot {
+            PartSlot::Body => &self.bodies,
+            PartSlot::Hair => &self.hair,
+            PartSlot::Clothing => &self.clothing,
+        };
+    }
+
+    /// Returns the part of the slot with the id.
+    pub fn find(&self, slot: PartSlot, id: &str) -> Option<&CharacterPart> {
+        return self.parts(slot).iter().find(|part| part.id == id);
+    }
+}
+
+/// A component with the look of a character, the id of the part in every slot and the
+/// colors of the parts.
+#[derive(Component, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Appearance {
+    pub body: String,
+    pub hair: Option<String>,
+    pub clothing: Option<String>,
+    pub body_color: Color,
+    pub hair_color: Color,
+    pub clothing_color: Color,
+}
+
+impl Default for Appearance {
+    fn default() -> Self {
+        return Appearance {
+            body: "body".to_string(),
+            hair: Some("short_hair".to_string()),
+            clothing: Some("overalls".to_string()),
+            body_color: Color::hex("#71daff").unwrap(),
+            hair_color: Color::hex("#5a3825").unwrap(),
+            clothing_color: Color::hex("#3b5b92").unwrap(),
+        };
+    }
+}
+
+impl Appearance {
+    /// Returns the id of the part in the slot and its color, if the slot has a part.
+    pub fn slot(&self, slot: PartSlot) -> Option<(&str, Color)> {
+        return match slot {
+            PartSlot::Body => Some((self.body.as_str(), self.body_color)),
+            PartSlot::Hair => self.hair.as_deref().map(|hair| (hair, self.hair_color)),
+            PartSlot::Clothing => self
+                .clothing
+                .as_deref()
+                .map(|clothing| (clothing, self.clothing_color)),
+        };
+    }
+}
+
+/// A component on a character with the entities of the parts it is assembled from, so
+/// they can be replaced when its appearance changes.
+#[derive(Component, Default)]
+pub struct CharacterParts {
+    pub entities: Vec<Entity>,
+}
+
+/// A component on a part spawned from a glTF scene that still has to be tinted with the
+/// color of its slot. The scene takes a few frames to spawn, so the tint is applied once
+/// its meshes exist.
+#[derive(Component)]
+pub struct TintScene {
+    pub color: Color,
+}
+
+/// A component on a part built from a procedural mesh. Procedural parts have no
+/// skeleton, so they are animated by moving them from their rest transform.
+#[derive(Component)]
+pub struct ProceduralPart {
+    pub rest_transform: Transform,
+}
+
+/// A Bevy Engine resource with the meshes of the procedural parts.
+#[derive(Resource, Default)]
+pub struct ProceduralPartMeshes(pub HashMap<ProceduralShape, Handle<Mesh>>);
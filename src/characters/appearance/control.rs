@@ -0,0 +1,151 @@
+use std::path::Path;
+
+use bevy::prelude::{
+    default, AssetServer, Assets, BuildChildren, Children, Color, Commands, DespawnRecursiveExt,
+    DetectChanges, Entity, Handle, HierarchyQueryExt, PbrBundle, Query, Ref, Res, ResMut,
+    SceneBundle, StandardMaterial,
+};
+
+use super::components::{
+    Appearance, CharacterPart, CharacterPartCatalog, CharacterParts, PartSlot, ProceduralPart,
+    ProceduralPartMeshes, TintScene,
+};
+use super::start_up::procedural_part_transform;
+
+/// The directory the asset paths of the parts are relative to.
+const ASSET_DIRECTORY: &str = "assets";
+
+/// Returns true when the glTF file of the scene asset path exists, ignoring the label of
+/// the scene in the file.
+fn scene_file_exists(scene_path: &str) -> bool {
+    let file_path = scene_path.split('#').next().unwrap_or(scene_path);
+    return Path::new(ASSET_DIRECTORY).join(file_path).exists();
+}
+
+/// Spawns a part of a character from its glTF scene, or from its procedural mesh when
+/// it has no scene or the scene file is missing. Returns the part entity.
+fn spawn_character_part(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    part_meshes: &ProceduralPartMeshes,
+    materials: &mut Assets<StandardMaterial>,
+    part: &CharacterPart,
+    color: Color,
+) -> Entity {
+    if let Some(scene_path) = part.scene.as_ref().filter(|path| scene_file_exists(path)) {
+        return commands
+            .spawn((
+                SceneBundle {
+                    scene: asset_server.load(scene_path.as_str()),
+                    ..default()
+                },
+                TintScene { color: color },
+            ))
+            .id();
+    }
+    let rest_transform = procedural_part_transform(part.fallback);
+    return commands
+        .spawn((
+            PbrBundle {
+                mesh: part_meshes
+                    .0
+                    .get(&part.fallback)
+                    .cloned()
+                    .unwrap_or_default(),
+                material: materials.add(color.into()),
+                transform: rest_transform,
+                ..default()
+            },
+            ProceduralPart {
+                rest_transform: rest_transform,
+            },
+        ))
+        .id();
+}
+
+/// A system that assembles the parts of every character whose appearance changed. The
+/// parts from the old appearance are despawned and the parts of every slot are spawned
+/// as children of the character.
+pub fn assemble_character_parts(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    catalog: Res<CharacterPartCatalog>,
+    part_meshes: Res<ProceduralPartMeshes>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut characters: Query<(Entity, Ref<Appearance>, Option<&mut CharacterParts>)>,
+) {
+    for (character_entity, appearance, character_parts) in characters.iter_mut() {
+        if !appearance.is_changed() {
+            continue;
+        }
+        let mut part_entities = Vec::new();
+        for slot in [PartSlot::Body, PartSlot::Hair, PartSlot::Clothing] {
+            let Some((part_id, color)) = appearance.slot(slot) else {
+                continue;
+            };
+            // Parts missing from the catalog fall back to the first part of the slot so
+            // a character never ends up without a body.
+            let part = catalog
+                .find(slot, part_id)
+                .or_else(|| catalog.parts(slot).first());
+            if let Some(part) = part {
+                part_entities.push(spawn_character_part(
+                    &mut commands,
+                    &asset_server,
+                    &part_meshes,
+                    &mut materials,
+                    part,
+                    color,
+                ));
+            }
+        }
+        commands
+            .entity(character_entity)
+            .push_children(&part_entities);
+        match character_parts {
+            Some(mut character_parts) => {
+                for old_part in character_parts.entities.drain(..) {
+                    commands.entity(old_part).despawn_recursive();
+                }
+                character_parts.entities = part_entities;
+            }
+            None => {
+                commands.entity(character_entity).insert(CharacterParts {
+                    entities: part_entities,
+                });
+            }
+        }
+    }
+}
+
+/// A system that tints the meshes of the parts spawned from glTF scenes once the scene
+/// has spawned. Every material of the scene is copied so other characters with the same
+/// part keep their own color.
+pub fn tint_part_scenes(
+    mut commands: Commands,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    parts: Query<(Entity, &TintScene)>,
+    children: Query<&Children>,
+    mesh_materials: Query<&Handle<StandardMaterial>>,
+) {
+    for (part_entity, tint_scene) in parts.iter() {
+        let mut tinted_any = false;
+        for descendant in children.iter_descendants(part_entity) {
+            let Ok(material_handle) = mesh_materials.get(descendant) else {
+                continue;
+            };
+            let Some(material) = materials.get(material_handle) else {
+                continue;
+            };
+            let mut tinted_material = material.clone();
+            tinted_material.base_color = tint_scene.color;
+            commands
+                .entity(descendant)
+                .insert(materials.add(tinted_material));
+            tinted_any = true;
+        }
+        if tinted_any {
+            commands.entity(part_entity).remove::<TintScene>();
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use std::path::Path;
+
+use bevy::prelude::{shape, Assets, Commands, Mesh, ResMut, Transform, Vec3};
+
+use crate::storage::load_ron_or_default;
+
+use super::components::{CharacterPartCatalog, ProceduralPartMeshes, ProceduralShape};
+
+/// The file the catalog of character parts is loaded from.
+const CHARACTER_PART_CATALOG_PATH: &str = "assets/characters/parts.ron";
+
+/// Loads the catalog of character parts from its data file. The default catalog has
+/// only procedural parts.
+fn load_character_part_catalog() -> CharacterPartCatalog {
+    return load_ron_or_default(
+        Path::new(CHARACTER_PART_CATALOG_PATH),
+        "the character part catalog",
+    );
+}
+
+/// Initial system to add the catalog of character parts.
+pub fn add_character_part_catalog(mut commands: Commands) {
+    commands.insert_resource(load_character_part_catalog());
+}
+
+/// Returns where a procedural part sits on a character. The character origin is at its
+/// height mid point, its feet are one unit below it and it faces the negative z axis.
+pub fn procedural_part_transform(procedural_shape: ProceduralShape) -> Transform {
+    return match procedural_shape {
+        ProceduralShape::Body => Transform::IDENTITY,
+        // The hair sits toward the back of the head so the face shows which way the
+        // character is facing.
+        ProceduralShape::ShortHair => Transform::from_xyz(0.0, 0.62, 0.06),
+        ProceduralShape::LongHair => Transform::from_xyz(0.0, 0.45, 0.22),
+        ProceduralShape::Shirt => Transform::from_xyz(0.0, 0.15, 0.0),
+        ProceduralShape::Overalls => Transform::from_xyz(0.0, -0.25, 0.0),
+    };
+}
+
+/// Initial system to build the meshes of the procedural parts.
+pub fn add_procedural_part_meshes(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>) {
+    let mut part_meshes = ProceduralPartMeshes::default();
+    part_meshes.0.insert(
+        ProceduralShape::Body,
+        meshes.add(
+            shape::Capsule {
+                radius: 0.4,
+                depth: 1.2,
+                ..Default::default()
+            }
+            .into(),
+        ),
+    );
+    part_meshes.0.insert(
+        ProceduralShape::ShortHair,
+        meshes.add(
+            shape::UVSphere {
+                radius: 0.38,
+                sectors: 24,
+                stacks: 12,
+            }
+            .into(),
+        ),
+    );
+    let long_hair_size = Vec3::new(0.8, 0.9, 0.3);
+    part_meshes.0.insert(
+        ProceduralShape::LongHair,
+        meshes.add(shape::Box::new(long_hair_size.x, long_hair_size.y, long_hair_size.z).into()),
+    );
+    part_meshes.0.insert(
+        ProceduralShape::Shirt,
+        meshes.add(
+            shape::Cylinder {
+                radius: 0.43,
+                height: 0.6,
+                ..Default::default()
+            }
+            .into(),
+        ),
+    );
+    part_meshes.0.insert(
+        ProceduralShape::Overalls,
+        meshes.add(
+            shape::Cylinder {
+                radius: 0.42,
+                height: 1.0,
+                ..Default::default()
+            }
+            .into(),
+        ),
+    );
+    commands.insert_resource(part_meshes);
+}
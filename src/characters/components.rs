@@ -1,6 +1,11 @@
-use bevy::prelude::Component;
+//! Components that are common to different character types.
 
-/// Components that are common to different character types.
+use std::collections::BTreeMap;
+
+use bevy::prelude::{Component, Vec3};
+use serde::{Deserialize, Serialize};
+
+use crate::collision::kinematic::{CapsuleShape, KinematicSettings};
 
 /// A component to give an entity a name.
 #[derive(Component)]
@@ -9,3 +14,155 @@ pub struct Name(pub String);
 /// A component to indicate if an entity is not a player  character.
 #[derive(Component)]
 pub struct NonPlayerCharacter;
+
+/// A component with how a character is trying to move this frame. The direction is on
+/// the ground plane and its length, up to 1, is how strongly the character is trying to
+/// move, like how far a stick is pushed. The direction is zero when the character is not
+/// trying to move.
+#[derive(Component, Default)]
+pub struct MovementIntent {
+    pub direction: Vec3,
+    pub sprint: bool,
+}
+
+/// The tuning values of how a character moves. They are data driven so different
+/// characters can move differently.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct MovementTuning {
+    /// The top speed when the movement intent is below the run threshold, in units per
+    /// second.
+    pub walk_speed: f32,
+    pub run_speed: f32,
+    pub sprint_speed: f32,
+    /// The movement intent strength from which the character runs instead of walks.
+    pub run_threshold: f32,
+    /// How fast the character speeds up toward the speed it wants, in units per second
+    /// squared.
+    pub acceleration: f32,
+    /// How fast the character slows down when it stops trying to move or wants to move
+    /// slower, in units per second squared.
+    pub deceleration: f32,
+    /// The stamina used per second of sprinting.
+    pub sprint_stamina_cost: f32,
+}
+
+impl Default for MovementTuning {
+    fn default() -> Self {
+        return MovementTuning {
+            walk_speed: 3.0,
+            run_speed: 7.0,
+            sprint_speed: 11.0,
+            run_threshold: 0.6,
+            acceleration: 40.0,
+            deceleration: 50.0,
+            sprint_stamina_cost: 15.0,
+        };
+    }
+}
+
+/// A change to the movement tuning of a character from something like its profession
+/// or equipment. The multipliers of every modifier on a character are combined.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct MovementModifier {
+    pub speed_multiplier: f32,
+    pub acceleration_multiplier: f32,
+    pub stamina_cost_multiplier: f32,
+}
+
+impl Default for MovementModifier {
+    fn default() -> Self {
+        return MovementModifier {
+            speed_multiplier: 1.0,
+            acceleration_multiplier: 1.0,
+            stamina_cost_multiplier: 1.0,
+        };
+    }
+}
+
+/// A component with the movement tuning of a character, the modifiers applied to it
+/// and the current velocity of the character on the ground plane.
+#[derive(Component, Default)]
+pub struct MovementModel {
+    pub tuning: MovementTuning,
+    /// The modifiers by the name of what applies them, so they can be removed again.
+    pub modifiers: BTreeMap<String, MovementModifier>,
+    pub velocity: Vec3,
+}
+
+impl MovementModel {
+    /// Returns the tuning with the modifiers applied.
+    pub fn modified_tuning(&self) -> MovementTuning {
+        let mut tuning = self.tuning;
+        for modifier in self.modifiers.values() {
+            tuning.walk_speed *= modifier.speed_multiplier;
+            tuning.run_speed *= modifier.speed_multiplier;
+            tuning.sprint_speed *= modifier.speed_multiplier;
+            tuning.acceleration *= modifier.acceleration_multiplier;
+            tuning.deceleration *= modifier.acceleration_multiplier;
+            tuning.sprint_stamina_cost *= modifier.stamina_cost_multiplier;
+        }
+        return tuning;
+    }
+}
+
+/// A component with the stamina of a character. Stamina is used by sprinting and comes
+/// back once the character has not used it for the regeneration delay.
+#[derive(Component)]
+pub struct Stamina {
+    pub current: f32,
+    pub max: f32,
+    /// The stamina regained per second.
+    pub regeneration_rate: f32,
+    /// The seconds after using stamina before it starts to come back.
+    pub regeneration_delay: f32,
+    pub seconds_since_use: f32,
+}
+
+impl Stamina {
+    /// Creates a full stamina stat.
+    pub fn full(max: f32) -> Self {
+        return Stamina {
+            current: max,
+            max: max,
+            regeneration_rate: 10.0,
+            regeneration_delay: 1.5,
+            seconds_since_use: 0.0,
+        };
+    }
+}
+
+/// What a character turns to face.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum FacingMode {
+    /// The character faces the direction it is moving in, which is the usual third
+    /// person behavior. The character keeps its facing while standing still.
+    MovementDirection,
+    /// The character faces the direction its camera looks in and strafes while moving,
+    /// for modes like aiming or locking on to a target.
+    CameraDirection,
+}
+
+/// A component that turns a character to face a direction, turning at most the turn
+/// rate so the turn is smooth.
+#[derive(Component)]
+pub struct CharacterFacing {
+    pub mode: FacingMode,
+    /// The turn rate in radians per second.
+    pub turn_rate: f32,
+}
+
+/// A component that moves a character as a kinematic capsule through the static
+/// colliders of the world. The character falls with gravity, stays on the ground while
+/// walking over slopes and steps, and slides along what it walks into.
+#[derive(Component)]
+pub struct CharacterController {
+    pub capsule: CapsuleShape,
+    pub settings: KinematicSettings,
+    /// The downward acceleration in units per second squared.
+    pub gravity: f32,
+    pub max_fall_speed: f32,
+    /// The current vertical speed, negative while falling.
+    pub vertical_speed: f32,
+    pub grounded: bool,
+    pub ground_normal: Vec3,
+}
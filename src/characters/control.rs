@@ -0,0 +1,188 @@
+use bevy::prelude::{GlobalTransform, Quat, Query, Res, Time, Transform, Vec3, With, Without};
+
+use crate::collision::components::{StaticCollider, WorldCollider};
+use crate::collision::kinematic::move_kinematic_capsule;
+
+use super::components::{
+    CharacterController, CharacterFacing, FacingMode, MovementIntent, MovementModel,
+    MovementTuning, Stamina,
+};
+use super::player::camera::components::PlayerCamera;
+use super::player::control::PlayerCameraLink;
+
+/// Returns the rotation around the vertical axis that makes the transform forward point
+/// in the direction on the ground plane. Returns None when the direction has no ground
+/// plane component.
+pub fn heading_rotation(direction: Vec3) -> Option<Quat> {
+    let ground_direction = Vec3::new(direction.x, 0.0, direction.z).normalize_or_zero();
+    if ground_direction == Vec3::ZERO {
+        return None;
+    }
+    // The transform forward is the negative z axis.
+    return Some(Quat::from_rotation_y(f32::atan2(
+        -ground_direction.x,
+        -ground_direction.z,
+    )));
+}
+
+/// Turns the rotation toward the target rotation by at most the max angle in radians.
+pub fn turn_toward(rotation: Quat, target_rotation: Quat, max_angle: f32) -> Quat {
+    let angle = rotation.angle_between(target_rotation);
+    if angle <= max_angle || angle <= f32::EPSILON {
+        return target_rotation;
+    }
+    return rotation.slerp(target_rotation, max_angle / angle);
+}
+
+/// A system that turns every character with a facing toward the heading of its facing
+/// mode. Characters facing their movement direction use their movement intent, and
+/// players facing their camera direction use the camera linked to them.
+pub fn turn_characters_to_face_heading(
+    timer: Res<Time>,
+    mut characters: Query<
+        (
+            &CharacterFacing,
+            &MovementIntent,
+            Option<&PlayerCameraLink>,
+            &mut Transform,
+        ),
+        Without<PlayerCamera>,
+    >,
+    cameras: Query<&Transform, With<PlayerCamera>>,
+) {
+    for (facing, movement_intent, camera_link, mut transform) in characters.iter_mut() {
+        let heading = match facing.mode {
+            FacingMode::MovementDirection => movement_intent.direction,
+            FacingMode::CameraDirection => camera_link
+                .and_then(|camera_link| cameras.get(camera_link.camera).ok())
+                .map(|camera_transform| camera_transform.forward())
+                .unwrap_or(Vec3::ZERO),
+        };
+        if let Some(target_rotation) = heading_rotation(heading) {
+            transform.rotation = turn_toward(
+                transform.rotation,
+                target_rotation,
+                facing.turn_rate * timer.delta_seconds(),
+            );
+        }
+    }
+}
+
+/// Returns the speed a character wants to move at for the strength of its movement
+/// intent.
+fn target_speed(tuning: &MovementTuning, intent_strength: f32, sprinting: bool) -> f32 {
+    if intent_strength <= f32::EPSILON {
+        return 0.0;
+    }
+    if sprinting {
+        return tuning.sprint_speed;
+    }
+    if intent_strength >= tuning.run_threshold {
+        return tuning.run_speed;
+    }
+    return tuning.walk_speed * intent_strength / tuning.run_threshold.max(f32::EPSILON);
+}
+
+/// Moves the velocity toward the target velocity by at most the max change.
+fn move_velocity_toward(velocity: Vec3, target_velocity: Vec3, max_change: f32) -> Vec3 {
+    let change = target_velocity - velocity;
+    if change.length() <= max_change {
+        return target_velocity;
+    }
+    return velocity + change.normalize() * max_change;
+}
+
+/// Uses stamina for sprinting for the frame, or regenerates it when the character is
+/// not sprinting. Returns true when the character has stamina left to sprint.
+fn update_stamina(stamina: &mut Stamina, sprinting: bool, cost: f32, delta_seconds: f32) -> bool {
+    if sprinting && stamina.current > 0.0 {
+        stamina.current = (stamina.current - cost * delta_seconds).max(0.0);
+        stamina.seconds_since_use = 0.0;
+        return true;
+    }
+    stamina.seconds_since_use += delta_seconds;
+    if stamina.seconds_since_use >= stamina.regeneration_delay {
+        stamina.current =
+            (stamina.current + stamina.regeneration_rate * delta_seconds).min(stamina.max);
+    }
+    return false;
+}
+
+/// A system that updates the velocity of every character with a movement model from
+/// its movement intent. Characters speed up and slow down with the acceleration of
+/// their tuning, and sprinting uses their stamina. Characters without stamina can
+/// sprint freely.
+pub fn update_movement_velocities(
+    timer: Res<Time>,
+    mut characters: Query<(&MovementIntent, &mut MovementModel, Option<&mut Stamina>)>,
+) {
+    let delta_seconds = timer.delta_seconds();
+    for (movement_intent, mut movement_model, stamina) in characters.iter_mut() {
+        let tuning = movement_model.modified_tuning();
+        let intent_strength = movement_intent.direction.length().min(1.0);
+        let wants_to_sprint = movement_intent.sprint && intent_strength >= tuning.run_threshold;
+        let sprinting = match stamina {
+            Some(mut stamina) => update_stamina(
+                &mut stamina,
+                wants_to_sprint,
+                tuning.sprint_stamina_cost,
+                delta_seconds,
+            ),
+            None => wants_to_sprint,
+        };
+        let target_velocity = movement_intent.direction.normalize_or_zero()
+            * target_speed(&tuning, intent_strength, sprinting);
+        let rate = if target_velocity.length() >= movement_model.velocity.length() {
+            tuning.acceleration
+        } else {
+            tuning.deceleration
+        };
+        movement_model.velocity = move_velocity_toward(
+            movement_model.velocity,
+            target_velocity,
+            rate * delta_seconds,
+        );
+    }
+}
+
+/// A system that moves every character with a controller by the velocity of its
+/// movement model and gravity, colliding with the static colliders of the world.
+pub fn move_character_controllers(
+    timer: Res<Time>,
+    colliders: Query<(&StaticCollider, &GlobalTransform), Without<CharacterController>>,
+    mut characters: Query<(&mut CharacterController, &MovementModel, &mut Transform)>,
+) {
+    let delta_seconds = timer.delta_seconds();
+    if delta_seconds <= f32::EPSILON {
+        return;
+    }
+    let world_colliders: Vec<WorldCollider> = colliders
+        .iter()
+        .map(|(collider, transform)| WorldCollider::from_static_collider(collider, transform))
+        .collect();
+    for (mut controller, movement_model, mut transform) in characters.iter_mut() {
+        if controller.grounded {
+            controller.vertical_speed = 0.0;
+        } else {
+            controller.vertical_speed = (controller.vertical_speed
+                - controller.gravity * delta_seconds)
+                .max(-controller.max_fall_speed);
+        }
+        let displacement = Vec3::new(
+            movement_model.velocity.x,
+            controller.vertical_speed,
+            movement_model.velocity.z,
+        ) * delta_seconds;
+        let kinematic_move = move_kinematic_capsule(
+            transform.translation,
+            displacement,
+            controller.grounded,
+            &controller.capsule,
+            &controller.settings,
+            &world_colliders,
+        );
+        transform.translation = kinematic_move.position;
+        controller.grounded = kinematic_move.grounded;
+        controller.ground_normal = kinematic_move.ground_normal;
+    }
+}
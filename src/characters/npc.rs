@@ -0,0 +1,5 @@
+/// npc module for the characters module. Spawns the characters that are not controlled
+/// by players from data files and moves them through their daily routines.
+pub(crate) mod components;
+pub(crate) mod control;
+pub(crate) mod start_up;
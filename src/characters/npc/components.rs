@@ -0,0 +1,141 @@
+use bevy::prelude::{Component, Resource};
+use serde::{Deserialize, Serialize};
+
+use super::super::appearance::components::Appearance;
+
+/// What a character does during a part of its day.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ScheduleActivity {
+    Wake,
+    Work,
+    Lunch,
+    Socialize,
+    Sleep,
+}
+
+/// Where a character goes for a part of its day.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ScheduleLocation {
+    Home,
+    Workplace,
+    /// One of the named locations of the world.
+    Place(String),
+}
+
+/// A part of the day of a character, which lasts until the start of the next entry of
+/// the schedule.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScheduleEntry {
+    /// The in-game hour the entry starts at.
+    pub start_hour: f32,
+    pub activity: ScheduleActivity,
+    pub location: ScheduleLocation,
+}
+
+/// The data a character that is not controlled by a player is spawned from. The home,
+/// workplace and schedule places are names of world locations. Characters without a
+/// workplace of their own work at the workplace of their profession.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct NpcDefinition {
+    pub id: String,
+    pub name: String,
+    pub profession: String,
+    pub home: String,
+    #[serde(default)]
+    pub workplace: Option<String>,
+    #[serde(default)]
+    pub appearance: Appearance,
+    /// The name of the movement tuning file of the character.
+    #[serde(default = "default_movement_tuning")]
+    pub movement_tuning: String,
+    pub schedule: Vec<ScheduleEntry>,
+    /// The lines the character says in turn when players talk to them.
+    #[serde(default)]
+    pub dialogue: Vec<String>,
+}
+
+/// Returns the name of the movement tuning used when the definition does not name one.
+fn default_movement_tuning() -> String {
+    return "npc".to_string();
+}
+
+/// A Bevy Engine resource with the definitions of all the characters that are not
+/// controlled by players, including the ones that only join the community later.
+#[derive(Resource, Default)]
+pub struct NpcCatalog(pub Vec<NpcDefinition>);
+
+impl NpcCatalog {
+    /// Returns the definition with the id.
+    pub fn find(&self, id: &str) -> Option<&NpcDefinition> {
+        return self.0.iter().find(|definition| definition.id == id);
+    }
+}
+
+/// A component with the id of the definition a character was spawned from.
+#[derive(Component)]
+pub struct NpcId(pub String);
+
+/// A component with the lines a character says when players talk to them, and the next
+/// line they say.
+#[derive(Component)]
+pub struct Dialogue {
+    pub lines: Vec<String>,
+    pub next: usize,
+}
+
+impl Dialogue {
+    /// Returns the next line of the character and moves on to the one after it. A
+    /// character without lines only greets.
+    pub fn next_line(&mut self) -> String {
+        if self.lines.is_empty() {
+            return "Hello.".to_string();
+        }
+        let line = self.lines[self.next % self.lines.len()].clone();
+        self.next = (self.next + 1) % self.lines.len();
+        return line;
+    }
+}
+
+/// A component with the world location a character lives at.
+#[derive(Component)]
+pub struct Home {
+    pub location: String,
+}
+
+/// A component with the daily routine of a character. The entries are sorted by their
+/// start hour and the current entry is the one the character is following.
+#[derive(Component)]
+pub struct DailySchedule {
+    pub entries: Vec<ScheduleEntry>,
+    pub current: Option<usize>,
+}
+
+impl DailySchedule {
+    /// Creates a schedule with the entries sorted by their start hour.
+    pub fn new(mut entries: Vec<ScheduleEntry>) -> Self {
+        entries.sort_by(|a, b| a.start_hour.total_cmp(&b.start_hour));
+        return DailySchedule {
+            entries: entries,
+            current: None,
+        };
+    }
+
+    /// Returns the index of the entry for the hour of the day. Before the first entry
+    /// of the day the last entry of the day before is still going on.
+    pub fn entry_index_at(&self, hour: f32) -> Option<usize> {
+        if self.entries.is_empty() {
+            return None;
+        }
+        let index = self
+            .entries
+            .iter()
+            .rposition(|entry| entry.start_hour <= hour)
+            .unwrap_or(self.entries.len() - 1);
+        return Some(index);
+    }
+
+    /// Returns the entry the character is following.
+    pub fn current_entry(&self) -> Option<&ScheduleEntry> {
+        return self.current.and_then(|index| self.entries.get(index));
+    }
+}
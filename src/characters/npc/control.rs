@@ -0,0 +1,149 @@
+use bevy::prelude::{
+    info, warn, DetectChanges, EventReader, EventWriter, Query, Res, Vec3, Visibility, With,
+};
+
+use crate::game_time::GameClock;
+use crate::game_world::WorldLocations;
+use crate::interaction::components::{InteractionEvent, InteractionKind};
+use crate::navigation::components::NavigationTarget;
+use crate::ui::player_overlay::PlayerMessageEvent;
+use crate::weather::components::Weather;
+use crate::weather::forecast::WeatherKind;
+
+use super::super::animation::components::CharacterActivity;
+use super::super::components::{Name, NonPlayerCharacter};
+use super::super::profession::components::{Profession, Workplace};
+use super::components::{DailySchedule, Dialogue, Home, ScheduleActivity, ScheduleLocation};
+
+/// Returns the position of the location of a schedule entry, or None when the world has
+/// no location with its name.
+fn resolve_schedule_location(
+    location: &ScheduleLocation,
+    home: &Home,
+    workplace: &Workplace,
+    locations: &WorldLocations,
+) -> Option<Vec3> {
+    let name = match location {
+        ScheduleLocation::Home => &home.location,
+        ScheduleLocation::Workplace => &workplace.location,
+        ScheduleLocation::Place(name) => name,
+    };
+    return locations.0.get(name).copied();
+}
+
+/// Returns whether the weather keeps characters at home instead of going out for the
+/// activity. Nobody goes out in a storm, and rain or snow keep everyone from socializing.
+fn weather_keeps_home(weather: WeatherKind, activity: ScheduleActivity) -> bool {
+    return match weather {
+        WeatherKind::Storm => true,
+        WeatherKind::Rain | WeatherKind::Snow => activity == ScheduleActivity::Socialize,
+        WeatherKind::Sun | WeatherKind::Fog => false,
+    };
+}
+
+/// A system that moves every character on to the entry of its schedule for the in-game
+/// hour, sending it to the location of the entry, or home when the weather keeps it in.
+/// The entries are looked at again when the weather changes.
+pub fn follow_daily_schedules(
+    clock: Res<GameClock>,
+    locations: Res<WorldLocations>,
+    weather: Res<Weather>,
+    mut characters: Query<(
+        &Name,
+        &Profession,
+        &Home,
+        &Workplace,
+        &mut DailySchedule,
+        &mut NavigationTarget,
+    )>,
+) {
+    for (name, profession, home, workplace, mut schedule, mut target) in characters.iter_mut() {
+        let index = schedule.entry_index_at(clock.hour);
+        if schedule.current == index && !weather.is_changed() {
+            continue;
+        }
+        schedule.current = index;
+        let Some(entry) = schedule.current_entry() else {
+            target.position = None;
+            continue;
+        };
+        let location = if weather_keeps_home(weather.today, entry.activity) {
+            &ScheduleLocation::Home
+        } else {
+            &entry.location
+        };
+        let position = resolve_schedule_location(location, home, workplace, &locations);
+        if position.is_none() {
+            warn!(
+                "{} has no location {:?} to {:?} at",
+                name.0, location, entry.activity
+            );
+        } else {
+            info!(
+                "{} the {} heads to {:?} to {:?}",
+                name.0, profession.0, location, entry.activity
+            );
+        }
+        target.position = position;
+        target.arrived = false;
+    }
+}
+
+/// A system that shows what every character is doing once it reaches the location of
+/// its schedule entry. Characters sit down for lunch and go out of sight while they
+/// sleep at home.
+pub fn act_out_schedule_activities(
+    mut characters: Query<
+        (
+            &DailySchedule,
+            &NavigationTarget,
+            &mut CharacterActivity,
+            &mut Visibility,
+        ),
+        With<NonPlayerCharacter>,
+    >,
+) {
+    for (schedule, target, mut activity, mut visibility) in characters.iter_mut() {
+        let scheduled_activity = schedule
+            .current_entry()
+            .filter(|_| target.arrived)
+            .map(|entry| entry.activity);
+        let wanted_visibility = if scheduled_activity == Some(ScheduleActivity::Sleep) {
+            Visibility::Hidden
+        } else {
+            Visibility::Inherited
+        };
+        if *visibility != wanted_visibility {
+            *visibility = wanted_visibility;
+        }
+        // Only the schedule sits the character down and gets it back up, other
+        // activities like using a tool are left alone.
+        let sitting = scheduled_activity == Some(ScheduleActivity::Lunch);
+        match *activity {
+            CharacterActivity::None if sitting => *activity = CharacterActivity::Sitting,
+            CharacterActivity::Sitting if !sitting => *activity = CharacterActivity::None,
+            _ => {}
+        }
+    }
+}
+
+/// A system that shows the next line of the characters players talk to on the overlay
+/// of the player.
+pub fn talk_to_npcs(
+    mut interaction_events: EventReader<InteractionEvent>,
+    mut characters: Query<(&Name, &mut Dialogue), With<NonPlayerCharacter>>,
+    mut messages: EventWriter<PlayerMessageEvent>,
+) {
+    for event in interaction_events.iter() {
+        if event.kind != InteractionKind::Talk {
+            continue;
+        }
+        let Ok((name, mut dialogue)) = characters.get_mut(event.target) else {
+            continue;
+        };
+        messages.send(PlayerMessageEvent {
+            player: event.player,
+            text: format!("{}: {}", name.0, dialogue.next_line()),
+        });
+    }
+}
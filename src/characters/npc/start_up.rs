@@ -0,0 +1,204 @@
+use std::f32::consts::PI;
+use std::path::Path;
+
+use bevy::prelude::{
+    default, info, warn, Bundle, Commands, Entity, Res, ResMut, SpatialBundle, Transform, Vec3,
+};
+
+use crate::collision::kinematic::{CapsuleShape, KinematicSettings};
+use crate::game_world::WorldLocations;
+use crate::interaction::components::{Interactable, InteractionKind};
+use crate::navigation::components::{NavigationAgent, NavigationTarget};
+use crate::storage::load_ron_or_default;
+
+use super::super::animation::components::{AnimationController, CharacterActivity};
+use super::super::appearance::components::Appearance;
+use super::super::components::{
+    CharacterController, CharacterFacing, FacingMode, MovementIntent, MovementModel, Name,
+    NonPlayerCharacter,
+};
+use super::super::profession::components::{
+    Profession, ProfessionDefinition, ProfessionRegistry, ProfessionRoles, ProfessionTools,
+    QuestLines, Role, RoleHolder, Workplace,
+};
+use super::super::tuning::load_movement_tuning;
+use super::components::{DailySchedule, Dialogue, Home, NpcCatalog, NpcDefinition, NpcId};
+
+/// The file the characters that are not controlled by players are loaded from.
+const NPC_DEFINITIONS_PATH: &str = "assets/characters/npcs.ron";
+/// The height of the origin of a character above its feet.
+const NPC_HEIGHT_MID_POINT: f32 = 1.0;
+
+/// A component bundle used to initialize a character that is not controlled by a
+/// player.
+#[derive(Bundle)]
+struct NpcInitBundle {
+    character_type: NonPlayerCharacter,
+    id: NpcId,
+    name: Name,
+    profession: Profession,
+    home: Home,
+    workplace: Workplace,
+    tools: ProfessionTools,
+    quest_lines: QuestLines,
+    schedule: DailySchedule,
+    dialogue: Dialogue,
+    interactable: Interactable,
+    navigation_target: NavigationTarget,
+    navigation_agent: NavigationAgent,
+    facing: CharacterFacing,
+    movement_intent: MovementIntent,
+    character_controller: CharacterController,
+    movement_model: MovementModel,
+    appearance: Appearance,
+    animation_controller: AnimationController,
+    activity: CharacterActivity,
+    spatial: SpatialBundle,
+}
+
+/// Creates the bundle to add a character from its definition at its home location.
+fn create_npc_init_bundle(
+    definition: &NpcDefinition,
+    profession: Option<&ProfessionDefinition>,
+    home_location: Vec3,
+) -> NpcInitBundle {
+    let workplace = definition
+        .workplace
+        .clone()
+        .or_else(|| profession.map(|profession| profession.workplace.clone()))
+        .unwrap_or_else(|| definition.home.clone());
+    return NpcInitBundle {
+        character_type: NonPlayerCharacter,
+        id: NpcId(definition.id.clone()),
+        name: Name(definition.name.clone()),
+        profession: Profession(definition.profession.clone()),
+        home: Home {
+            location: definition.home.clone(),
+        },
+        workplace: Workplace {
+            location: workplace,
+        },
+        tools: ProfessionTools {
+            tools: profession.map_or_else(Vec::new, |profession| profession.tools.clone()),
+        },
+        quest_lines: profession.map_or_else(QuestLines::default, |profession| {
+            profession.starting_quest_lines()
+        }),
+        schedule: DailySchedule::new(definition.schedule.clone()),
+        dialogue: Dialogue {
+            lines: definition.dialogue.clone(),
+            next: 0,
+        },
+        interactable: Interactable::new(
+            InteractionKind::Talk,
+            format!("Talk to {}", definition.name),
+            NPC_HEIGHT_MID_POINT + 1.3,
+        ),
+        navigation_target: NavigationTarget::default(),
+        navigation_agent: NavigationAgent::default(),
+        facing: CharacterFacing {
+            mode: FacingMode::MovementDirection,
+            turn_rate: 2.0 * PI,
+        },
+        movement_intent: MovementIntent::default(),
+        character_controller: CharacterController {
+            capsule: CapsuleShape {
+                radius: 0.45,
+                half_height: NPC_HEIGHT_MID_POINT,
+            },
+            settings: KinematicSettings {
+                max_slope_angle: PI / 4.0,
+                step_height: 0.4,
+                snap_distance: 0.5,
+            },
+            gravity: 20.0,
+            max_fall_speed: 50.0,
+            vertical_speed: 0.0,
+            grounded: false,
+            ground_normal: Vec3::Y,
+        },
+        movement_model: MovementModel {
+            tuning: load_movement_tuning(&definition.movement_tuning),
+            ..default()
+        },
+        appearance: definition.appearance.clone(),
+        animation_controller: AnimationController::default(),
+        activity: CharacterActivity::None,
+        spatial: SpatialBundle::from_transform(Transform::from_translation(
+            home_location + Vec3::Y * NPC_HEIGHT_MID_POINT,
+        )),
+    };
+}
+
+/// Loads the definitions of the characters that are not controlled by players.
+pub fn load_npc_catalog() -> NpcCatalog {
+    return NpcCatalog(load_ron_or_default(
+        Path::new(NPC_DEFINITIONS_PATH),
+        "the characters",
+    ));
+}
+
+/// Spawns a character from its definition at its home. Returns the character entity, or
+/// None when its home is not a world location.
+pub fn spawn_npc(
+    commands: &mut Commands,
+    definition: &NpcDefinition,
+    registry: &ProfessionRegistry,
+    locations: &WorldLocations,
+) -> Option<Entity> {
+    let Some(home_location) = locations.0.get(&definition.home).copied() else {
+        warn!(
+            "Could not spawn {}, there is no home location {}",
+            definition.name, definition.home
+        );
+        return None;
+    };
+    let profession = registry.find(&definition.profession);
+    return Some(
+        commands
+            .spawn(create_npc_init_bundle(
+                definition,
+                profession,
+                home_location,
+            ))
+            .id(),
+    );
+}
+
+/// Initial system to spawn the characters that are not controlled by players at their
+/// homes. The first NPC of every profession fills its role, and the later NPCs of the
+/// professions wait until a role is vacated.
+pub fn spawn_non_player_characters(
+    mut commands: Commands,
+    catalog: Res<NpcCatalog>,
+    registry: Res<ProfessionRegistry>,
+    locations: Res<WorldLocations>,
+    mut roles: ResMut<ProfessionRoles>,
+) {
+    for profession in registry.professions.iter() {
+        roles.0.insert(profession.id.clone(), Role::default());
+    }
+    let mut spawned = 0;
+    for definition in catalog.0.iter() {
+        if !registry.spawns_at_start(&definition.id) {
+            continue;
+        }
+        let Some(entity) = spawn_npc(&mut commands, definition, &registry, &locations) else {
+            continue;
+        };
+        spawned += 1;
+        let fills_role = registry
+            .find(&definition.profession)
+            .is_some_and(|profession| profession.npcs.first() == Some(&definition.id));
+        if fills_role {
+            roles.0.insert(
+                definition.profession.clone(),
+                Role {
+                    holder: RoleHolder::Npc(entity),
+                    next_npc: 1,
+                },
+            );
+        }
+    }
+    info!("Spawned {} non player characters", spawned);
+}
@@ -1,3 +1,5 @@
 pub(crate) mod camera;
 pub(crate) mod control;
 pub(crate) mod entity;
+pub(crate) mod input;
+pub(crate) mod profile;
@@ -1,3 +1,5 @@
 pub(crate) mod components;
 pub(crate) mod control;
+pub(crate) mod occlusion;
+pub(crate) mod split_screen;
 pub(crate) mod start_up;
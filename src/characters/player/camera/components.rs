@@ -1,7 +1,85 @@
-use bevy::prelude::Component;
+use bevy::prelude::{AlphaMode, Component, Vec3};
 
 /// A component to put on a camera entity to associate it with a player.
 #[derive(Component)]
 pub struct PlayerCamera {
     pub player_id: u8,
 }
+
+/// The position of an orbit camera around its target, described by the angles around the
+/// target and the distance from it.
+#[derive(Clone, Copy)]
+pub struct OrbitPose {
+    /// The angle around the vertical axis in radians. A yaw of zero puts the camera on the
+    /// positive z side of the target.
+    pub yaw: f32,
+    /// The angle above the horizontal plane in radians.
+    pub pitch: f32,
+    pub distance: f32,
+    /// The point the camera looks at.
+    pub target: Vec3,
+}
+
+/// A component to put on a player camera to make it orbit the player character it
+/// follows. The yaw, pitch and distance are the values the player asked for through
+/// their controller. The camera eases toward them, and toward the player, based on the
+/// damping so the camera motion is smooth.
+#[derive(Component)]
+pub struct OrbitCamera {
+    pub yaw: f32,
+    pub pitch: f32,
+    pub distance: f32,
+    pub min_pitch: f32,
+    pub max_pitch: f32,
+    pub min_distance: f32,
+    pub max_distance: f32,
+    /// The offset from the player character translation to the point the camera looks
+    /// at.
+    pub target_offset: Vec3,
+    /// The rotation speed, in radians per second, around the vertical axis at full stick
+    /// deflection.
+    pub yaw_speed: f32,
+    /// The rotation speed, in radians per second, above and below the horizontal plane
+    /// at full stick deflection.
+    pub pitch_speed: f32,
+    /// The zoom speed in distance units per second at full trigger deflection.
+    pub zoom_speed: f32,
+    /// How quickly the camera catches up to the requested pose. Higher values follow more
+    /// tightly, and zero stops the camera from moving.
+    pub damping: f32,
+    /// How far the camera is kept from world geometry that is between it and the player.
+    pub collision_margin: f32,
+    /// The pose the camera is currently at. This is None until the camera has been placed
+    /// for the first time.
+    pub current: Option<OrbitPose>,
+}
+
+/// A component to put on world geometry with a static collider and a standard material
+/// so it fades out while it is between a player camera and the player it follows.
+#[derive(Component)]
+pub struct FadeWhenOccluding {
+    /// The alpha of the material while the object is hiding a player.
+    pub faded_alpha: f32,
+    /// How fast the alpha changes, in alpha per second.
+    pub fade_speed: f32,
+    /// The current alpha of the material.
+    pub alpha: f32,
+    /// If the object is currently between a player camera and its player.
+    pub occluding: bool,
+    /// The alpha mode of the material before it started fading. This is None until the
+    /// object has been given its own copy of the material, which is done the first time
+    /// it fades so objects sharing a material do not fade together.
+    pub original_alpha_mode: Option<AlphaMode>,
+}
+
+impl Default for FadeWhenOccluding {
+    fn default() -> Self {
+        return FadeWhenOccluding {
+            faded_alpha: 0.25,
+            fade_speed: 3.0,
+            alpha: 1.0,
+            occluding: false,
+            original_alpha_mode: None,
+        };
+    }
+}
@@ -1,72 +1,257 @@
-use std::f32::consts::PI;
-
 use bevy::prelude::{
-    Axis, Entity, Gamepad, GamepadAxis, GamepadAxisType, Gamepads, Mat3, Quat, Query, Res, Time,
-    Transform, Vec3,
+    Entity, GlobalTransform, Query, Res, Time, Transform, Vec2, Vec3, With, Without,
 };
 
-use super::super::control::{player_gamepad_movement_wrapper, Controller, PlayerInfo};
+use crate::collision::components::{StaticCollider, WorldCollider};
+use crate::collision::ray_cast::cast_ray;
+
+use super::super::control::PlayerCameraLink;
 use super::super::entity::components::PlayerCharacter;
-use super::components::PlayerCamera;
+use super::components::{FadeWhenOccluding, OrbitCamera, OrbitPose, PlayerCamera};
 
-/// Uses the gamepad right stick axes information to calculate a rotation. This rotation
-/// is a percentage of a radian based on the rotation speed and the axes value.
-fn calculate_rotation(
-    gamepad: Gamepad,
-    axes: Res<Axis<GamepadAxis>>,
-    timer: Res<Time>,
-) -> Option<Quat> {
-    let mut rotation: Option<Quat> = None;
-    let speed = 1.0;
-    let x_axis = GamepadAxis::new(gamepad, GamepadAxisType::RightStickX);
-    let y_axis = GamepadAxis::new(gamepad, GamepadAxisType::RightStickY);
-    if let (Some(x_axis), Some(y_axis)) = (axes.get(x_axis), axes.get(y_axis)) {
-        let percent_y_rotation = speed * x_axis * timer.delta_seconds();
-        let y_rotation = Quat::from_rotation_y(percent_y_rotation * 2.0 * PI);
-        let percent_x_rotation = speed * y_axis * timer.delta_seconds();
-        let x_rotation = Quat::from_rotation_x(percent_x_rotation * PI);
-        rotation = Some(y_rotation * x_rotation);
+/// Updates the requested yaw, pitch and distance of the orbit camera based on the look
+/// input and the zoom input. Positive zoom input zooms in and negative zoom input zooms
+/// out. The pitch and distance are clamped to the limits of the orbit camera.
+pub fn orbit_camera_input(
+    look_input: Vec2,
+    zoom_input: f32,
+    timer: &Time,
+    orbit_camera: &mut OrbitCamera,
+) {
+    orbit_camera.yaw += orbit_camera.yaw_speed * look_input.x * timer.delta_seconds();
+    // Looking up lowers the camera behind the player.
+    orbit_camera.pitch -= orbit_camera.pitch_speed * look_input.y * timer.delta_seconds();
+    orbit_camera.pitch = orbit_camera
+        .pitch
+        .clamp(orbit_camera.min_pitch, orbit_camera.max_pitch);
+    orbit_camera.distance -=
+        orbit_camera.zoom_speed * zoom_input.clamp(-1.0, 1.0) * timer.delta_seconds();
+    orbit_camera.distance = orbit_camera
+        .distance
+        .clamp(orbit_camera.min_distance, orbit_camera.max_distance);
+}
+
+/// Calculates the offset from the orbit target to the camera for the pose.
+pub fn calculate_orbit_offset(pose: &OrbitPose) -> Vec3 {
+    let horizontal_distance = pose.distance * pose.pitch.cos();
+    return Vec3::new(
+        horizontal_distance * pose.yaw.sin(),
+        pose.distance * pose.pitch.sin(),
+        horizontal_distance * pose.yaw.cos(),
+    );
+}
+
+/// Moves the current pose toward the requested pose. The amount moved is frame rate
+/// independent, an exponential decay based on the damping and the frame time.
+fn smooth_pose(
+    current: &OrbitPose,
+    requested: &OrbitPose,
+    damping: f32,
+    delta_seconds: f32,
+) -> OrbitPose {
+    let blend = 1.0 - (-damping * delta_seconds).exp();
+    let lerp = |from: f32, to: f32| from + (to - from) * blend;
+    return OrbitPose {
+        yaw: lerp(current.yaw, requested.yaw),
+        pitch: lerp(current.pitch, requested.pitch),
+        distance: lerp(current.distance, requested.distance),
+        target: current.target.lerp(requested.target, blend),
+    };
+}
+
+/// Returns how far the camera can be from the target, in the direction of the offset,
+/// without going into world geometry. A ray is cast from the target toward the camera and
+/// the camera is pulled in front of anything it hits, leaving the margin between the
+/// camera and the geometry.
+pub fn limit_distance_by_collisions(
+    target: Vec3,
+    offset: Vec3,
+    margin: f32,
+    colliders: impl IntoIterator<Item = (Entity, WorldCollider)>,
+) -> f32 {
+    let distance = offset.length();
+    let direction = offset.normalize_or_zero();
+    if direction == Vec3::ZERO {
+        return distance;
     }
-    return rotation;
+    let limited_distance = match cast_ray(target, direction, distance + margin, colliders) {
+        Some(hit) => (hit.distance - margin).clamp(margin.min(distance), distance),
+        None => distance,
+    };
+    return limited_distance;
 }
 
-/// Move the camera entity around, and looking at, the center. The movement
-/// is based on the the gamepad axes information. This implements the player_info
-/// parameter as required by the wrapper function but does not use it.
-fn move_camera(
-    axes: Res<Axis<GamepadAxis>>,
-    mut transforms: Query<&mut Transform>,
+/// A system that moves every orbit camera around the player character it is linked to
+/// and points it at the target on the player. The camera eases toward the requested
+/// pose so the camera motion is smooth, except when world geometry is in the way where
+/// it is pulled in right away so it never goes through the geometry. Geometry that fades
+/// when occluding is left out so it is faded instead of pulling the camera in, also when
+/// the camera ends up inside of it.
+pub fn follow_players_with_orbit_cameras(
     timer: Res<Time>,
-    _player_info: PlayerInfo,
-    gamepad: Gamepad,
-    camera_entity: Entity,
+    colliders: Query<(Entity, &StaticCollider, &GlobalTransform), Without<FadeWhenOccluding>>,
+    players: Query<(&Transform, &PlayerCameraLink), (With<PlayerCharacter>, Without<PlayerCamera>)>,
+    mut cameras: Query<(&mut OrbitCamera, &mut Transform), With<PlayerCamera>>,
 ) {
-    if let Some(rotation) = calculate_rotation(gamepad, axes, timer) {
-        if let Ok(mut camera_transform) = transforms.get_mut(camera_entity) {
-            // Apply the rotation to the vector
-            let rotation_matrix = Mat3::from_quat(rotation);
-            camera_transform.translation = rotation_matrix.mul_vec3(camera_transform.translation);
-            // Now look at center
-            let refocused_transform =
-                camera_transform.looking_at(Vec3::new(0.0, 0.0, 0.0), Vec3::Y);
-            camera_transform.translation = refocused_transform.translation;
-            camera_transform.rotation = refocused_transform.rotation;
-            camera_transform.scale = refocused_transform.scale;
+    for (player_transform, camera_link) in players.iter() {
+        if let Ok((mut orbit_camera, mut camera_transform)) = cameras.get_mut(camera_link.camera) {
+            let requested = OrbitPose {
+                yaw: orbit_camera.yaw,
+                pitch: orbit_camera.pitch,
+                distance: orbit_camera.distance,
+                target: player_transform.translation + orbit_camera.target_offset,
+            };
+            let mut pose = match orbit_camera.current {
+                Some(current) => smooth_pose(
+                    &current,
+                    &requested,
+                    orbit_camera.damping,
+                    timer.delta_seconds(),
+                ),
+                // The camera has not been placed yet so put it right where it belongs.
+                None => requested,
+            };
+            pose.distance = limit_distance_by_collisions(
+                pose.target,
+                calculate_orbit_offset(&pose),
+                orbit_camera.collision_margin,
+                colliders.iter().map(|(entity, collider, transform)| {
+                    return (
+                        entity,
+                        WorldCollider::from_static_collider(collider, transform),
+                    );
+                }),
+            );
+            orbit_camera.current = Some(pose);
+            *camera_transform =
+                Transform::from_translation(pose.target + calculate_orbit_offset(&pose))
+                    .looking_at(pose.target, Vec3::Y);
         }
     }
 }
 
-/// Generate a system to move the player camera based on the right stick movement of
-/// the gamepad.
-pub fn generate_move_player_camera_system(
-    player_id: u8,
-) -> impl Fn(
-    Res<Gamepads>,
-    Res<Axis<GamepadAxis>>,
-    Query<(Entity, &PlayerCharacter, &Controller)>,
-    Query<(Entity, &PlayerCamera)>,
-    Query<&mut Transform>,
-    Res<Time>,
-) {
-    return player_gamepad_movement_wrapper(player_id, move_camera);
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::{App, Entity, GlobalTransform, Time, Transform, Vec3};
+
+    use crate::collision::components::{ColliderShape, StaticCollider, WorldCollider};
+
+    use super::super::super::control::PlayerCameraLink;
+    use super::super::super::entity::components::PlayerCharacter;
+    use super::super::components::{FadeWhenOccluding, PlayerCamera};
+    use super::super::start_up::create_orbit_camera_component;
+    use super::{follow_players_with_orbit_cameras, limit_distance_by_collisions};
+
+    fn wall_at(center: Vec3) -> (Entity, WorldCollider) {
+        return (
+            Entity::from_raw(1),
+            WorldCollider {
+                shape: ColliderShape::Cuboid {
+                    half_extents: Vec3::new(5.0, 5.0, 0.5),
+                },
+                center: center,
+                rotation: Default::default(),
+            },
+        );
+    }
+
+    #[test]
+    fn keeps_the_distance_without_colliders() {
+        let distance =
+            limit_distance_by_collisions(Vec3::ZERO, Vec3::new(0.0, 0.0, 10.0), 0.3, Vec::new());
+        assert_eq!(distance, 10.0);
+    }
+
+    #[test]
+    fn pulls_the_camera_in_front_of_a_wall() {
+        let distance = limit_distance_by_collisions(
+            Vec3::ZERO,
+            Vec3::new(0.0, 0.0, 10.0),
+            0.3,
+            vec![wall_at(Vec3::new(0.0, 0.0, 5.0))],
+        );
+        // The wall face toward the target is at 4.5 and the margin is kept in front of it.
+        assert!((distance - 4.2).abs() < 1e-4);
+    }
+
+    #[test]
+    fn keeps_the_margin_to_a_wall_just_behind_the_camera() {
+        let distance = limit_distance_by_collisions(
+            Vec3::ZERO,
+            Vec3::new(0.0, 0.0, 10.0),
+            0.3,
+            vec![wall_at(Vec3::new(0.0, 0.0, 10.6))],
+        );
+        assert!((distance - 9.8).abs() < 1e-4);
+    }
+
+    #[test]
+    fn ignores_walls_beside_the_ray() {
+        let distance = limit_distance_by_collisions(
+            Vec3::ZERO,
+            Vec3::new(0.0, 0.0, 10.0),
+            0.3,
+            vec![wall_at(Vec3::new(20.0, 0.0, 5.0))],
+        );
+        assert_eq!(distance, 10.0);
+    }
+
+    #[test]
+    fn never_pulls_the_camera_through_the_target() {
+        let distance = limit_distance_by_collisions(
+            Vec3::ZERO,
+            Vec3::new(0.0, 0.0, 10.0),
+            0.3,
+            vec![wall_at(Vec3::new(0.0, 0.0, 0.6))],
+        );
+        assert!((distance - 0.3).abs() < 1e-4);
+    }
+
+    #[test]
+    fn collides_with_walls_but_not_with_objects_that_fade() {
+        let mut app = App::new();
+        app.insert_resource(Time::default())
+            .add_system(follow_players_with_orbit_cameras);
+        let mut orbit_camera = create_orbit_camera_component(0.0);
+        orbit_camera.yaw = 0.0;
+        orbit_camera.pitch = 0.0;
+        orbit_camera.distance = 10.0;
+        let camera = app
+            .world
+            .spawn((
+                PlayerCamera { player_id: 0 },
+                orbit_camera,
+                Transform::default(),
+            ))
+            .id();
+        app.world.spawn((
+            PlayerCharacter {
+                id: 0,
+                player_height_mid_point: 0.0,
+            },
+            PlayerCameraLink { camera: camera },
+            Transform::default(),
+        ));
+        let wall_collider = StaticCollider {
+            shape: ColliderShape::Cuboid {
+                half_extents: Vec3::new(5.0, 5.0, 0.5),
+            },
+            offset: Vec3::ZERO,
+        };
+        let fadeable = app
+            .world
+            .spawn((
+                wall_collider,
+                GlobalTransform::from_translation(Vec3::new(0.0, 0.0, 5.0)),
+                FadeWhenOccluding::default(),
+            ))
+            .id();
+        app.update();
+        let camera_z = |app: &App| app.world.get::<Transform>(camera).unwrap().translation.z;
+        assert!((camera_z(&app) - 10.0).abs() < 1e-4);
+
+        app.world.entity_mut(fadeable).remove::<FadeWhenOccluding>();
+        app.update();
+        assert!((camera_z(&app) - 4.2).abs() < 1e-4);
+    }
 }
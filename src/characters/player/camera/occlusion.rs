@@ -0,0 +1,272 @@
+use bevy::prelude::{
+    AlphaMode, Assets, Entity, GlobalTransform, Handle, Query, Res, ResMut, StandardMaterial, Time,
+    Transform, With,
+};
+
+use crate::collision::components::{StaticCollider, WorldCollider};
+use crate::collision::contact::contains_point;
+use crate::collision::ray_cast::cast_ray_all;
+
+use super::components::{FadeWhenOccluding, OrbitCamera, PlayerCamera};
+
+/// Moves the alpha toward the target alpha at the fade speed, without overshooting it.
+pub fn step_fade_alpha(alpha: f32, target_alpha: f32, fade_speed: f32, delta_seconds: f32) -> f32 {
+    let max_step = fade_speed * delta_seconds;
+    return alpha + (target_alpha - alpha).clamp(-max_step, max_step);
+}
+
+/// A system that finds the objects that can fade which are between a player camera and
+/// the point it looks at on the player, and marks them as occluding. The camera does
+/// not collide with these objects, so an object the camera is inside of occludes too.
+pub fn detect_occluding_objects(
+    cameras: Query<(&OrbitCamera, &Transform), With<PlayerCamera>>,
+    mut fadeable_objects: Query<(
+        Entity,
+        &StaticCollider,
+        &GlobalTransform,
+        &mut FadeWhenOccluding,
+    )>,
+) {
+    let colliders: Vec<(Entity, WorldCollider)> = fadeable_objects
+        .iter()
+        .map(|(entity, collider, transform, _)| {
+            return (
+                entity,
+                WorldCollider::from_static_collider(collider, transform),
+            );
+        })
+        .collect();
+    for (_, _, _, mut fade) in fadeable_objects.iter_mut() {
+        fade.occluding = false;
+    }
+    for (orbit_camera, camera_transform) in cameras.iter() {
+        if let Some(pose) = orbit_camera.current {
+            let to_target = pose.target - camera_transform.translation;
+            let hits = cast_ray_all(
+                camera_transform.translation,
+                to_target.normalize_or_zero(),
+                to_target.length(),
+                colliders.iter().copied(),
+            );
+            let inside = colliders
+                .iter()
+                .filter(|(_, collider)| contains_point(collider, camera_transform.translation))
+                .map(|(entity, _)| *entity);
+            for entity in hits.iter().map(|hit| hit.entity).chain(inside) {
+                if let Ok((_, _, _, mut fade)) = fadeable_objects.get_mut(entity) {
+                    fade.occluding = true;
+                }
+            }
+        }
+    }
+}
+
+/// A system that fades the material of the objects that are occluding a player and
+/// fades it back in once they are not. The first time an object fades it is given its
+/// own copy of its material so other objects sharing the material are not affected.
+/// Materials are shared by all the player cameras so an object hiding one player fades
+/// for every player.
+pub fn fade_occluding_objects(
+    timer: Res<Time>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut fadeable_objects: Query<(&mut FadeWhenOccluding, &mut Handle<StandardMaterial>)>,
+) {
+    for (mut fade, mut material_handle) in fadeable_objects.iter_mut() {
+        let target_alpha = if fade.occluding {
+            fade.faded_alpha
+        } else {
+            1.0
+        };
+        if fade.alpha == target_alpha {
+            continue;
+        }
+        fade.alpha = step_fade_alpha(
+            fade.alpha,
+            target_alpha,
+            fade.fade_speed,
+            timer.delta_seconds(),
+        );
+        if fade.original_alpha_mode.is_none() {
+            if let Some(material) = materials.get(&material_handle).cloned() {
+                fade.original_alpha_mode = Some(material.alpha_mode);
+                *material_handle = materials.add(material);
+            }
+        }
+        if let (Some(material), Some(original_alpha_mode)) = (
+            materials.get_mut(&material_handle),
+            fade.original_alpha_mode,
+        ) {
+            material.base_color.set_a(fade.alpha);
+            material.alpha_mode = if fade.alpha < 1.0 {
+                AlphaMode::Blend
+            } else {
+                original_alpha_mode
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use bevy::prelude::{
+        AddAsset, AlphaMode, App, AssetPlugin, Assets, Color, Entity, GlobalTransform, Handle,
+        IntoSystemConfig, StandardMaterial, TaskPoolPlugin, Time, Transform, Vec3,
+    };
+
+    use crate::collision::components::{ColliderShape, StaticCollider};
+
+    use super::super::components::{FadeWhenOccluding, OrbitPose, PlayerCamera};
+    use super::super::start_up::create_orbit_camera_component;
+    use super::{detect_occluding_objects, fade_occluding_objects, step_fade_alpha};
+
+    /// Creates an app with a camera at z 10 looking at the origin and a time step of a
+    /// tenth of a second per update.
+    fn create_app() -> App {
+        let mut app = App::new();
+        app.add_plugin(TaskPoolPlugin::default())
+            .add_plugin(AssetPlugin::default())
+            .add_asset::<StandardMaterial>()
+            .add_system(detect_occluding_objects)
+            .add_system(fade_occluding_objects.after(detect_occluding_objects));
+        let mut time = Time::default();
+        time.update_with_instant(Instant::now());
+        app.insert_resource(time);
+        let mut orbit_camera = create_orbit_camera_component(0.0);
+        orbit_camera.current = Some(OrbitPose {
+            yaw: 0.0,
+            pitch: 0.0,
+            distance: 10.0,
+            target: Vec3::ZERO,
+        });
+        app.world.spawn((
+            PlayerCamera { player_id: 0 },
+            orbit_camera,
+            Transform::from_xyz(0.0, 0.0, 10.0),
+        ));
+        return app;
+    }
+
+    fn step_time(app: &mut App) {
+        let mut time = app.world.resource_mut::<Time>();
+        let next = time.last_update().unwrap() + Duration::from_millis(100);
+        time.update_with_instant(next);
+    }
+
+    fn spawn_fadeable(app: &mut App, position: Vec3, material: Handle<StandardMaterial>) -> Entity {
+        return app
+            .world
+            .spawn((
+                StaticCollider {
+                    shape: ColliderShape::Cuboid {
+                        half_extents: Vec3::new(1.0, 1.0, 0.5),
+                    },
+                    offset: Vec3::ZERO,
+                },
+                GlobalTransform::from_translation(position),
+                FadeWhenOccluding::default(),
+                material,
+            ))
+            .id();
+    }
+
+    #[test]
+    fn steps_the_alpha_without_overshooting() {
+        assert!((step_fade_alpha(1.0, 0.25, 3.0, 0.1) - 0.7).abs() < 1e-6);
+        assert_eq!(step_fade_alpha(0.3, 0.25, 3.0, 0.1), 0.25);
+        assert_eq!(step_fade_alpha(0.25, 1.0, 3.0, 1.0), 1.0);
+    }
+
+    #[test]
+    fn marks_only_objects_between_the_camera_and_the_target() {
+        let mut app = create_app();
+        let material = app
+            .world
+            .resource_mut::<Assets<StandardMaterial>>()
+            .add(StandardMaterial::default());
+        let between = spawn_fadeable(&mut app, Vec3::new(0.0, 0.0, 5.0), material.clone());
+        let beside = spawn_fadeable(&mut app, Vec3::new(5.0, 0.0, 5.0), material.clone());
+        let behind = spawn_fadeable(&mut app, Vec3::new(0.0, 0.0, -5.0), material);
+        app.update();
+        let occluding = |entity: Entity| {
+            app.world
+                .get::<FadeWhenOccluding>(entity)
+                .unwrap()
+                .occluding
+        };
+        assert!(occluding(between));
+        assert!(!occluding(beside));
+        assert!(!occluding(behind));
+    }
+
+    #[test]
+    fn marks_objects_the_camera_is_inside_of() {
+        let mut app = create_app();
+        let material = app
+            .world
+            .resource_mut::<Assets<StandardMaterial>>()
+            .add(StandardMaterial::default());
+        let shed = app
+            .world
+            .spawn((
+                StaticCollider {
+                    shape: ColliderShape::Cuboid {
+                        half_extents: Vec3::new(2.0, 2.0, 2.0),
+                    },
+                    offset: Vec3::ZERO,
+                },
+                GlobalTransform::from_translation(Vec3::new(0.0, 0.0, 9.0)),
+                FadeWhenOccluding::default(),
+                material,
+            ))
+            .id();
+        app.update();
+        assert!(app.world.get::<FadeWhenOccluding>(shed).unwrap().occluding);
+    }
+
+    #[test]
+    fn fades_occluders_out_and_back_in_on_their_own_material() {
+        let mut app = create_app();
+        let shared_material = app
+            .world
+            .resource_mut::<Assets<StandardMaterial>>()
+            .add(StandardMaterial::from(Color::WHITE));
+        let wall = spawn_fadeable(&mut app, Vec3::new(0.0, 0.0, 5.0), shared_material.clone());
+        for _ in 0..10 {
+            step_time(&mut app);
+            app.update();
+        }
+        let fade = app.world.get::<FadeWhenOccluding>(wall).unwrap();
+        assert_eq!(fade.alpha, fade.faded_alpha);
+        assert_eq!(fade.original_alpha_mode, Some(AlphaMode::Opaque));
+        let wall_material = app
+            .world
+            .get::<Handle<StandardMaterial>>(wall)
+            .unwrap()
+            .clone();
+        assert_ne!(wall_material, shared_material);
+        let materials = app.world.resource::<Assets<StandardMaterial>>();
+        assert_eq!(
+            materials.get(&wall_material).unwrap().alpha_mode,
+            AlphaMode::Blend
+        );
+        assert_eq!(materials.get(&shared_material).unwrap().base_color.a(), 1.0);
+
+        // Moving the wall out of the way fades it back in.
+        *app.world.get_mut::<GlobalTransform>(wall).unwrap() =
+            GlobalTransform::from_translation(Vec3::new(20.0, 0.0, 5.0));
+        for _ in 0..10 {
+            step_time(&mut app);
+            app.update();
+        }
+        let fade = app.world.get::<FadeWhenOccluding>(wall).unwrap();
+        assert!(!fade.occluding);
+        assert_eq!(fade.alpha, 1.0);
+        let materials = app.world.resource::<Assets<StandardMaterial>>();
+        assert_eq!(
+            materials.get(&wall_material).unwrap().alpha_mode,
+            AlphaMode::Opaque
+        );
+    }
+}
@@ -0,0 +1,182 @@
+use bevy::{
+    core_pipeline::clear_color::ClearColorConfig,
+    prelude::{
+        default, Camera, Camera3d, Local, Query, Res, Resource, UVec2, Window, With, Without,
+    },
+    render::camera::Viewport,
+    window::PrimaryWindow,
+};
+
+use super::super::entity::components::{AwaitingReconnect, PlayerCharacter};
+use super::components::PlayerCamera;
+
+/// How the window is divided when two players are active.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum TwoPlayerSplit {
+    /// The window is divided by a vertical line, one player on the left and one on the
+    /// right.
+    Vertical,
+    /// The window is divided by a horizontal line, one player on the top and one on the
+    /// bottom.
+    Horizontal,
+    /// The window is divided along its longer side, vertically for a landscape window and
+    /// horizontally for a portrait window.
+    Automatic,
+}
+
+/// A Bevy Engine resource with the settings used to lay out the player camera
+/// viewports.
+#[derive(Resource)]
+pub struct SplitScreenSettings {
+    pub two_player_split: TwoPlayerSplit,
+}
+
+impl Default for SplitScreenSettings {
+    fn default() -> Self {
+        return SplitScreenSettings {
+            two_player_split: TwoPlayerSplit::Automatic,
+        };
+    }
+}
+
+/// The state the viewports were last laid out for. The viewports only need to be laid
+/// out again when any of this changes.
+#[derive(Default, PartialEq)]
+pub struct SplitScreenLayout {
+    active_player_ids: Vec<u8>,
+    window_size: UVec2,
+    two_player_split: Option<TwoPlayerSplit>,
+}
+
+/// Splits a length in two, giving the extra pixel of an odd length to the second half.
+fn split_length(length: u32) -> (u32, u32) {
+    let first = length / 2;
+    return (first, length - first);
+}
+
+/// Resolves the automatic two player split based on the shape of the window.
+fn resolve_two_player_split(
+    two_player_split: TwoPlayerSplit,
+    window_size: UVec2,
+) -> TwoPlayerSplit {
+    return match two_player_split {
+        TwoPlayerSplit::Automatic if window_size.x >= window_size.y => TwoPlayerSplit::Vertical,
+        TwoPlayerSplit::Automatic => TwoPlayerSplit::Horizontal,
+        split => split,
+    };
+}
+
+/// Calculates the viewports for the number of active players in a window of the
+/// provided physical size. One player gets the full window, two players split the window
+/// in half and three or four players get a quadrant each. The viewports are returned in
+/// player order as (physical position, physical size) pairs.
+pub fn calculate_viewports(
+    player_count: usize,
+    window_size: UVec2,
+    two_player_split: TwoPlayerSplit,
+) -> Vec<(UVec2, UVec2)> {
+    let (left_width, right_width) = split_length(window_size.x);
+    let (top_height, bottom_height) = split_length(window_size.y);
+    let viewports = match player_count {
+        0 => Vec::new(),
+        1 => vec![(UVec2::ZERO, window_size)],
+        2 => match resolve_two_player_split(two_player_split, window_size) {
+            TwoPlayerSplit::Vertical => vec![
+                (UVec2::ZERO, UVec2::new(left_width, window_size.y)),
+                (
+                    UVec2::new(left_width, 0),
+                    UVec2::new(right_width, window_size.y),
+                ),
+            ],
+            _ => vec![
+                (UVec2::ZERO, UVec2::new(window_size.x, top_height)),
+                (
+                    UVec2::new(0, top_height),
+                    UVec2::new(window_size.x, bottom_height),
+                ),
+            ],
+        },
+        _ => {
+            let quadrants = [
+                (UVec2::ZERO, UVec2::new(left_width, top_height)),
+                (
+                    UVec2::new(left_width, 0),
+                    UVec2::new(right_width, top_height),
+                ),
+                (
+                    UVec2::new(0, top_height),
+                    UVec2::new(left_width, bottom_height),
+                ),
+                (
+                    UVec2::new(left_width, top_height),
+                    UVec2::new(right_width, bottom_height),
+                ),
+            ];
+            quadrants.into_iter().take(player_count).collect()
+        }
+    };
+    return viewports;
+}
+
+/// A system that sets the viewport and render order of every player camera based on
+/// the number of active players. The viewports are laid out again whenever a player
+/// joins, leaves, is waiting for their controller to reconnect, or the window is
+/// resized. Cameras of players waiting for their controller are turned off so the
+/// active players get the whole window.
+pub fn layout_split_screen_viewports(
+    mut last_layout: Local<SplitScreenLayout>,
+    settings: Res<SplitScreenSettings>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    active_players: Query<&PlayerCharacter, Without<AwaitingReconnect>>,
+    mut player_cameras: Query<(&PlayerCamera, &mut Camera, &mut Camera3d)>,
+) {
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+    let window_size = UVec2::new(window.physical_width(), window.physical_height());
+    if window_size.x == 0 || window_size.y == 0 {
+        // The window is minimized so there is nothing to lay out.
+        return;
+    }
+    let mut active_player_ids: Vec<u8> = active_players.iter().map(|player| player.id).collect();
+    active_player_ids.sort();
+    let layout = SplitScreenLayout {
+        active_player_ids: active_player_ids,
+        window_size: window_size,
+        two_player_split: Some(settings.two_player_split),
+    };
+    if *last_layout == layout {
+        return;
+    }
+    let viewports = calculate_viewports(
+        layout.active_player_ids.len(),
+        window_size,
+        settings.two_player_split,
+    );
+    for (player_camera, mut camera, mut camera_3d) in player_cameras.iter_mut() {
+        let slot = layout
+            .active_player_ids
+            .iter()
+            .position(|player_id| *player_id == player_camera.player_id);
+        match slot.and_then(|slot| viewports.get(slot).map(|viewport| (slot, viewport))) {
+            Some((slot, (physical_position, physical_size))) => {
+                camera.is_active = true;
+                camera.order = slot as isize;
+                camera.viewport = Some(Viewport {
+                    physical_position: *physical_position,
+                    physical_size: *physical_size,
+                    ..default()
+                });
+                // Only the first camera clears the window. Clearing in later cameras
+                // would wipe out the viewports rendered before them.
+                camera_3d.clear_color = if slot == 0 {
+                    ClearColorConfig::Default
+                } else {
+                    ClearColorConfig::None
+                };
+            }
+            None => camera.is_active = false,
+        }
+    }
+    *last_layout = layout;
+}
@@ -1,6 +1,8 @@
+use std::f32::consts::FRAC_PI_2;
+
 use bevy::prelude::{default, Camera3dBundle, Transform, Vec3};
 
-use super::components::PlayerCamera;
+use super::components::{OrbitCamera, PlayerCamera};
 
 // Create a player camera component.
 pub fn create_player_camera_component(player_id: u8) -> PlayerCamera {
@@ -9,6 +11,30 @@ pub fn create_player_camera_component(player_id: u8) -> PlayerCamera {
     };
 }
 
+/// Creates an orbit camera component that looks at the head height of a player
+/// character with the provided height mid point. The camera starts behind the player on
+/// the negative x side, slightly above them.
+pub fn create_orbit_camera_component(player_height_mid_point: f32) -> OrbitCamera {
+    return OrbitCamera {
+        yaw: -FRAC_PI_2,
+        pitch: 0.2,
+        distance: 25.0,
+        min_pitch: -0.3,
+        max_pitch: 1.4,
+        min_distance: 4.0,
+        max_distance: 40.0,
+        // The player character translation is at its height mid point, so the head is
+        // the mid point above it.
+        target_offset: Vec3::new(0.0, player_height_mid_point, 0.0),
+        yaw_speed: 2.0,
+        pitch_speed: 1.5,
+        zoom_speed: 15.0,
+        damping: 10.0,
+        collision_margin: 0.3,
+        current: None,
+    };
+}
+
 /// Creates a camera 3d bundle.
 pub fn create_camera_3d_bundle() -> Camera3dBundle {
     let look_location = Vec3::new(0.0, 0.0, 0.0);
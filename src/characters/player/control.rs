@@ -1,114 +1,108 @@
-use bevy::prelude::{
-    Axis, Component, Entity, Gamepad, GamepadAxis, Gamepads, Query, Res, Transform,
-};
+use bevy::prelude::{Component, Entity, Gamepad, Query, Res, Transform, With, Without};
 use bevy::time::Time;
 
-use super::camera::components::PlayerCamera;
+use crate::crafting::components::CraftingMenu;
+use crate::items::components::InventoryMenu;
+
+use super::super::animation::components::CharacterActivity;
+use super::super::components::{CharacterFacing, FacingMode, MovementIntent};
+use super::super::profession::components::ChoosingProfession;
+use super::camera::components::{OrbitCamera, PlayerCamera};
+use super::camera::control::orbit_camera_input;
 use super::entity::components::PlayerCharacter;
+use super::entity::control::move_entity;
+use super::input::components::{ActionState, ButtonAction, StickAction};
+use super::profile::components::{ChoosingProfile, CustomizingAppearance};
+
+/// The input devices that control a player.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputSource {
+    Gamepad(Gamepad),
+    KeyboardMouse,
+}
 
 /// A Bevy Engine component that is attached to an entity that represents the resource
 /// that controls that entity. Expected to be attached to entities that also have the
-/// Player component. Currently only supports Gamepad controls.
+/// Player component.
 #[derive(Component)]
 pub struct Controller {
-    pub gamepad: Gamepad,
-}
-
-/// Contains player information for determining player movement.
-pub struct PlayerInfo {
-    pub entity: Entity,
-    pub gamepad_id: usize,
-}
-
-/// Return the entity and gamepad id for the requested player.
-pub fn get_player_entity_and_gamepad_id(
-    player_id: u8,
-    players_with_controller: Query<(Entity, &PlayerCharacter, &Controller)>,
-) -> Option<PlayerInfo> {
-    let mut player_data = None;
-    for (player_entity, player, controller) in players_with_controller.iter() {
-        if player.id == player_id {
-            player_data = Some(PlayerInfo {
-                entity: player_entity,
-                gamepad_id: controller.gamepad.id,
-            });
-            // Found the info for the player of interest so stop.
-            break;
-        }
-    }
-    return player_data;
+    pub source: InputSource,
 }
 
-/// Returns the requested gamepad.
-pub fn get_gamepad(gamepad_id: usize, gamepads: Res<Gamepads>) -> Option<Gamepad> {
-    let mut gamepad_returned = None;
-    for gamepad in gamepads.iter() {
-        if gamepad.id == gamepad_id {
-            gamepad_returned = Some(gamepad);
-            // Found the gamepad we want so stop.
-            break;
-        }
-    }
-    return gamepad_returned;
-}
-
-/// Returns the entity for the camera associated with the provide player id.
-pub fn get_player_camera_entity(
-    player_id: u8,
-    player_cameras: Query<(Entity, &PlayerCamera)>,
-) -> Option<Entity> {
-    let mut entity_returned = None;
-    for (camera_entity, player_camera) in player_cameras.iter() {
-        if player_camera.player_id == player_id {
-            entity_returned = Some(camera_entity);
-            // Found the camera associated with the player we want so stop.
-            break;
-        }
-    }
-    return entity_returned;
+/// A Bevy Engine component that is attached to a player character entity to link it to
+/// the entity of the camera that follows it.
+#[derive(Component)]
+pub struct PlayerCameraLink {
+    pub camera: Entity,
 }
 
-/// This is a function wrapper for getting all the elements needed to apply
-/// transformations to both the entity and camera rotations for a player.
-pub fn player_gamepad_movement_wrapper(
-    player_id: u8,
-    movement_application_fn: fn(
-        Res<Axis<GamepadAxis>>,
-        Query<&mut Transform>,
-        Res<Time>,
-        PlayerInfo,
-        Gamepad,
-        Entity,
-    ),
-) -> impl Fn(
-    Res<Gamepads>,
-    Res<Axis<GamepadAxis>>,
-    Query<(Entity, &PlayerCharacter, &Controller)>,
-    Query<(Entity, &PlayerCamera)>,
-    Query<&mut Transform>,
-    Res<Time>,
+/// A system that moves every player character that has a controller, and orbits the
+/// camera linked to it, based on the actions of the player. Players face their camera
+/// direction while they hold the aim action, which lines up the tiles in front of them
+/// with the camera, and their movement direction otherwise. Players using a tool or
+/// sitting do not move. Players are found through the query so any number of players
+/// is handled by this one system.
+pub fn move_players_and_cameras(
+    timer: Res<Time>,
+    mut players: Query<
+        (
+            &ActionState,
+            &PlayerCameraLink,
+            &Transform,
+            &CharacterActivity,
+            &mut MovementIntent,
+            &mut CharacterFacing,
+        ),
+        (
+            With<PlayerCharacter>,
+            With<Controller>,
+            Without<ChoosingProfile>,
+            Without<CustomizingAppearance>,
+            Without<ChoosingProfession>,
+            Without<InventoryMenu>,
+            Without<CraftingMenu>,
+            Without<PlayerCamera>,
+        ),
+    >,
+    mut cameras: Query<(&mut OrbitCamera, &Transform), With<PlayerCamera>>,
 ) {
-    move |gamepads: Res<Gamepads>,
-          axes: Res<Axis<GamepadAxis>>,
-          players_with_controller: Query<(Entity, &PlayerCharacter, &Controller)>,
-          player_cameras: Query<(Entity, &PlayerCamera)>,
-          transforms: Query<&mut Transform>,
-          timer: Res<Time>| {
-        if let Some(player_info) =
-            get_player_entity_and_gamepad_id(player_id, players_with_controller)
-        {
-            if let Some(gamepad) = get_gamepad(player_info.gamepad_id, gamepads) {
-                if let Some(camera_entity) = get_player_camera_entity(player_id, player_cameras) {
-                    movement_application_fn(
-                        axes,
-                        transforms,
-                        timer,
-                        player_info,
-                        gamepad,
-                        camera_entity,
-                    );
-                }
+    for (action_state, camera_link, player_transform, activity, mut movement_intent, mut facing) in
+        players.iter_mut()
+    {
+        let facing_mode = if action_state.pressed(ButtonAction::Aim) {
+            FacingMode::CameraDirection
+        } else {
+            FacingMode::MovementDirection
+        };
+        if facing.mode != facing_mode {
+            facing.mode = facing_mode;
+        }
+        if let Ok((mut orbit_camera, camera_transform)) = cameras.get_mut(camera_link.camera) {
+            let zoom_input = action_state.value(ButtonAction::ZoomIn)
+                - action_state.value(ButtonAction::ZoomOut);
+            orbit_camera_input(
+                action_state.stick(StickAction::Look),
+                zoom_input,
+                &timer,
+                &mut orbit_camera,
+            );
+            // The tiles and node of a tool use are picked when it starts, so the player
+            // stays put until it is over. Sitting players stay on their seat until
+            // they stand up.
+            if !matches!(
+                activity,
+                CharacterActivity::None | CharacterActivity::Carrying
+            ) {
+                *movement_intent = MovementIntent::default();
+                continue;
             }
+            move_entity(
+                action_state.stick(StickAction::Move),
+                action_state.pressed(ButtonAction::Sprint),
+                camera_transform,
+                player_transform,
+                &mut movement_intent,
+            );
         }
     }
 }
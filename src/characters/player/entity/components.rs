@@ -1,4 +1,4 @@
-use bevy::prelude::Component;
+use bevy::prelude::{Component, Gamepad, Timer};
 
 /// A component to indicate if an entity is a player character.
 #[derive(Component)]
@@ -6,3 +6,12 @@ pub struct PlayerCharacter {
     pub id: u8,
     pub player_height_mid_point: f32,
 }
+
+/// A component to put on a player character entity whose controller disconnected. The
+/// player is kept in the game until the grace timer finishes so the same controller can
+/// reconnect and reclaim the player (for example after its batteries are replaced).
+#[derive(Component)]
+pub struct AwaitingReconnect {
+    pub gamepad: Gamepad,
+    pub grace_timer: Timer,
+}
@@ -4,72 +4,223 @@ use bevy::{
         GamepadConnectionEvent, GamepadInfo,
     },
     prelude::{
-        info, Axis, Commands, Entity, EventReader, Gamepad, GamepadAxis, GamepadAxisType, Gamepads,
-        Query, Res, Time, Transform, Vec3, Without,
+        info, Commands, DespawnRecursiveExt, Entity, EventReader, Gamepad, Input, KeyCode, Query,
+        Res, Time, Timer, TimerMode, Transform, Vec2, Vec3,
     },
 };
 
-use super::super::camera::components::PlayerCamera;
-use super::super::control::{player_gamepad_movement_wrapper, Controller, PlayerInfo};
-use super::components::PlayerCharacter;
+use super::super::super::components::MovementIntent;
+use super::super::control::{Controller, InputSource, PlayerCameraLink};
+use super::components::{AwaitingReconnect, PlayerCharacter};
+use super::start_up::spawn_player;
+
+/// The maximum number of players that can be in a game at the same time.
+pub const MAX_PLAYERS: u8 = 4;
+
+/// How long a player whose controller disconnected is kept in the game, waiting for a
+/// controller to reconnect, before the player is despawned.
+const RECONNECT_GRACE_PERIOD_SECONDS: f32 = 30.0;
+
+/// The key a player using the keyboard and mouse presses to join the game.
+const KEYBOARD_MOUSE_JOIN_KEY: KeyCode = KeyCode::Return;
+
+/// The distance between the spawn locations of consecutive player ids so players do not
+/// spawn on top of each other.
+const PLAYER_SPAWN_SPACING: f32 = 3.0;
+
+/// The connection status of a player in the roster.
+enum RosterStatus {
+    Controlled(InputSource),
+    AwaitingReconnect(Gamepad),
+    Uncontrolled,
+}
+
+/// A player in the roster.
+struct RosterEntry {
+    entity: Entity,
+    player_id: u8,
+    status: RosterStatus,
+}
+
+/// A snapshot of the players in the game that is used while handling the connection
+/// events of a frame. Commands are not applied until after the system runs, so the
+/// roster is updated as players are spawned, reclaimed or parked to keep multiple
+/// events in the same frame from claiming the same player or player id.
+struct PlayerRoster {
+    entries: Vec<RosterEntry>,
+}
+
+impl PlayerRoster {
+    /// Creates the roster from the players in the game.
+    fn from_players(players: &PlayerRosterQuery) -> Self {
+        let mut roster = PlayerRoster {
+            entries: Vec::new(),
+        };
+        for (player_entity, player, controller, awaiting_reconnect) in players.iter() {
+            let status = match (controller, awaiting_reconnect) {
+                (Some(controller), _) => RosterStatus::Controlled(controller.source),
+                (None, Some(awaiting_reconnect)) => {
+                    RosterStatus::AwaitingReconnect(awaiting_reconnect.gamepad)
+                }
+                (None, None) => RosterStatus::Uncontrolled,
+            };
+            roster.entries.push(RosterEntry {
+                entity: player_entity,
+                player_id: player.id,
+                status: status,
+            });
+        }
+        return roster;
+    }
+
+    /// Returns the index of the player currently controlled by the input source.
+    fn find_controlled(&self, source: InputSource) -> Option<usize> {
+        return self.entries.iter().position(|entry| match entry.status {
+            RosterStatus::Controlled(controlling_source) => controlling_source == source,
+            _ => false,
+        });
+    }
+
+    /// Returns the index of the player that should be reclaimed by a connecting input
+    /// source. A player that was last controlled by the same gamepad is preferred,
+    /// otherwise the waiting player with the lowest id is used.
+    fn find_reclaimable(&self, source: InputSource) -> Option<usize> {
+        let mut reclaimable: Option<usize> = None;
+        for (index, entry) in self.entries.iter().enumerate() {
+            if let RosterStatus::AwaitingReconnect(last_gamepad) = entry.status {
+                if source == InputSource::Gamepad(last_gamepad) {
+                    return Some(index);
+                }
+                let is_lower_id = match reclaimable {
+                    Some(current) => entry.player_id < self.entries[current].player_id,
+                    None => true,
+                };
+                if is_lower_id {
+                    reclaimable = Some(index);
+                }
+            }
+        }
+        return reclaimable;
+    }
+
+    /// Returns the lowest player id that is not in use.
+    fn next_free_player_id(&self) -> Option<u8> {
+        return (0..MAX_PLAYERS).find(|player_id| {
+            !self
+                .entries
+                .iter()
+                .any(|entry| entry.player_id == *player_id)
+        });
+    }
+}
+
+/// The query used to build the player roster.
+type PlayerRosterQuery<'world, 'state> = Query<
+    'world,
+    'state,
+    (
+        Entity,
+        &'static PlayerCharacter,
+        Option<&'static Controller>,
+        Option<&'static AwaitingReconnect>,
+    ),
+>;
+
+/// Returns the location to spawn the player with the provided id.
+fn player_spawn_location(player_id: u8) -> Vec3 {
+    return Vec3::new(0.0, 0.0, PLAYER_SPAWN_SPACING * player_id as f32);
+}
+
+/// Connects an input source to a player. If a player is waiting for their controller to
+/// reconnect, the input source reclaims that player. Otherwise a new player, and their
+/// camera, is spawned with the next free player id and the input source is attached to
+/// it. This lets a player drop in at any time.
+fn connect_input_source_to_player(
+    commands: &mut Commands,
+    roster: &mut PlayerRoster,
+    source: InputSource,
+    source_name: &str,
+) {
+    if roster.find_controlled(source).is_some() {
+        // The input source is already controlling a player so there is nothing to do.
+        return;
+    }
+    if let Some(index) = roster.find_reclaimable(source) {
+        let entry = &mut roster.entries[index];
+        commands
+            .entity(entry.entity)
+            .remove::<AwaitingReconnect>()
+            .insert(Controller { source: source });
+        entry.status = RosterStatus::Controlled(source);
+        info!(
+            "{} reclaimed player with id {}",
+            source_name, entry.player_id
+        );
+    } else if let Some(player_id) = roster.next_free_player_id() {
+        let player_entity = spawn_player(commands, player_id, player_spawn_location(player_id));
+        commands
+            .entity(player_entity)
+            .insert(Controller { source: source });
+        roster.entries.push(RosterEntry {
+            entity: player_entity,
+            player_id: player_id,
+            status: RosterStatus::Controlled(source),
+        });
+        info!("{} joined as player with id {}", source_name, player_id);
+    } else {
+        info!(
+            "{} tried to join but all {} players are taken",
+            source_name, MAX_PLAYERS
+        );
+    }
+}
 
 /// A function that is run on GamepadConnection.Connected GamepadConnectionEvents to
-/// add the connected gamepad to the first player in the player_entities_without_controllers
-/// query.
+/// connect the gamepad to a player.
 /// This function also works if the gamepad is connected to the machine before the
-/// program starts because on program startup, the player character is added before
-/// checking for connected gamepads (the check for connected gamepads when the program
-/// starts issues a connection event for all gamepads connected with the machine before
-/// the program started).
-///
-/// TODO: It probably makes sense to change the logic here. The current logic expects
-/// a player to already be created, which is fine for this early development. But, in
-/// the long run, it probably makes sense for a controller connection event to spawn a
-/// player entity. That way a player can drop in rather than needing to pick the number
-/// of players when launching the game.
-/// A consequence of this would probably needing to spawn a generic player that loads
-/// from saved data or created from new. Another consequence of this would be
-/// potentially trying to connect to an existing player for an in-progress game (like
-/// the gamepad dies and connecting a new one to keep playing).
+/// program starts because the check for connected gamepads when the program starts
+/// issues a connection event for all gamepads connected with the machine before the
+/// program started.
 fn connect_controller_to_player(
     commands: &mut Commands,
+    roster: &mut PlayerRoster,
     connection_event: &GamepadConnectionEvent,
     gamepad_info: &GamepadInfo,
-    player_entities_without_controllers: &Query<(Entity, &PlayerCharacter), Without<Controller>>,
 ) {
-    if let Some((player_entity, player)) = player_entities_without_controllers.into_iter().next() {
-        commands.entity(player_entity).insert(Controller {
-            gamepad: connection_event.gamepad,
-        });
-        info!(
-            "Gamepad {} of id {} assigned to player with id {}",
-            gamepad_info.name, connection_event.gamepad.id, player.id
-        );
-    }
+    let gamepad = connection_event.gamepad;
+    connect_input_source_to_player(
+        commands,
+        roster,
+        InputSource::Gamepad(gamepad),
+        &format!("Gamepad {} of id {}", gamepad_info.name, gamepad.id),
+    );
 }
 
 /// A function that is run on GamepadConnection.Disconnected GamepadConnectionEvents to
-/// remove the disconnected gamepad from the first player in the
-/// player_entities_with_controllers query that has a gamepad with an id that matches the id of
-/// the gamepad that disconnected.
-///
-/// TODO: Make this an updated corollary to the connected function. This function would
-/// despawn the player for the gamepad that was disconnected. There also might need to
-/// be some logic to deal with gamepads that disconnect due to a lose of power.
+/// remove the disconnected gamepad from the player it controls. The player is not
+/// despawned right away, instead it waits for a controller to reconnect for a grace
+/// period. This deals with gamepads that disconnect due to a loss of power.
 fn disconnect_controller_from_player(
     commands: &mut Commands,
+    roster: &mut PlayerRoster,
     connection_event: &GamepadConnectionEvent,
-    player_entities_with_controllers: &Query<(Entity, &PlayerCharacter, &Controller)>,
 ) {
-    for (player_entity, player, controller) in player_entities_with_controllers {
-        if controller.gamepad.id == connection_event.gamepad.id {
-            commands.entity(player_entity).remove::<Controller>();
-            info!(
-                "Controller with gamepad of id {} removed from player with id {}",
-                connection_event.gamepad.id, player.id
-            );
-            break;
-        }
+    let gamepad = connection_event.gamepad;
+    if let Some(index) = roster.find_controlled(InputSource::Gamepad(gamepad)) {
+        let entry = &mut roster.entries[index];
+        commands
+            .entity(entry.entity)
+            .remove::<Controller>()
+            .insert(MovementIntent::default())
+            .insert(AwaitingReconnect {
+                gamepad: gamepad,
+                grace_timer: Timer::from_seconds(RECONNECT_GRACE_PERIOD_SECONDS, TimerMode::Once),
+            });
+        entry.status = RosterStatus::AwaitingReconnect(gamepad);
+        info!(
+            "Controller with gamepad of id {} removed from player with id {}, waiting {} seconds for it to reconnect",
+            gamepad.id, entry.player_id, RECONNECT_GRACE_PERIOD_SECONDS
+        );
     }
 }
 
@@ -78,108 +229,115 @@ fn disconnect_controller_from_player(
 pub fn gamepad_connection_events(
     mut commands: Commands,
     mut connection_events: EventReader<GamepadConnectionEvent>,
-    player_entities_with_controllers: Query<(Entity, &PlayerCharacter, &Controller)>,
-    player_entities_without_controllers: Query<(Entity, &PlayerCharacter), Without<Controller>>,
+    players: PlayerRosterQuery,
 ) {
+    if connection_events.is_empty() {
+        return;
+    }
+    let mut roster = PlayerRoster::from_players(&players);
     for connection_event in connection_events.iter() {
         match &connection_event.connection {
             Connected(gamepad_info) => connect_controller_to_player(
                 &mut commands,
+                &mut roster,
                 connection_event,
                 gamepad_info,
-                &player_entities_without_controllers,
-            ),
-            Disconnected => disconnect_controller_from_player(
-                &mut commands,
-                connection_event,
-                &player_entities_with_controllers,
             ),
+            Disconnected => {
+                disconnect_controller_from_player(&mut commands, &mut roster, connection_event)
+            }
         }
     }
 }
 
-/// Gets the axis state of the left stick of the gamepad and uses it to calculate a
-/// displacement vector for the frame. This displacement vector is relative to the
-/// camera forward vector.
-fn calculate_displacement_vector(
-    gamepad: Gamepad,
-    axes: Res<Axis<GamepadAxis>>,
-    camera_transform: &Transform,
-    player_transform: &Transform,
-    timer: Res<Time>,
-) -> Option<Vec3> {
-    let mut displacement_vector = None;
-    let speed = 10.0;
-    let horizontal_axis = GamepadAxis::new(gamepad, GamepadAxisType::LeftStickX);
-    let forward_axis = GamepadAxis::new(gamepad, GamepadAxisType::LeftStickY);
-    if let (Some(horizontal_input), Some(forward_input)) =
-        (axes.get(horizontal_axis), axes.get(forward_axis))
-    {
-        let right = camera_transform.right();
-        // The camera forward cannot be used here as that means the player character
-        // will go forward in the direction the camera is pointing. This means that the
-        // player character is not constrained to the "ground". To constrain the
-        // player character to the same plane it was on before by using using its up
-        // and aligning the camera right with the player right.
-        // This generates a z axis where the positive direction is toward the camera.
-        let forward = right.cross(player_transform.up());
-        let right_displacement = right * horizontal_input;
-        // The desired behavior is that up on the left stick moves the player character
-        // entity away from the camera so we need to flip the direction by using the
-        // negative of the left stick Y as axis.
-        let forward_displacement = forward * forward_input;
-        let mut combined_displacement = right_displacement + forward_displacement * -1.0;
-        combined_displacement *= speed;
-        combined_displacement.clamp_length_max(speed);
-        combined_displacement *= timer.delta_seconds();
-        displacement_vector = Some(combined_displacement);
+/// A system that lets a player using the keyboard and mouse drop in by pressing the
+/// join key.
+pub fn keyboard_mouse_join(
+    mut commands: Commands,
+    keys: Res<Input<KeyCode>>,
+    players: PlayerRosterQuery,
+) {
+    if keys.just_pressed(KEYBOARD_MOUSE_JOIN_KEY) {
+        let mut roster = PlayerRoster::from_players(&players);
+        connect_input_source_to_player(
+            &mut commands,
+            &mut roster,
+            InputSource::KeyboardMouse,
+            "Keyboard and mouse",
+        );
     }
-    return displacement_vector;
 }
 
-/// Calculate a displacement based on the controller axes and apply that to the player
-/// character entity.
-fn move_entity(
-    axes: Res<Axis<GamepadAxis>>,
-    mut transforms: Query<&mut Transform>,
+/// A system that counts down the grace period of players waiting for their controller
+/// to reconnect. Players whose grace period ran out are despawned along with their
+/// camera.
+pub fn despawn_disconnected_players(
+    mut commands: Commands,
     timer: Res<Time>,
-    player_info: PlayerInfo,
-    gamepad: Gamepad,
-    camera_entity: Entity,
+    mut waiting_players: Query<(
+        Entity,
+        &PlayerCharacter,
+        &PlayerCameraLink,
+        &mut AwaitingReconnect,
+    )>,
 ) {
-    let mut displacement = None;
-    if let Ok(camera_transform) = transforms.get(camera_entity) {
-        if let Ok(player_transform) = transforms.get(player_info.entity) {
-            displacement = calculate_displacement_vector(
-                gamepad,
-                axes,
-                &camera_transform,
-                &player_transform,
-                timer,
-            )
-        }
-    }
-    if let Ok(mut player_transform) = transforms.get_mut(player_info.entity) {
-        match displacement {
-            Some(vec) => player_transform.translation += vec,
-            None => (),
+    for (player_entity, player, camera_link, mut awaiting_reconnect) in waiting_players.iter_mut() {
+        if awaiting_reconnect
+            .grace_timer
+            .tick(timer.delta())
+            .just_finished()
+        {
+            commands.entity(player_entity).despawn_recursive();
+            commands.entity(camera_link.camera).despawn_recursive();
+            info!(
+                "Player with id {} despawned after their controller did not reconnect",
+                player.id
+            );
         }
     }
 }
 
-/// Generates a system to move a player entity's transform based on the input from the
-/// player's gamepad's left stick.
-pub fn generate_move_player_system(
-    player_id: u8,
-) -> impl Fn(
-    Res<Gamepads>,
-    Res<Axis<GamepadAxis>>,
-    Query<(Entity, &PlayerCharacter, &Controller)>,
-    Query<(Entity, &PlayerCamera)>,
-    Query<&mut Transform>,
-    Res<Time>,
+/// Uses the move input to calculate a movement direction for the player character. This
+/// direction is relative to the camera forward vector and its length is how far the
+/// move input is pushed, up to 1.
+fn calculate_move_direction(
+    move_input: Vec2,
+    camera_transform: &Transform,
+    player_transform: &Transform,
+) -> Vec3 {
+    let right = camera_transform.right();
+    // The camera forward cannot be used here as that means the player character
+    // will go forward in the direction the camera is pointing. This means that the
+    // player character is not constrained to the "ground". To constrain the
+    // player character to the same plane it was on before by using using its up
+    // and aligning the camera right with the player right.
+    // This generates a z axis where the positive direction is toward the camera.
+    let forward = right.cross(player_transform.up());
+    let right_direction = right * move_input.x;
+    // The desired behavior is that up on the move input moves the player character
+    // entity away from the camera so we need to flip the direction by using the
+    // negative of the move input Y as axis.
+    let forward_direction = forward * move_input.y;
+    let combined_direction = right_direction + forward_direction * -1.0;
+    // Diagonal input can be longer than 1, so it would be faster than cardinal input
+    // without clamping.
+    return combined_direction.clamp_length_max(1.0);
+}
+
+/// Calculate a movement direction based on the move input and record it in the
+/// movement intent of the player character entity, along with whether the player wants
+/// to sprint. The movement model and character controller move the player with it and
+/// the player turns to face it.
+pub fn move_entity(
+    move_input: Vec2,
+    sprint: bool,
+    camera_transform: &Transform,
+    player_transform: &Transform,
+    movement_intent: &mut MovementIntent,
 ) {
-    return player_gamepad_movement_wrapper(player_id, move_entity);
+    movement_intent.direction =
+        calculate_move_direction(move_input, camera_transform, player_transform);
+    movement_intent.sprint = sprint;
 }
 
 // fn gamepad_events(
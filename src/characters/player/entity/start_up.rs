@@ -1,27 +1,53 @@
+use std::f32::consts::PI;
+
 use bevy::prelude::{
-    default, shape, Assets, BuildChildren, Bundle, Color, Commands, Mesh, PbrBundle, ResMut,
-    StandardMaterial, Transform, Vec3,
+    default, Bundle, Commands, Entity, SpatialBundle, Transform, UiCameraConfig, Vec3,
 };
 
-use super::super::super::components::Name;
-use super::super::camera::start_up::{create_camera_3d_bundle, create_player_camera_component};
+use crate::collision::kinematic::{CapsuleShape, KinematicSettings};
+use crate::items::components::Hotbar;
+use crate::items::inventory::Inventory;
+
+use super::super::super::animation::components::{AnimationController, CharacterActivity};
+use super::super::super::appearance::components::Appearance;
+use super::super::super::components::{
+    CharacterController, CharacterFacing, FacingMode, MovementIntent, MovementModel, Name, Stamina,
+};
+use super::super::super::profession::components::QuestLines;
+use super::super::super::tuning::load_movement_tuning;
+use super::super::camera::start_up::{
+    create_camera_3d_bundle, create_orbit_camera_component, create_player_camera_component,
+};
+use super::super::control::PlayerCameraLink;
+use super::super::input::components::ActionState;
+use super::super::input::persistence::load_input_profile;
 use super::components::PlayerCharacter;
 
+/// The number of slots of a player inventory, including the hotbar.
+const PLAYER_INVENTORY_SIZE: usize = 24;
+
 /// A component bundle used to initialize a player character.
 #[derive(Bundle)]
 struct PlayerInitBundle {
     character_type: PlayerCharacter,
     name: Name,
-    renderer_representation: PbrBundle,
+    facing: CharacterFacing,
+    movement_intent: MovementIntent,
+    character_controller: CharacterController,
+    movement_model: MovementModel,
+    stamina: Stamina,
+    appearance: Appearance,
+    animation_controller: AnimationController,
+    activity: CharacterActivity,
+    quest_lines: QuestLines,
+    inventory: Inventory,
+    hotbar: Hotbar,
+    spatial: SpatialBundle,
 }
 
-/// Creates the bundle to add the player entity.
-fn create_player_init_bundle(
-    player_id: u8,
-    spawn_location: Vec3,
-    meshes: &mut ResMut<Assets<Mesh>>,
-    materials: &mut ResMut<Assets<StandardMaterial>>,
-) -> PlayerInitBundle {
+/// Creates the bundle to add the player entity. The look of the player is assembled
+/// from the parts of its appearance.
+fn create_player_init_bundle(player_id: u8, spawn_location: Vec3) -> PlayerInitBundle {
     let player_height_mid_point = 1.0;
     let initial_player_translation = spawn_location + Vec3::new(0.0, player_height_mid_point, 0.0);
     let bundle = PlayerInitBundle {
@@ -29,43 +55,70 @@ fn create_player_init_bundle(
             id: player_id,
             player_height_mid_point: player_height_mid_point,
         },
-        name: Name("Player1".to_string()),
-        renderer_representation: PbrBundle {
-            mesh: meshes.add(
-                Mesh::try_from(shape::Icosphere {
-                    radius: player_height_mid_point,
-                    subdivisions: 32,
-                })
-                .unwrap(),
-            ),
-            material: materials.add(StandardMaterial {
-                base_color: Color::hex("#71daff").unwrap(),
-                ..default()
-            }),
-            transform: Transform::from_translation(initial_player_translation),
+        name: Name(format!("Player {}", player_id + 1)),
+        facing: CharacterFacing {
+            mode: FacingMode::MovementDirection,
+            turn_rate: 4.0 * PI,
+        },
+        movement_intent: MovementIntent::default(),
+        character_controller: CharacterController {
+            capsule: CapsuleShape {
+                radius: 0.45,
+                half_height: player_height_mid_point,
+            },
+            settings: KinematicSettings {
+                max_slope_angle: PI / 4.0,
+                step_height: 0.4,
+                snap_distance: 0.5,
+            },
+            gravity: 20.0,
+            max_fall_speed: 50.0,
+            vertical_speed: 0.0,
+            grounded: false,
+            ground_normal: Vec3::Y,
+        },
+        movement_model: MovementModel {
+            tuning: load_movement_tuning("player"),
             ..default()
         },
+        stamina: Stamina::full(100.0),
+        appearance: Appearance::default(),
+        animation_controller: AnimationController::default(),
+        activity: CharacterActivity::None,
+        quest_lines: QuestLines::default(),
+        inventory: Inventory::new(PLAYER_INVENTORY_SIZE),
+        hotbar: Hotbar::default(),
+        spatial: SpatialBundle::from_transform(Transform::from_translation(
+            initial_player_translation,
+        )),
     };
     return bundle;
 }
 
-/// Generates a system that adds a player and their camera with the id provided.
-/// The system also takes in a location at which to spawn the player and associated
-/// camera.
-pub fn generate_add_player_system(
-    player_id: u8,
-    spawn_location: Vec3,
-) -> impl Fn(Commands, ResMut<Assets<Mesh>>, ResMut<Assets<StandardMaterial>>) {
-    move |mut commands: Commands,
-          mut meshes: ResMut<Assets<Mesh>>,
-          mut materials: ResMut<Assets<StandardMaterial>>| {
-        let player_init_bundle =
-            create_player_init_bundle(player_id, spawn_location, &mut meshes, &mut materials);
-        let player_entity = commands.spawn(player_init_bundle).id();
-        let player_camera_component = create_player_camera_component(player_id);
-        let mut camera_entity_commands = commands.spawn(player_camera_component);
-        camera_entity_commands.set_parent(player_entity);
-        let camera_3d_bundle = create_camera_3d_bundle();
-        camera_entity_commands.insert(camera_3d_bundle);
-    }
+/// Spawns a player and their orbit camera with the id provided at the spawn location.
+/// The camera is not a child of the player so it does not turn with the player, instead
+/// the player is linked to the camera through a PlayerCameraLink. The saved input
+/// profile of the player is loaded onto the player until they choose a player profile.
+/// Returns the player entity.
+pub fn spawn_player(commands: &mut Commands, player_id: u8, spawn_location: Vec3) -> Entity {
+    let player_init_bundle = create_player_init_bundle(player_id, spawn_location);
+    let orbit_camera =
+        create_orbit_camera_component(player_init_bundle.character_type.player_height_mid_point);
+    let player_entity = commands.spawn(player_init_bundle).id();
+    let camera_entity = commands
+        .spawn((
+            create_player_camera_component(player_id),
+            orbit_camera,
+            create_camera_3d_bundle(),
+            UiCameraConfig { show_ui: false },
+        ))
+        .id();
+    commands.entity(player_entity).insert((
+        PlayerCameraLink {
+            camera: camera_entity,
+        },
+        load_input_profile(player_id),
+        ActionState::default(),
+    ));
+    return player_entity;
 }
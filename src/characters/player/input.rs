@@ -0,0 +1,5 @@
+pub(crate) mod bindings;
+pub(crate) mod components;
+pub(crate) mod control;
+pub(crate) mod controls_menu;
+pub(crate) mod persistence;
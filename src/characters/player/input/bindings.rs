@@ -0,0 +1,560 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use bevy::prelude::{
+    info, Commands, Entity, EventReader, GamepadAxisType, GamepadButton, GamepadButtonType, Input,
+    KeyCode, MouseButton, Query, Res, Resource,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::storage::load_ron_or_default;
+
+use super::super::control::{Controller, InputSource};
+use super::components::{ButtonAction, CapturingBinding, InputProfile, StickAction};
+
+/// The file the default input bindings are loaded from.
+const INPUT_BINDINGS_PATH: &str = "config/input_bindings.ron";
+
+/// An input that drives a stick action.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub enum StickBinding {
+    /// A gamepad stick made of the two axes.
+    GamepadStick {
+        x: GamepadAxisType,
+        y: GamepadAxisType,
+    },
+    /// Four keys that push the stick in each direction.
+    Keys {
+        up: KeyCode,
+        down: KeyCode,
+        left: KeyCode,
+        right: KeyCode,
+    },
+    /// The mouse motion. The sensitivity is how much stick deflection one pixel of motion
+    /// per second gives.
+    MouseMotion { sensitivity: f32 },
+}
+
+/// An input that drives a button action.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ButtonBinding {
+    GamepadButton(GamepadButtonType),
+    Key(KeyCode),
+    Mouse(MouseButton),
+}
+
+impl ButtonBinding {
+    /// Returns the short name of the input shown in on-screen prompts. The face buttons
+    /// of gamepads are named like the buttons of the common controller layout.
+    pub fn label(&self) -> String {
+        return match self {
+            ButtonBinding::GamepadButton(GamepadButtonType::South) => "A".to_string(),
+            ButtonBinding::GamepadButton(GamepadButtonType::East) => "B".to_string(),
+            ButtonBinding::GamepadButton(GamepadButtonType::West) => "X".to_string(),
+            ButtonBinding::GamepadButton(GamepadButtonType::North) => "Y".to_string(),
+            ButtonBinding::GamepadButton(button) => format!("{:?}", button),
+            ButtonBinding::Key(key) => format!("{:?}", key),
+            ButtonBinding::Mouse(mouse_button) => format!("Mouse {:?}", mouse_button),
+        };
+    }
+}
+
+/// The bindings of every action for one kind of input source.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct BindingSet {
+    pub sticks: BTreeMap<StickAction, Vec<StickBinding>>,
+    pub buttons: BTreeMap<ButtonAction, Vec<ButtonBinding>>,
+}
+
+/// The button actions a player rebound, one map for gamepads and one for the keyboard
+/// and mouse. Only the rebound actions are kept, so actions added to the default
+/// bindings later are bound for players who rebound something before.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct BindingOverrides {
+    #[serde(default)]
+    pub gamepad: BTreeMap<ButtonAction, Vec<ButtonBinding>>,
+    #[serde(default)]
+    pub keyboard_mouse: BTreeMap<ButtonAction, Vec<ButtonBinding>>,
+}
+
+impl BindingOverrides {
+    /// Returns the overrides of the input source.
+    pub fn for_source(&self, source: InputSource) -> &BTreeMap<ButtonAction, Vec<ButtonBinding>> {
+        return match source {
+            InputSource::Gamepad(_) => &self.gamepad,
+            InputSource::KeyboardMouse => &self.keyboard_mouse,
+        };
+    }
+
+    fn for_source_mut(
+        &mut self,
+        source: InputSource,
+    ) -> &mut BTreeMap<ButtonAction, Vec<ButtonBinding>> {
+        return match source {
+            InputSource::Gamepad(_) => &mut self.gamepad,
+            InputSource::KeyboardMouse => &mut self.keyboard_mouse,
+        };
+    }
+}
+
+/// The bindings from inputs to player actions, one set for gamepads and one for the
+/// keyboard and mouse. As a Bevy Engine resource it holds the default bindings, which
+/// players use for every action they did not rebind in their input profile.
+#[derive(Resource, Clone, Serialize, Deserialize)]
+pub struct InputBindings {
+    pub gamepad: BindingSet,
+    pub keyboard_mouse: BindingSet,
+}
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        let gamepad = BindingSet {
+            sticks: BTreeMap::from([
+                (
+                    StickAction::Move,
+                    vec![StickBinding::GamepadStick {
+                        x: GamepadAxisType::LeftStickX,
+                        y: GamepadAxisType::LeftStickY,
+                    }],
+                ),
+                (
+                    StickAction::Look,
+                    vec![StickBinding::GamepadStick {
+                        x: GamepadAxisType::RightStickX,
+                        y: GamepadAxisType::RightStickY,
+                    }],
+                ),
+            ]),
+            buttons: BTreeMap::from([
+                (
+                    ButtonAction::Sprint,
+                    vec![ButtonBinding::GamepadButton(GamepadButtonType::LeftThumb)],
+                ),
+                (
+                    ButtonAction::Aim,
+                    vec![ButtonBinding::GamepadButton(GamepadButtonType::East)],
+                ),
+                (
+                    ButtonAction::ZoomIn,
+                    vec![
+                        ButtonBinding::GamepadButton(GamepadButtonType::RightTrigger2),
+                        ButtonBinding::GamepadButton(GamepadButtonType::RightTrigger),
+                    ],
+                ),
+                (
+                    ButtonAction::ZoomOut,
+                    vec![
+                        ButtonBinding::GamepadButton(GamepadButtonType::LeftTrigger2),
+                        ButtonBinding::GamepadButton(GamepadButtonType::LeftTrigger),
+                    ],
+                ),
+                (
+                    ButtonAction::Interact,
+                    vec![ButtonBinding::GamepadButton(GamepadButtonType::South)],
+                ),
+                (
+                    ButtonAction::UseTool,
+                    vec![ButtonBinding::GamepadButton(GamepadButtonType::West)],
+                ),
+                (
+                    ButtonAction::OpenInventory,
+                    vec![ButtonBinding::GamepadButton(GamepadButtonType::North)],
+                ),
+                (
+                    ButtonAction::NextHotbarSlot,
+                    vec![ButtonBinding::GamepadButton(GamepadButtonType::DPadRight)],
+                ),
+                (
+                    ButtonAction::PreviousHotbarSlot,
+                    vec![ButtonBinding::GamepadButton(GamepadButtonType::DPadLeft)],
+                ),
+                (
+                    ButtonAction::ChooseProfession,
+                    vec![ButtonBinding::GamepadButton(GamepadButtonType::Select)],
+                ),
+                (
+                    ButtonAction::Pause,
+                    vec![ButtonBinding::GamepadButton(GamepadButtonType::Start)],
+                ),
+                (
+                    ButtonAction::QuickSave,
+                    vec![ButtonBinding::GamepadButton(GamepadButtonType::DPadUp)],
+                ),
+                (
+                    ButtonAction::QuickLoad,
+                    vec![ButtonBinding::GamepadButton(GamepadButtonType::DPadDown)],
+                ),
+            ]),
+        };
+        let keyboard_mouse = BindingSet {
+            sticks: BTreeMap::from([
+                (
+                    StickAction::Move,
+                    vec![StickBinding::Keys {
+                        up: KeyCode::W,
+                        down: KeyCode::S,
+                        left: KeyCode::A,
+                        right: KeyCode::D,
+                    }],
+                ),
+                (
+                    StickAction::Look,
+                    vec![
+                        StickBinding::MouseMotion { sensitivity: 0.005 },
+                        StickBinding::Keys {
+                            up: KeyCode::Up,
+                            down: KeyCode::Down,
+                            left: KeyCode::Left,
+                            right: KeyCode::Right,
+                        },
+                    ],
+                ),
+            ]),
+            buttons: BTreeMap::from([
+                (
+                    ButtonAction::Sprint,
+                    vec![ButtonBinding::Key(KeyCode::LShift)],
+                ),
+                (
+                    ButtonAction::Aim,
+                    vec![ButtonBinding::Mouse(MouseButton::Right)],
+                ),
+                (ButtonAction::ZoomIn, vec![ButtonBinding::Key(KeyCode::R)]),
+                (ButtonAction::ZoomOut, vec![ButtonBinding::Key(KeyCode::F)]),
+                (ButtonAction::Interact, vec![ButtonBinding::Key(KeyCode::E)]),
+                (
+                    ButtonAction::UseTool,
+                    vec![ButtonBinding::Mouse(MouseButton::Left)],
+                ),
+                (
+                    ButtonAction::OpenInventory,
+                    vec![ButtonBinding::Key(KeyCode::Tab)],
+                ),
+                (
+                    ButtonAction::NextHotbarSlot,
+                    vec![ButtonBinding::Key(KeyCode::X)],
+                ),
+                (
+                    ButtonAction::PreviousHotbarSlot,
+                    vec![ButtonBinding::Key(KeyCode::Z)],
+                ),
+                (
+                    ButtonAction::ChooseProfession,
+                    vec![ButtonBinding::Key(KeyCode::P)],
+                ),
+                (
+                    ButtonAction::Pause,
+                    vec![ButtonBinding::Key(KeyCode::Escape)],
+                ),
+                (
+                    ButtonAction::QuickSave,
+                    vec![ButtonBinding::Key(KeyCode::F5)],
+                ),
+                (
+                    ButtonAction::QuickLoad,
+                    vec![ButtonBinding::Key(KeyCode::F9)],
+                ),
+            ]),
+        };
+        return InputBindings {
+            gamepad: gamepad,
+            keyboard_mouse: keyboard_mouse,
+        };
+    }
+}
+
+impl InputBindings {
+    /// Returns the bindings used for the input source.
+    pub fn binding_set(&self, source: InputSource) -> &BindingSet {
+        return match source {
+            InputSource::Gamepad(_) => &self.gamepad,
+            InputSource::KeyboardMouse => &self.keyboard_mouse,
+        };
+    }
+
+    /// Returns the inputs bound to the button action for the input source, the ones of
+    /// the input profile when the player rebound the action and the default ones
+    /// otherwise.
+    pub fn button_bindings<'a>(
+        &'a self,
+        input_profile: &'a InputProfile,
+        source: InputSource,
+        action: ButtonAction,
+    ) -> &'a [ButtonBinding] {
+        return input_profile
+            .binding_overrides
+            .for_source(source)
+            .get(&action)
+            .or_else(|| self.binding_set(source).buttons.get(&action))
+            .map_or(&[], |bindings| bindings.as_slice());
+    }
+
+    /// Returns the label of the first input bound to the button action for the input
+    /// source with the input profile, or None when the action is not bound.
+    pub fn button_label(
+        &self,
+        input_profile: &InputProfile,
+        source: InputSource,
+        action: ButtonAction,
+    ) -> Option<String> {
+        return self
+            .button_bindings(input_profile, source, action)
+            .first()
+            .map(|binding| binding.label());
+    }
+
+    /// Binds the button action of the input source to only the provided binding in the
+    /// input profile. The binding is removed from any other action so one input does not
+    /// trigger two actions, which overrides those actions too.
+    pub fn rebind_button(
+        &self,
+        input_profile: &mut InputProfile,
+        source: InputSource,
+        action: ButtonAction,
+        binding: ButtonBinding,
+    ) {
+        for other_action in ButtonAction::ALL {
+            let bindings = self.button_bindings(input_profile, source, other_action);
+            if other_action == action || !bindings.contains(&binding) {
+                continue;
+            }
+            let remaining_bindings: Vec<ButtonBinding> = bindings
+                .iter()
+                .copied()
+                .filter(|existing_binding| *existing_binding != binding)
+                .collect();
+            input_profile
+                .binding_overrides
+                .for_source_mut(source)
+                .insert(other_action, remaining_bindings);
+        }
+        input_profile
+            .binding_overrides
+            .for_source_mut(source)
+            .insert(action, vec![binding]);
+    }
+}
+
+/// Loads the default input bindings from the config file. The built in bindings are used
+/// when there is no config file.
+pub fn load_input_bindings() -> InputBindings {
+    return load_ron_or_default(Path::new(INPUT_BINDINGS_PATH), "the input bindings");
+}
+
+/// An event to ask for the next input of a player to be bound to the button action.
+pub struct RebindButtonAction {
+    pub player: Entity,
+    pub action: ButtonAction,
+}
+
+/// A system that starts capturing the next input of the players that asked to rebind a
+/// button action.
+pub fn start_rebinding(mut commands: Commands, mut rebind_events: EventReader<RebindButtonAction>) {
+    for rebind_event in rebind_events.iter() {
+        commands
+            .entity(rebind_event.player)
+            .insert(CapturingBinding {
+                action: rebind_event.action,
+            });
+    }
+}
+
+/// Returns the first input the source pressed this frame as a button binding.
+fn find_pressed_binding(
+    source: InputSource,
+    keys: &Input<KeyCode>,
+    mouse_buttons: &Input<MouseButton>,
+    gamepad_buttons: &Input<GamepadButton>,
+) -> Option<ButtonBinding> {
+    return match source {
+        InputSource::Gamepad(gamepad) => gamepad_buttons
+            .get_just_pressed()
+            .find(|button| button.gamepad == gamepad)
+            .map(|button| ButtonBinding::GamepadButton(button.button_type)),
+        InputSource::KeyboardMouse => keys
+            .get_just_pressed()
+            .next()
+            .map(|key| ButtonBinding::Key(*key))
+            .or_else(|| {
+                return mouse_buttons
+                    .get_just_pressed()
+                    .next()
+                    .map(|mouse_button| ButtonBinding::Mouse(*mouse_button));
+            }),
+    };
+}
+
+/// A system that binds the first input pressed by a player that is capturing a binding
+/// to the action being rebound. Only the bindings of that player change, and they are
+/// saved with the rest of the input profile in the player profile.
+pub fn capture_rebinding(
+    mut commands: Commands,
+    keys: Res<Input<KeyCode>>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    input_bindings: Res<InputBindings>,
+    mut capturing_players: Query<(Entity, &Controller, &CapturingBinding, &mut InputProfile)>,
+) {
+    for (player_entity, controller, capturing_binding, mut input_profile) in
+        capturing_players.iter_mut()
+    {
+        if let Some(binding) =
+            find_pressed_binding(controller.source, &keys, &mouse_buttons, &gamepad_buttons)
+        {
+            input_bindings.rebind_button(
+                &mut input_profile,
+                controller.source,
+                capturing_binding.action,
+                binding,
+            );
+            commands.entity(player_entity).remove::<CapturingBinding>();
+            info!(
+                "Rebound {:?} to {} for the player {:?}",
+                capturing_binding.action,
+                binding.label(),
+                player_entity
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::input::mouse::MouseMotion;
+    use bevy::prelude::{
+        App, Axis, Gamepad, GamepadAxis, GamepadButton, Input, IntoSystemConfig, KeyCode,
+        MouseButton, Time,
+    };
+
+    use super::super::super::control::{Controller, InputSource};
+    use super::super::components::{ActionState, ButtonAction, CapturingBinding, InputProfile};
+    use super::super::control::update_action_states;
+    use super::{capture_rebinding, ButtonBinding, InputBindings};
+
+    fn create_app() -> App {
+        let mut app = App::new();
+        app.init_resource::<InputBindings>()
+            .init_resource::<Time>()
+            .init_resource::<Input<KeyCode>>()
+            .init_resource::<Input<MouseButton>>()
+            .init_resource::<Input<GamepadButton>>()
+            .init_resource::<Axis<GamepadAxis>>()
+            .init_resource::<Axis<GamepadButton>>()
+            .add_event::<MouseMotion>()
+            .add_system(capture_rebinding)
+            .add_system(update_action_states.after(capture_rebinding));
+        return app;
+    }
+
+    #[test]
+    fn rebinds_only_the_capturing_player_without_pressing_the_action() {
+        let mut app = create_app();
+        let capturing_player = app
+            .world
+            .spawn((
+                Controller {
+                    source: InputSource::KeyboardMouse,
+                },
+                InputProfile::default(),
+                ActionState::default(),
+                CapturingBinding {
+                    action: ButtonAction::Pause,
+                },
+            ))
+            .id();
+        let other_player = app
+            .world
+            .spawn((
+                Controller {
+                    source: InputSource::KeyboardMouse,
+                },
+                InputProfile::default(),
+                ActionState::default(),
+            ))
+            .id();
+        let pause_state = |app: &App| {
+            let action_state = app.world.get::<ActionState>(capturing_player).unwrap();
+            return (
+                action_state.pressed(ButtonAction::Pause),
+                action_state.just_pressed(ButtonAction::Pause),
+            );
+        };
+
+        app.world.resource_mut::<Input<KeyCode>>().press(KeyCode::G);
+        app.update();
+        let input_profile = app.world.get::<InputProfile>(capturing_player).unwrap();
+        assert!(
+            input_profile.binding_overrides.keyboard_mouse[&ButtonAction::Pause]
+                == vec![ButtonBinding::Key(KeyCode::G)]
+        );
+        assert!(app
+            .world
+            .get::<CapturingBinding>(capturing_player)
+            .is_none());
+        assert!(app
+            .world
+            .get::<InputProfile>(other_player)
+            .unwrap()
+            .binding_overrides
+            .keyboard_mouse
+            .is_empty());
+        assert_eq!(pause_state(&app), (true, false));
+
+        // Holding the captured key does not press the action it was bound to.
+        app.world.resource_mut::<Input<KeyCode>>().clear();
+        app.update();
+        assert_eq!(pause_state(&app), (true, false));
+
+        // Pressing it again does.
+        app.world
+            .resource_mut::<Input<KeyCode>>()
+            .release(KeyCode::G);
+        app.update();
+        app.world.resource_mut::<Input<KeyCode>>().press(KeyCode::G);
+        app.update();
+        assert_eq!(pause_state(&app), (true, true));
+    }
+
+    #[test]
+    fn keeps_the_default_bindings_of_the_actions_that_were_not_rebound() {
+        let defaults = InputBindings::default();
+        let mut input_profile = InputProfile::default();
+        let source = InputSource::KeyboardMouse;
+        // R zooms in by default, so rebinding Interact to it unbinds zooming in.
+        defaults.rebind_button(
+            &mut input_profile,
+            source,
+            ButtonAction::Interact,
+            ButtonBinding::Key(KeyCode::R),
+        );
+        assert_eq!(
+            input_profile.binding_overrides.keyboard_mouse.len(),
+            2,
+            "only the rebound action and the action that lost its input are overridden"
+        );
+        // An action added to the default bindings after the rebind is bound too.
+        let mut later_defaults = defaults.clone();
+        later_defaults.keyboard_mouse.buttons.insert(
+            ButtonAction::QuickSave,
+            vec![ButtonBinding::Key(KeyCode::F6)],
+        );
+
+        let bindings = |action: ButtonAction| {
+            return later_defaults
+                .button_bindings(&input_profile, source, action)
+                .to_vec();
+        };
+        assert!(bindings(ButtonAction::Interact) == vec![ButtonBinding::Key(KeyCode::R)]);
+        assert!(bindings(ButtonAction::ZoomIn).is_empty());
+        assert!(bindings(ButtonAction::UseTool) == vec![ButtonBinding::Mouse(MouseButton::Left)]);
+        assert!(bindings(ButtonAction::QuickSave) == vec![ButtonBinding::Key(KeyCode::F6)]);
+        assert_eq!(
+            later_defaults.button_label(
+                &input_profile,
+                InputSource::Gamepad(Gamepad::new(0)),
+                ButtonAction::Interact
+            ),
+            Some("A".to_string())
+        );
+    }
+}
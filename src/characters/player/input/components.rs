@@ -0,0 +1,234 @@
+use bevy::prelude::{Component, Entity, Vec2};
+use bevy::utils::HashMap;
+use serde::{Deserialize, Serialize};
+
+use crate::ui::menu_navigation::MenuNavigation;
+
+use super::bindings::BindingOverrides;
+
+/// How the dead zones of a stick are measured.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub enum DeadZoneShape {
+    /// The dead zones are measured on the distance of the stick from its center. This
+    /// keeps the direction of the stick intact.
+    Radial,
+    /// The dead zones are measured on each axis separately. This makes it easier to push
+    /// the stick along exactly one axis.
+    Axial,
+}
+
+/// How the stick deflection, after the dead zones, is mapped to the output value.
+#[derive(Clone, Serialize, Deserialize)]
+pub enum ResponseCurve {
+    Linear,
+    /// Squares the deflection for finer control near the center of the stick.
+    Quadratic,
+    /// A curve through the provided (input, output) points, both between 0 and 1,
+    /// sorted by input. The output is interpolated linearly between the points.
+    Custom(Vec<(f32, f32)>),
+}
+
+/// The settings used to turn the raw values of a stick into the values used by the
+/// game.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct StickProfile {
+    /// Deflections up to the inner dead zone are ignored, which hides stick drift.
+    pub inner_dead_zone: f32,
+    /// Deflections past the outer dead zone are treated as full deflection, so worn
+    /// sticks that can not reach their edge still reach full speed.
+    pub outer_dead_zone: f32,
+    pub dead_zone_shape: DeadZoneShape,
+    pub response_curve: ResponseCurve,
+    /// A multiplier applied to the output.
+    pub sensitivity: f32,
+    pub invert_y: bool,
+}
+
+impl Default for StickProfile {
+    fn default() -> Self {
+        return StickProfile {
+            inner_dead_zone: 0.15,
+            outer_dead_zone: 0.95,
+            dead_zone_shape: DeadZoneShape::Radial,
+            response_curve: ResponseCurve::Linear,
+            sensitivity: 1.0,
+            invert_y: false,
+        };
+    }
+}
+
+/// A Bevy Engine component that is attached to a player character entity with the
+/// settings used to read the controller of that player. The stick profiles are applied
+/// before the stick values are used for any movement or camera math.
+#[derive(Component, Clone, Default, Serialize, Deserialize)]
+pub struct InputProfile {
+    pub move_stick: StickProfile,
+    pub look_stick: StickProfile,
+    /// The bindings of the actions the player rebound. The other actions use the
+    /// default input bindings.
+    #[serde(default)]
+    pub binding_overrides: BindingOverrides,
+}
+
+/// The player actions that have a direction, like a stick.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum StickAction {
+    Move,
+    Look,
+}
+
+/// The player actions that are pressed, like a button. Button actions also have an
+/// analog value for inputs like triggers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum ButtonAction {
+    Sprint,
+    /// Held to face the direction the camera looks in and strafe.
+    Aim,
+    ZoomIn,
+    ZoomOut,
+    Interact,
+    UseTool,
+    OpenInventory,
+    NextHotbarSlot,
+    PreviousHotbarSlot,
+    ChooseProfession,
+    Pause,
+    QuickSave,
+    QuickLoad,
+}
+
+impl ButtonAction {
+    /// Every button action, in the order they are listed in the controls menu.
+    pub const ALL: [ButtonAction; 13] = [
+        ButtonAction::Sprint,
+        ButtonAction::Aim,
+        ButtonAction::ZoomIn,
+        ButtonAction::ZoomOut,
+        ButtonAction::Interact,
+        ButtonAction::UseTool,
+        ButtonAction::OpenInventory,
+        ButtonAction::NextHotbarSlot,
+        ButtonAction::PreviousHotbarSlot,
+        ButtonAction::ChooseProfession,
+        ButtonAction::Pause,
+        ButtonAction::QuickSave,
+        ButtonAction::QuickLoad,
+    ];
+
+    /// Returns the name of the action shown to players.
+    pub fn name(&self) -> &'static str {
+        return match self {
+            ButtonAction::Sprint => "Sprint",
+            ButtonAction::Aim => "Aim",
+            ButtonAction::ZoomIn => "Zoom in",
+            ButtonAction::ZoomOut => "Zoom out",
+            ButtonAction::Interact => "Interact",
+            ButtonAction::UseTool => "Use tool",
+            ButtonAction::OpenInventory => "Open inventory",
+            ButtonAction::NextHotbarSlot => "Next hotbar slot",
+            ButtonAction::PreviousHotbarSlot => "Previous hotbar slot",
+            ButtonAction::ChooseProfession => "Choose profession",
+            ButtonAction::Pause => "Pause",
+            ButtonAction::QuickSave => "Quick save",
+            ButtonAction::QuickLoad => "Quick load",
+        };
+    }
+}
+
+/// The state of a button action for the current frame.
+#[derive(Clone, Copy, Default)]
+pub struct ButtonState {
+    /// How far the action is pressed, between 0 and 1.
+    pub value: f32,
+    pub pressed: bool,
+    /// If the action started being pressed this frame.
+    pub just_pressed: bool,
+}
+
+/// A Bevy Engine component that is attached to a player character entity with the
+/// state of every player action for the current frame. The state is filled in from the
+/// input source of the player controller through the input bindings, so gameplay code
+/// reads actions instead of specific gamepad, keyboard or mouse inputs.
+#[derive(Component, Default)]
+pub struct ActionState {
+    sticks: HashMap<StickAction, Vec2>,
+    buttons: HashMap<ButtonAction, ButtonState>,
+}
+
+impl ActionState {
+    /// Returns the direction of the stick action.
+    pub fn stick(&self, action: StickAction) -> Vec2 {
+        return self.sticks.get(&action).copied().unwrap_or(Vec2::ZERO);
+    }
+
+    /// Returns the analog value of the button action.
+    pub fn value(&self, action: ButtonAction) -> f32 {
+        return self.button(action).value;
+    }
+
+    /// Returns if the button action is pressed.
+    pub fn pressed(&self, action: ButtonAction) -> bool {
+        return self.button(action).pressed;
+    }
+
+    /// Returns if the button action started being pressed this frame.
+    pub fn just_pressed(&self, action: ButtonAction) -> bool {
+        return self.button(action).just_pressed;
+    }
+
+    fn button(&self, action: ButtonAction) -> ButtonState {
+        return self.buttons.get(&action).copied().unwrap_or_default();
+    }
+
+    /// Sets the direction of the stick action for the frame.
+    pub fn set_stick(&mut self, action: StickAction, value: Vec2) {
+        self.sticks.insert(action, value);
+    }
+
+    /// Sets the value and pressed state of the button action for the frame. The action
+    /// is just pressed when it was not pressed in the previous frame.
+    pub fn set_button(&mut self, action: ButtonAction, value: f32, pressed: bool) {
+        let was_pressed = self.pressed(action);
+        self.buttons.insert(
+            action,
+            ButtonState {
+                value: value,
+                pressed: pressed,
+                just_pressed: pressed && !was_pressed,
+            },
+        );
+    }
+
+    /// Sets the pressed state of the button action for the frame without it ever being
+    /// just pressed, and with no analog value. Holding the input afterwards does not
+    /// press the action either, since it was already pressed.
+    pub fn hold_button(&mut self, action: ButtonAction, pressed: bool) {
+        self.buttons.insert(
+            action,
+            ButtonState {
+                value: 0.0,
+                pressed: pressed,
+                just_pressed: false,
+            },
+        );
+    }
+}
+
+/// A Bevy Engine component that is attached to a player character entity while the next
+/// input of the player is captured to be bound to the action. The actions of the player
+/// are held while capturing, never just pressed, so the captured input does not trigger
+/// anything, even on the frame after it is bound.
+#[derive(Component)]
+pub struct CapturingBinding {
+    pub action: ButtonAction,
+}
+
+/// A component on a player character that has the controls menu open, which is open for
+/// every player while the game is paused. The player moves the cursor through the button
+/// actions with the move stick and rebinds the selected one with the interact action.
+#[derive(Component)]
+pub struct ControlsMenu {
+    pub cursor: usize,
+    pub navigation: MenuNavigation,
+    pub prompt: Entity,
+}
@@ -0,0 +1,384 @@
+use bevy::input::mouse::MouseMotion;
+use bevy::prelude::{
+    Axis, EventReader, Gamepad, GamepadAxis, GamepadAxisType, GamepadButton, Input, KeyCode,
+    MouseButton, Query, Res, Time, Vec2,
+};
+
+use super::super::control::{Controller, InputSource};
+use super::bindings::{ButtonBinding, InputBindings, StickBinding};
+use super::components::{
+    ActionState, ButtonAction, CapturingBinding, DeadZoneShape, InputProfile, ResponseCurve,
+    StickAction, StickProfile,
+};
+
+/// Maps a deflection between 0 and 1 through the response curve.
+fn apply_response_curve(deflection: f32, response_curve: &ResponseCurve) -> f32 {
+    return match response_curve {
+        ResponseCurve::Linear => deflection,
+        ResponseCurve::Quadratic => deflection * deflection,
+        ResponseCurve::Custom(points) => {
+            let mut previous = (0.0, 0.0);
+            for point in points {
+                if deflection <= point.0 {
+                    let span = point.0 - previous.0;
+                    if span <= f32::EPSILON {
+                        return point.1;
+                    }
+                    let blend = (deflection - previous.0) / span;
+                    return previous.1 + (point.1 - previous.1) * blend;
+                }
+                previous = *point;
+            }
+            // Past the last point the output stays at the value of the last point.
+            previous.1
+        }
+    };
+}
+
+/// Rescales a deflection so the inner dead zone maps to 0 and the outer dead zone maps
+/// to 1, then applies the response curve.
+fn rescale_deflection(deflection: f32, stick_profile: &StickProfile) -> f32 {
+    let live_range = stick_profile.outer_dead_zone - stick_profile.inner_dead_zone;
+    if deflection <= stick_profile.inner_dead_zone {
+        return 0.0;
+    }
+    let rescaled = if live_range <= f32::EPSILON {
+        1.0
+    } else {
+        ((deflection - stick_profile.inner_dead_zone) / live_range).clamp(0.0, 1.0)
+    };
+    return apply_response_curve(rescaled, &stick_profile.response_curve);
+}
+
+/// Applies the dead zones, response curve, sensitivity and y inversion of the stick
+/// profile to the raw stick value.
+pub fn apply_stick_profile(raw_value: Vec2, stick_profile: &StickProfile) -> Vec2 {
+    let mut value = match stick_profile.dead_zone_shape {
+        DeadZoneShape::Radial => {
+            let deflection = raw_value.length();
+            if deflection <= f32::EPSILON {
+                Vec2::ZERO
+            } else {
+                raw_value / deflection * rescale_deflection(deflection, stick_profile)
+            }
+        }
+        DeadZoneShape::Axial => Vec2::new(
+            raw_value.x.signum() * rescale_deflection(raw_value.x.abs(), stick_profile),
+            raw_value.y.signum() * rescale_deflection(raw_value.y.abs(), stick_profile),
+        ),
+    };
+    value *= stick_profile.sensitivity;
+    if stick_profile.invert_y {
+        value.y = -value.y;
+    }
+    return value;
+}
+
+/// The input devices of the frame that the bindings are read from.
+struct InputDevices<'a> {
+    keys: &'a Input<KeyCode>,
+    mouse_buttons: &'a Input<MouseButton>,
+    mouse_motion: Vec2,
+    gamepad_axes: &'a Axis<GamepadAxis>,
+    gamepad_button_axes: &'a Axis<GamepadButton>,
+    gamepad_buttons: &'a Input<GamepadButton>,
+    delta_seconds: f32,
+}
+
+/// Reads the raw value of a gamepad stick from its two axes.
+fn read_gamepad_stick(
+    axes: &Axis<GamepadAxis>,
+    gamepad: Gamepad,
+    x_axis_type: GamepadAxisType,
+    y_axis_type: GamepadAxisType,
+) -> Option<Vec2> {
+    let x_axis = GamepadAxis::new(gamepad, x_axis_type);
+    let y_axis = GamepadAxis::new(gamepad, y_axis_type);
+    if let (Some(x_value), Some(y_value)) = (axes.get(x_axis), axes.get(y_axis)) {
+        return Some(Vec2::new(x_value, y_value));
+    }
+    return None;
+}
+
+/// Reads the value of a stick binding for the input source. The stick profile is
+/// applied to gamepad sticks. The mouse motion only uses the sensitivity and y inversion
+/// of the profile since it has no dead zones.
+fn read_stick_binding(
+    binding: &StickBinding,
+    source: InputSource,
+    devices: &InputDevices,
+    stick_profile: &StickProfile,
+) -> Vec2 {
+    let value = match (binding, source) {
+        (StickBinding::GamepadStick { x, y }, InputSource::Gamepad(gamepad)) => {
+            read_gamepad_stick(devices.gamepad_axes, gamepad, *x, *y)
+                .map(|raw_value| apply_stick_profile(raw_value, stick_profile))
+        }
+        (
+            StickBinding::Keys {
+                up,
+                down,
+                left,
+                right,
+            },
+            InputSource::KeyboardMouse,
+        ) => {
+            let key_value = |key: &KeyCode| if devices.keys.pressed(*key) { 1.0 } else { 0.0 };
+            let direction = Vec2::new(
+                key_value(right) - key_value(left),
+                key_value(up) - key_value(down),
+            );
+            Some(direction.normalize_or_zero())
+        }
+        (StickBinding::MouseMotion { sensitivity }, InputSource::KeyboardMouse) => {
+            if devices.delta_seconds <= f32::EPSILON {
+                None
+            } else {
+                // Moving the mouse up gives a negative y motion, but it should look up
+                // like pushing a stick up.
+                let mut value = Vec2::new(devices.mouse_motion.x, -devices.mouse_motion.y)
+                    * *sensitivity
+                    / devices.delta_seconds
+                    * stick_profile.sensitivity;
+                if stick_profile.invert_y {
+                    value.y = -value.y;
+                }
+                Some(value)
+            }
+        }
+        // The binding is for another kind of input source.
+        _ => None,
+    };
+    return value.unwrap_or(Vec2::ZERO);
+}
+
+/// Reads the value and pressed state of a button binding for the input source. Gamepad
+/// buttons with an analog value, like triggers, report how far they are pressed.
+fn read_button_binding(
+    binding: &ButtonBinding,
+    source: InputSource,
+    devices: &InputDevices,
+) -> (f32, bool) {
+    let digital_state = |pressed: bool| (if pressed { 1.0 } else { 0.0 }, pressed);
+    return match (binding, source) {
+        (ButtonBinding::GamepadButton(button_type), InputSource::Gamepad(gamepad)) => {
+            let button = GamepadButton::new(gamepad, *button_type);
+            let pressed = devices.gamepad_buttons.pressed(button);
+            let value = devices
+                .gamepad_button_axes
+                .get(button)
+                .unwrap_or(if pressed { 1.0 } else { 0.0 });
+            (value, pressed)
+        }
+        (ButtonBinding::Key(key), InputSource::KeyboardMouse) => {
+            digital_state(devices.keys.pressed(*key))
+        }
+        (ButtonBinding::Mouse(mouse_button), InputSource::KeyboardMouse) => {
+            digital_state(devices.mouse_buttons.pressed(*mouse_button))
+        }
+        // The binding is for another kind of input source.
+        _ => (0.0, false),
+    };
+}
+
+/// Returns the stick profile that applies to the stick action.
+fn stick_profile_for_action(input_profile: &InputProfile, action: StickAction) -> &StickProfile {
+    return match action {
+        StickAction::Move => &input_profile.move_stick,
+        StickAction::Look => &input_profile.look_stick,
+    };
+}
+
+/// A system that updates the action state of every player with a controller from the
+/// input source of the controller, through the bindings of the player for that kind of
+/// source. When several inputs are bound to the same action their values are combined.
+/// While a player captures a binding their actions are only held, so neither the
+/// captured input nor the action it is bound to is just pressed.
+pub fn update_action_states(
+    input_bindings: Res<InputBindings>,
+    timer: Res<Time>,
+    keys: Res<Input<KeyCode>>,
+    mouse_buttons: Res<Input<MouseButton>>,
+    mut mouse_motion_events: EventReader<MouseMotion>,
+    gamepad_axes: Res<Axis<GamepadAxis>>,
+    gamepad_button_axes: Res<Axis<GamepadButton>>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    mut players: Query<(
+        &Controller,
+        &InputProfile,
+        &mut ActionState,
+        Option<&CapturingBinding>,
+    )>,
+) {
+    let devices = InputDevices {
+        keys: &keys,
+        mouse_buttons: &mouse_buttons,
+        mouse_motion: mouse_motion_events.iter().map(|motion| motion.delta).sum(),
+        gamepad_axes: &gamepad_axes,
+        gamepad_button_axes: &gamepad_button_axes,
+        gamepad_buttons: &gamepad_buttons,
+        delta_seconds: timer.delta_seconds(),
+    };
+    for (controller, input_profile, mut action_state, capturing_binding) in players.iter_mut() {
+        let binding_set = input_bindings.binding_set(controller.source);
+        for (action, bindings) in binding_set.sticks.iter() {
+            let stick_profile = stick_profile_for_action(input_profile, *action);
+            let value = bindings
+                .iter()
+                .map(|binding| {
+                    return read_stick_binding(binding, controller.source, &devices, stick_profile);
+                })
+                .sum();
+            if capturing_binding.is_some() {
+                action_state.set_stick(*action, Vec2::ZERO);
+            } else {
+                action_state.set_stick(*action, value);
+            }
+        }
+        for action in ButtonAction::ALL {
+            let (value, pressed) = input_bindings
+                .button_bindings(input_profile, controller.source, action)
+                .iter()
+                .map(|binding| read_button_binding(binding, controller.source, &devices))
+                .fold(
+                    (0.0, false),
+                    |(value, pressed), (binding_value, binding_pressed)| {
+                        return (f32::max(value, binding_value), pressed || binding_pressed);
+                    },
+                );
+            if capturing_binding.is_some() {
+                action_state.hold_button(action, pressed);
+            } else {
+                action_state.set_button(action, value, pressed);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::Vec2;
+
+    use super::super::components::{DeadZoneShape, ResponseCurve, StickProfile};
+    use super::apply_stick_profile;
+
+    /// A radial linear profile with an inner dead zone of 0.2 and an outer dead zone of
+    /// 0.8, so the live range is 0.6 wide.
+    fn create_stick_profile(response_curve: ResponseCurve) -> StickProfile {
+        return StickProfile {
+            inner_dead_zone: 0.2,
+            outer_dead_zone: 0.8,
+            dead_zone_shape: DeadZoneShape::Radial,
+            response_curve: response_curve,
+            sensitivity: 1.0,
+            invert_y: false,
+        };
+    }
+
+    fn assert_close(value: Vec2, expected: Vec2) {
+        assert!(
+            value.distance(expected) < 1e-5,
+            "expected {expected:?}, got {value:?}"
+        );
+    }
+
+    #[test]
+    fn ignores_deflections_inside_the_inner_dead_zone() {
+        let stick_profile = create_stick_profile(ResponseCurve::Linear);
+        assert_eq!(apply_stick_profile(Vec2::ZERO, &stick_profile), Vec2::ZERO);
+        assert_eq!(
+            apply_stick_profile(Vec2::new(0.1, 0.1), &stick_profile),
+            Vec2::ZERO
+        );
+        assert_eq!(
+            apply_stick_profile(Vec2::new(0.0, -0.2), &stick_profile),
+            Vec2::ZERO
+        );
+    }
+
+    #[test]
+    fn reaches_full_deflection_at_the_outer_dead_zone() {
+        let stick_profile = create_stick_profile(ResponseCurve::Linear);
+        assert_close(
+            apply_stick_profile(Vec2::new(0.8, 0.0), &stick_profile),
+            Vec2::new(1.0, 0.0),
+        );
+        assert_close(
+            apply_stick_profile(Vec2::new(0.0, -1.0), &stick_profile),
+            Vec2::new(0.0, -1.0),
+        );
+    }
+
+    #[test]
+    fn rescales_the_live_range_keeping_the_direction() {
+        let stick_profile = create_stick_profile(ResponseCurve::Linear);
+        // A deflection of 0.5 is halfway through the live range.
+        assert_close(
+            apply_stick_profile(Vec2::new(0.3, 0.4), &stick_profile),
+            Vec2::new(0.3, 0.4),
+        );
+    }
+
+    #[test]
+    fn measures_axial_dead_zones_on_each_axis() {
+        let mut stick_profile = create_stick_profile(ResponseCurve::Linear);
+        stick_profile.dead_zone_shape = DeadZoneShape::Axial;
+        // The x axis is inside the inner dead zone even though the stick is far out.
+        assert_close(
+            apply_stick_profile(Vec2::new(0.15, -0.9), &stick_profile),
+            Vec2::new(0.0, -1.0),
+        );
+        assert_close(
+            apply_stick_profile(Vec2::new(0.5, 0.5), &stick_profile),
+            Vec2::new(0.5, 0.5),
+        );
+    }
+
+    #[test]
+    fn squares_the_deflection_with_the_quadratic_curve() {
+        let stick_profile = create_stick_profile(ResponseCurve::Quadratic);
+        assert_close(
+            apply_stick_profile(Vec2::new(0.5, 0.0), &stick_profile),
+            Vec2::new(0.25, 0.0),
+        );
+        assert_close(
+            apply_stick_profile(Vec2::new(0.0, 0.9), &stick_profile),
+            Vec2::new(0.0, 1.0),
+        );
+    }
+
+    #[test]
+    fn interpolates_between_the_points_of_a_custom_curve() {
+        let stick_profile =
+            create_stick_profile(ResponseCurve::Custom(vec![(0.5, 0.2), (1.0, 1.0)]));
+        // Rescaled deflections of 0.25, 0.5, 0.75 and 1.
+        let outputs: Vec<f32> = [0.35, 0.5, 0.65, 0.8]
+            .iter()
+            .map(|deflection| apply_stick_profile(Vec2::new(*deflection, 0.0), &stick_profile).x)
+            .collect();
+        let expected = [0.1, 0.2, 0.6, 1.0];
+        for (output, expected) in outputs.iter().zip(expected) {
+            assert!((output - expected).abs() < 1e-5, "{outputs:?}");
+        }
+    }
+
+    #[test]
+    fn holds_the_last_point_of_a_custom_curve() {
+        let stick_profile = create_stick_profile(ResponseCurve::Custom(vec![(0.5, 0.7)]));
+        assert_close(
+            apply_stick_profile(Vec2::new(0.8, 0.0), &stick_profile),
+            Vec2::new(0.7, 0.0),
+        );
+    }
+
+    #[test]
+    fn applies_the_sensitivity_and_y_inversion_after_the_curve() {
+        let mut stick_profile = create_stick_profile(ResponseCurve::Quadratic);
+        stick_profile.sensitivity = 2.0;
+        stick_profile.invert_y = true;
+        assert_close(
+            apply_stick_profile(Vec2::new(0.0, 0.5), &stick_profile),
+            Vec2::new(0.0, -0.5),
+        );
+    }
+}
@@ -0,0 +1,142 @@
+use bevy::prelude::{
+    Color, Commands, DespawnRecursiveExt, Entity, EventWriter, Query, Res, Text, TextBundle,
+    TextStyle, With, Without,
+};
+
+use crate::characters::profession::components::ChoosingProfession;
+use crate::crafting::components::CraftingMenu;
+use crate::game_state::GamePaused;
+use crate::items::components::InventoryMenu;
+use crate::ui::menu_navigation::{wrap_index, MenuNavigation};
+use crate::ui::player_overlay::{spawn_in_player_overlay, PlayerOverlayLink};
+use crate::ui::start_up::UiFont;
+
+use super::super::control::{Controller, InputSource};
+use super::super::profile::components::{ActiveProfile, CustomizingAppearance};
+use super::bindings::{InputBindings, RebindButtonAction};
+use super::components::{
+    ActionState, ButtonAction, CapturingBinding, ControlsMenu, InputProfile, StickAction,
+};
+
+/// Returns the text of the controls menu, with the input bound to every button action
+/// for the input source with the input profile. The action being rebound asks for the
+/// new input instead.
+fn controls_menu_text(
+    bindings: &InputBindings,
+    input_profile: &InputProfile,
+    source: InputSource,
+    cursor: usize,
+    capturing: Option<ButtonAction>,
+) -> String {
+    let mut text = "Controls\n".to_string();
+    for (index, action) in ButtonAction::ALL.iter().enumerate() {
+        let cursor_mark = if index == cursor { ">" } else { " " };
+        let label = if capturing == Some(*action) {
+            "press an input...".to_string()
+        } else {
+            bindings
+                .button_label(input_profile, source, *action)
+                .unwrap_or_else(|| "unbound".to_string())
+        };
+        text.push_str(&format!("{} {}: {}\n", cursor_mark, action.name(), label));
+    }
+    text.push_str("Interact to rebind, Pause to resume");
+    return text;
+}
+
+/// A system that opens the controls menu of every player with a profile while the game
+/// is paused, unless they are in another menu.
+pub fn open_controls_menus(
+    mut commands: Commands,
+    game_paused: Res<GamePaused>,
+    ui_font: Res<UiFont>,
+    players: Query<
+        (Entity, &PlayerOverlayLink),
+        (
+            With<ActiveProfile>,
+            Without<ControlsMenu>,
+            Without<CustomizingAppearance>,
+            Without<ChoosingProfession>,
+            Without<InventoryMenu>,
+            Without<CraftingMenu>,
+        ),
+    >,
+) {
+    if !game_paused.0 {
+        return;
+    }
+    for (player_entity, overlay_link) in players.iter() {
+        let prompt = spawn_in_player_overlay(
+            &mut commands,
+            overlay_link,
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font: ui_font.0.clone(),
+                    font_size: 20.0,
+                    color: Color::WHITE,
+                },
+            ),
+        );
+        commands.entity(player_entity).insert(ControlsMenu {
+            cursor: 0,
+            navigation: MenuNavigation::default(),
+            prompt: prompt,
+        });
+    }
+}
+
+/// A system that lets the players with the controls menu open move through the button
+/// actions and start rebinding the selected one. The menus close, and any rebinding
+/// stops, once the game is resumed.
+pub fn use_controls_menus(
+    mut commands: Commands,
+    game_paused: Res<GamePaused>,
+    input_bindings: Res<InputBindings>,
+    mut players: Query<(
+        Entity,
+        &Controller,
+        &ActionState,
+        &InputProfile,
+        &mut ControlsMenu,
+        Option<&CapturingBinding>,
+    )>,
+    mut prompts: Query<&mut Text>,
+    mut rebind_events: EventWriter<RebindButtonAction>,
+) {
+    for (player_entity, controller, action_state, input_profile, mut menu, capturing_binding) in
+        players.iter_mut()
+    {
+        if !game_paused.0 {
+            commands.entity(menu.prompt).despawn_recursive();
+            commands
+                .entity(player_entity)
+                .remove::<ControlsMenu>()
+                .remove::<CapturingBinding>();
+            continue;
+        }
+        let step = menu.navigation.step(action_state.stick(StickAction::Move));
+        if let Some(step) = step.filter(|step| step.y != 0) {
+            // Pushing the stick up moves the cursor up the list.
+            menu.cursor = wrap_index(menu.cursor, -step.y, ButtonAction::ALL.len());
+        }
+        if capturing_binding.is_none() && action_state.just_pressed(ButtonAction::Interact) {
+            rebind_events.send(RebindButtonAction {
+                player: player_entity,
+                action: ButtonAction::ALL[menu.cursor],
+            });
+        }
+        if let Ok(mut prompt) = prompts.get_mut(menu.prompt) {
+            let text = controls_menu_text(
+                &input_bindings,
+                input_profile,
+                controller.source,
+                menu.cursor,
+                capturing_binding.map(|capturing_binding| capturing_binding.action),
+            );
+            if prompt.sections[0].value != text {
+                prompt.sections[0].value = text;
+            }
+        }
+    }
+}
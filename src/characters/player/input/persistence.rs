@@ -0,0 +1,22 @@
+use std::path::PathBuf;
+
+use crate::storage::load_ron_or_default;
+
+use super::components::InputProfile;
+
+/// The directory the input profiles of the players were saved in.
+const INPUT_PROFILE_DIRECTORY: &str = "input_profiles";
+
+/// Returns the path of the input profile file of the player.
+fn input_profile_path(player_id: u8) -> PathBuf {
+    return PathBuf::from(INPUT_PROFILE_DIRECTORY).join(format!("player_{}.ron", player_id));
+}
+
+/// Loads the input profile the player saved by player id, before the input settings were
+/// saved in the player profiles. New player profiles start with it.
+pub fn load_input_profile(player_id: u8) -> InputProfile {
+    return load_ron_or_default(
+        &input_profile_path(player_id),
+        &format!("the input profile of player with id {}", player_id),
+    );
+}
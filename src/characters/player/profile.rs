@@ -0,0 +1,6 @@
+/// profile module for the player module. The profiles players pick when they join,
+/// saved on disk with their name, look, profession and input settings.
+pub(crate) mod components;
+pub(crate) mod control;
+pub(crate) mod customization;
+pub(crate) mod persistence;
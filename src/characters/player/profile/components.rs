@@ -0,0 +1,71 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use bevy::prelude::{Component, Entity, Resource, Vec3};
+use serde::{Deserialize, Serialize};
+
+use crate::characters::appearance::components::Appearance;
+use crate::ui::menu_navigation::MenuNavigation;
+
+use super::super::input::components::InputProfile;
+
+/// A profile of a person playing the game, saved on disk so they can pick it again
+/// whenever they join.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PlayerProfile {
+    pub name: String,
+    pub appearance: Appearance,
+    /// The id of the profession the player chose, if they chose one.
+    pub profession: Option<String>,
+    /// How far the player is along their quest lines, by the id of the quest line.
+    #[serde(default)]
+    pub quest_lines: BTreeMap<String, u32>,
+    pub input: InputProfile,
+    /// Where the player was when the profile was last saved.
+    pub last_position: Option<Vec3>,
+}
+
+/// A component on a player character with the profile the player chose. The id is the
+/// name of the profile file.
+#[derive(Component)]
+pub struct ActiveProfile {
+    pub id: String,
+    pub profile: PlayerProfile,
+}
+
+/// A Bevy Engine resource with the ids of the profiles saved on disk, so new profiles
+/// get an id that is not taken without reading the profile directory every frame. The
+/// id of every profile that is saved is added to it.
+#[derive(Resource, Default)]
+pub struct SavedProfileIds(pub BTreeSet<String>);
+
+/// One of the choices a joining player can pick.
+pub enum ProfileChoice {
+    Existing {
+        id: String,
+        profile: Box<PlayerProfile>,
+    },
+    New,
+}
+
+/// A component on a player character that has joined but not chosen a profile yet. The
+/// player cycles through the choices with the move stick and confirms with the interact
+/// action. The prompt is the text node that shows the selected choice.
+#[derive(Component)]
+pub struct ChoosingProfile {
+    pub choices: Vec<ProfileChoice>,
+    pub selected: usize,
+    pub navigation: MenuNavigation,
+    pub prompt: Entity,
+}
+
+/// A component on a player character that is choosing the look of their new profile on
+/// the customization screen. The player moves between the rows with the move stick up
+/// and down, changes the row with the move stick left and right, and finishes with the
+/// interact action. The prompt is the text node of the screen, spawned once the screen
+/// opens.
+#[derive(Component, Default)]
+pub struct CustomizingAppearance {
+    pub row: usize,
+    pub navigation: MenuNavigation,
+    pub prompt: Option<Entity>,
+}
@@ -0,0 +1,283 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::{
+    info, warn, Color, Commands, DespawnRecursiveExt, DetectChanges, Entity, EventReader, Query,
+    Ref, Res, ResMut, Text, TextBundle, TextStyle, Transform, With, Without,
+};
+
+use crate::characters::appearance::components::Appearance;
+use crate::characters::components::Name;
+use crate::characters::profession::components::QuestLines;
+use crate::save::control::SaveGameEvent;
+use crate::ui::menu_navigation::{wrap_index, MenuNavigation};
+use crate::ui::player_overlay::{spawn_in_player_overlay, PlayerOverlayLink};
+use crate::ui::start_up::UiFont;
+
+use super::super::entity::components::{AwaitingReconnect, PlayerCharacter};
+use super::super::input::components::{ActionState, ButtonAction, InputProfile, StickAction};
+use super::super::input::persistence::load_input_profile;
+use super::components::{
+    ActiveProfile, ChoosingProfile, CustomizingAppearance, PlayerProfile, ProfileChoice,
+    SavedProfileIds,
+};
+use super::persistence::{load_profiles, profile_id_from_name, save_profile};
+
+/// The body colors new profiles get, in turn.
+pub const PROFILE_COLORS: [&str; 6] = [
+    "#71daff", "#ff8a65", "#9ccc65", "#ffd54f", "#ba68c8", "#4db6ac",
+];
+
+/// Creates a new profile for the player with the first farmer name that is not taken by
+/// a saved profile or a profile in use. The input settings the player saved before
+/// profiles existed are carried over.
+fn create_new_profile(player_id: u8, taken_ids: &[String]) -> (String, PlayerProfile) {
+    let mut number = 1;
+    loop {
+        let name = format!("Farmer {}", number);
+        let profile_id = profile_id_from_name(&name);
+        if !taken_ids.contains(&profile_id) {
+            let profile = PlayerProfile {
+                name: name,
+                appearance: Appearance {
+                    body_color: Color::hex(PROFILE_COLORS[(number - 1) % PROFILE_COLORS.len()])
+                        .unwrap(),
+                    ..Appearance::default()
+                },
+                profession: None,
+                quest_lines: BTreeMap::new(),
+                input: load_input_profile(player_id),
+                last_position: None,
+            };
+            return (profile_id, profile);
+        }
+        number += 1;
+    }
+}
+
+/// Returns the text of the prompt for the selected profile choice.
+fn prompt_text(choices: &[ProfileChoice], selected: usize) -> String {
+    let choice_name = match &choices[selected] {
+        ProfileChoice::Existing { profile, .. } => profile.name.clone(),
+        ProfileChoice::New => "New profile".to_string(),
+    };
+    return format!(
+        "Choose a profile ({}/{})\n<  {}  >\nInteract to confirm",
+        selected + 1,
+        choices.len(),
+        choice_name
+    );
+}
+
+/// A system that asks every player that joined without a profile to choose one. The
+/// saved profiles that are not in use by another player are offered along with a new
+/// profile.
+pub fn start_choosing_profiles(
+    mut commands: Commands,
+    ui_font: Res<UiFont>,
+    players: Query<
+        (Entity, &PlayerOverlayLink),
+        (
+            With<PlayerCharacter>,
+            Without<ActiveProfile>,
+            Without<ChoosingProfile>,
+        ),
+    >,
+    active_profiles: Query<&ActiveProfile>,
+) {
+    if players.is_empty() {
+        return;
+    }
+    let active_ids: Vec<&String> = active_profiles
+        .iter()
+        .map(|active_profile| &active_profile.id)
+        .collect();
+    for (player_entity, overlay_link) in players.iter() {
+        let mut choices: Vec<ProfileChoice> = load_profiles()
+            .into_iter()
+            .filter(|(profile_id, _)| !active_ids.contains(&profile_id))
+            .map(|(profile_id, profile)| ProfileChoice::Existing {
+                id: profile_id,
+                profile: Box::new(profile),
+            })
+            .collect();
+        choices.push(ProfileChoice::New);
+        let prompt = spawn_in_player_overlay(
+            &mut commands,
+            overlay_link,
+            TextBundle::from_section(
+                prompt_text(&choices, 0),
+                TextStyle {
+                    font: ui_font.0.clone(),
+                    font_size: 28.0,
+                    color: Color::WHITE,
+                },
+            ),
+        );
+        commands.entity(player_entity).insert(ChoosingProfile {
+            choices: choices,
+            selected: 0,
+            navigation: MenuNavigation::default(),
+            prompt: prompt,
+        });
+    }
+}
+
+/// A system that lets the players choosing a profile cycle through the choices and
+/// confirm one. The confirmed profile is applied to the player character and saved.
+pub fn choose_profiles(
+    mut commands: Commands,
+    mut players: Query<
+        (
+            Entity,
+            &PlayerCharacter,
+            &ActionState,
+            &mut ChoosingProfile,
+            &mut Name,
+            &mut Transform,
+            &mut InputProfile,
+            &mut Appearance,
+        ),
+        Without<ActiveProfile>,
+    >,
+    active_profiles: Query<&ActiveProfile>,
+    mut prompts: Query<&mut Text>,
+    mut saved_profile_ids: ResMut<SavedProfileIds>,
+) {
+    if players.is_empty() {
+        return;
+    }
+    let mut taken_ids: Vec<String> = active_profiles
+        .iter()
+        .map(|active_profile| active_profile.id.clone())
+        .chain(saved_profile_ids.0.iter().cloned())
+        .collect();
+    for (
+        player_entity,
+        player,
+        action_state,
+        mut choosing_profile,
+        mut name,
+        mut transform,
+        mut input_profile,
+        mut appearance,
+    ) in players.iter_mut()
+    {
+        let step = choosing_profile
+            .navigation
+            .step(action_state.stick(StickAction::Move));
+        if let Some(step) = step.filter(|step| step.x != 0) {
+            choosing_profile.selected = wrap_index(
+                choosing_profile.selected,
+                step.x,
+                choosing_profile.choices.len(),
+            );
+            if let Ok(mut prompt) = prompts.get_mut(choosing_profile.prompt) {
+                prompt.sections[0].value =
+                    prompt_text(&choosing_profile.choices, choosing_profile.selected);
+            }
+        }
+        if !action_state.just_pressed(ButtonAction::Interact) {
+            continue;
+        }
+        let (profile_id, profile, is_new) =
+            match &choosing_profile.choices[choosing_profile.selected] {
+                ProfileChoice::Existing { id, profile } => {
+                    if active_profiles.iter().any(|active| &active.id == id) {
+                        warn!("The profile {} is already in use by another player", id);
+                        continue;
+                    }
+                    (id.clone(), profile.as_ref().clone(), false)
+                }
+                ProfileChoice::New => {
+                    let (profile_id, profile) = create_new_profile(player.id, &taken_ids);
+                    (profile_id, profile, true)
+                }
+            };
+        taken_ids.push(profile_id.clone());
+        name.0 = profile.name.clone();
+        *appearance = profile.appearance.clone();
+        *input_profile = profile.input.clone();
+        if let Some(last_position) = profile.last_position {
+            transform.translation = last_position;
+        }
+        match save_profile(&profile_id, &profile) {
+            Ok(()) => {
+                saved_profile_ids.0.insert(profile_id.clone());
+            }
+            Err(error) => warn!("Could not save the profile {}: {}", profile_id, error),
+        }
+        info!(
+            "Player with id {} is playing with the profile {}",
+            player.id, profile.name
+        );
+        commands.entity(choosing_profile.prompt).despawn_recursive();
+        commands
+            .entity(player_entity)
+            .remove::<ChoosingProfile>()
+            .insert(ActiveProfile {
+                id: profile_id,
+                profile: profile,
+            });
+        if is_new {
+            // New profiles go through the customization screen to pick their look.
+            commands
+                .entity(player_entity)
+                .insert(CustomizingAppearance::default());
+        }
+    }
+}
+
+/// A system that saves the profile of every player whose input settings or appearance
+/// changed, whose quest lines or profession changed, whose controller disconnected, or
+/// when the game is saved. The name, look, input settings, quest progress and position
+/// of the player are written to the profile.
+pub fn save_player_profiles(
+    mut save_events: EventReader<SaveGameEvent>,
+    mut saved_profile_ids: ResMut<SavedProfileIds>,
+    mut players: Query<(
+        &mut ActiveProfile,
+        &Name,
+        Ref<InputProfile>,
+        Ref<Appearance>,
+        Ref<QuestLines>,
+        &Transform,
+        Option<Ref<AwaitingReconnect>>,
+    )>,
+) {
+    let game_saved = save_events.iter().count() > 0;
+    for (
+        mut active_profile,
+        name,
+        input_profile,
+        appearance,
+        quest_lines,
+        transform,
+        awaiting_reconnect,
+    ) in players.iter_mut()
+    {
+        let controller_disconnected =
+            awaiting_reconnect.is_some_and(|awaiting_reconnect| awaiting_reconnect.is_added());
+        if !(game_saved
+            || controller_disconnected
+            || input_profile.is_changed()
+            || appearance.is_changed()
+            || quest_lines.is_changed())
+        {
+            continue;
+        }
+        active_profile.profile.name = name.0.clone();
+        active_profile.profile.input = input_profile.clone();
+        active_profile.profile.appearance = appearance.clone();
+        active_profile.profile.quest_lines = quest_lines.0.clone();
+        active_profile.profile.last_position = Some(transform.translation);
+        match save_profile(&active_profile.id, &active_profile.profile) {
+            Ok(()) => {
+                saved_profile_ids.0.insert(active_profile.id.clone());
+            }
+            Err(error) => warn!(
+                "Could not save the profile {}: {}",
+                active_profile.id, error
+            ),
+        }
+    }
+}
@@ -0,0 +1,222 @@
+use bevy::prelude::{
+    info, Color, Commands, DespawnRecursiveExt, Entity, Query, Res, Text, TextBundle, TextStyle,
+};
+
+use crate::characters::appearance::components::{Appearance, CharacterPartCatalog, PartSlot};
+use crate::ui::menu_navigation::wrap_index;
+use crate::ui::player_overlay::{spawn_in_player_overlay, PlayerOverlayLink};
+use crate::ui::start_up::UiFont;
+
+use super::super::input::components::{ActionState, ButtonAction, StickAction};
+use super::components::CustomizingAppearance;
+use super::control::PROFILE_COLORS;
+
+/// The hair colors the customization screen offers.
+const HAIR_COLORS: [&str; 6] = [
+    "#5a3825", "#2b2118", "#c99a5b", "#e8d39b", "#a0462d", "#8c8c8c",
+];
+/// The clothing colors the customization screen offers.
+const CLOTHING_COLORS: [&str; 6] = [
+    "#3b5b92", "#6b8e23", "#b03a2e", "#d4ac0d", "#5d6d7e", "#f0f0f0",
+];
+
+/// The rows of the customization screen, from top to bottom.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum CustomizationRow {
+    Body,
+    Hair,
+    Clothing,
+    BodyColor,
+    HairColor,
+    ClothingColor,
+}
+
+const CUSTOMIZATION_ROWS: [CustomizationRow; 6] = [
+    CustomizationRow::Body,
+    CustomizationRow::Hair,
+    CustomizationRow::Clothing,
+    CustomizationRow::BodyColor,
+    CustomizationRow::HairColor,
+    CustomizationRow::ClothingColor,
+];
+
+/// Returns the part ids the slot can be set to. Hair and clothing can be left out.
+fn part_choices(catalog: &CharacterPartCatalog, slot: PartSlot) -> Vec<Option<String>> {
+    let part_ids = catalog.parts(slot).iter().map(|part| Some(part.id.clone()));
+    return match slot {
+        PartSlot::Body => part_ids.collect(),
+        PartSlot::Hair | PartSlot::Clothing => std::iter::once(None).chain(part_ids).collect(),
+    };
+}
+
+/// Returns the choice after moving the step from the current choice. A current choice
+/// that is not in the choices moves from the first choice.
+fn cycle_choice<T: Clone + PartialEq>(current: &T, choices: &[T], step: i32) -> T {
+    let index = choices
+        .iter()
+        .position(|choice| choice == current)
+        .unwrap_or(0);
+    return choices[wrap_index(index, step, choices.len())].clone();
+}
+
+/// Returns the colors of the palette.
+fn palette_colors(palette: &[&str]) -> Vec<Color> {
+    return palette.iter().map(|hex| Color::hex(hex).unwrap()).collect();
+}
+
+/// Changes the row of the appearance by the step.
+fn change_row(
+    appearance: &mut Appearance,
+    row: CustomizationRow,
+    step: i32,
+    catalog: &CharacterPartCatalog,
+) {
+    match row {
+        CustomizationRow::Body => {
+            let choices = part_choices(catalog, PartSlot::Body);
+            if let Some(body) = cycle_choice(&Some(appearance.body.clone()), &choices, step) {
+                appearance.body = body;
+            }
+        }
+        CustomizationRow::Hair => {
+            appearance.hair = cycle_choice(
+                &appearance.hair,
+                &part_choices(catalog, PartSlot::Hair),
+                step,
+            );
+        }
+        CustomizationRow::Clothing => {
+            appearance.clothing = cycle_choice(
+                &appearance.clothing,
+                &part_choices(catalog, PartSlot::Clothing),
+                step,
+            );
+        }
+        CustomizationRow::BodyColor => {
+            appearance.body_color = cycle_choice(
+                &appearance.body_color,
+                &palette_colors(&PROFILE_COLORS),
+                step,
+            );
+        }
+        CustomizationRow::HairColor => {
+            appearance.hair_color =
+                cycle_choice(&appearance.hair_color, &palette_colors(&HAIR_COLORS), step);
+        }
+        CustomizationRow::ClothingColor => {
+            appearance.clothing_color = cycle_choice(
+                &appearance.clothing_color,
+                &palette_colors(&CLOTHING_COLORS),
+                step,
+            );
+        }
+    }
+}
+
+/// Returns the position of the color in the palette as text, like 2/6.
+fn palette_position(color: Color, palette: &[&str]) -> String {
+    let colors = palette_colors(palette);
+    return match colors
+        .iter()
+        .position(|palette_color| *palette_color == color)
+    {
+        Some(index) => format!("{}/{}", index + 1, colors.len()),
+        None => "custom".to_string(),
+    };
+}
+
+/// Returns the text of the customization screen for the appearance with the selected
+/// row marked.
+fn customization_text(appearance: &Appearance, selected_row: usize) -> String {
+    let part_name = |part: &Option<String>| part.clone().unwrap_or_else(|| "none".to_string());
+    let mut lines = vec!["Customize your farmer".to_string()];
+    for (index, row) in CUSTOMIZATION_ROWS.iter().enumerate() {
+        let (label, value) = match row {
+            CustomizationRow::Body => ("Body", appearance.body.clone()),
+            CustomizationRow::Hair => ("Hair", part_name(&appearance.hair)),
+            CustomizationRow::Clothing => ("Clothing", part_name(&appearance.clothing)),
+            CustomizationRow::BodyColor => (
+                "Body color",
+                palette_position(appearance.body_color, &PROFILE_COLORS),
+            ),
+            CustomizationRow::HairColor => (
+                "Hair color",
+                palette_position(appearance.hair_color, &HAIR_COLORS),
+            ),
+            CustomizationRow::ClothingColor => (
+                "Clothing color",
+                palette_position(appearance.clothing_color, &CLOTHING_COLORS),
+            ),
+        };
+        let marker = if index == selected_row { ">" } else { " " };
+        lines.push(format!("{} {}: <  {}  >", marker, label, value));
+    }
+    lines.push("Interact when done".to_string());
+    return lines.join("\n");
+}
+
+/// A system that runs the customization screen of every player customizing their
+/// appearance. The appearance changes as the player goes through the choices so they
+/// see their character change, and the screen closes when they press interact.
+pub fn customize_appearances(
+    mut commands: Commands,
+    ui_font: Res<UiFont>,
+    catalog: Res<CharacterPartCatalog>,
+    mut players: Query<(
+        Entity,
+        &ActionState,
+        &PlayerOverlayLink,
+        &mut CustomizingAppearance,
+        &mut Appearance,
+    )>,
+    mut prompts: Query<&mut Text>,
+) {
+    for (player_entity, action_state, overlay_link, mut customizing, mut appearance) in
+        players.iter_mut()
+    {
+        let Some(prompt) = customizing.prompt else {
+            let prompt = spawn_in_player_overlay(
+                &mut commands,
+                overlay_link,
+                TextBundle::from_section(
+                    customization_text(&appearance, customizing.row),
+                    TextStyle {
+                        font: ui_font.0.clone(),
+                        font_size: 24.0,
+                        color: Color::WHITE,
+                    },
+                ),
+            );
+            customizing.prompt = Some(prompt);
+            continue;
+        };
+        if action_state.just_pressed(ButtonAction::Interact) {
+            commands.entity(prompt).despawn_recursive();
+            commands
+                .entity(player_entity)
+                .remove::<CustomizingAppearance>();
+            info!("A player finished customizing their appearance");
+            continue;
+        }
+        let Some(step) = customizing
+            .navigation
+            .step(action_state.stick(StickAction::Move))
+        else {
+            continue;
+        };
+        if step.y != 0 {
+            // Up on the stick moves to the row above.
+            customizing.row = wrap_index(customizing.row, -step.y, CUSTOMIZATION_ROWS.len());
+        } else {
+            change_row(
+                &mut appearance,
+                CUSTOMIZATION_ROWS[customizing.row],
+                step.x,
+                &catalog,
+            );
+        }
+        if let Ok(mut text) = prompts.get_mut(prompt) {
+            text.sections[0].value = customization_text(&appearance, customizing.row);
+        }
+    }
+}
@@ -0,0 +1,81 @@
+use std::fs;
+use std::path::PathBuf;
+
+use bevy::prelude::warn;
+
+use crate::storage::{read_ron_file, write_ron_file, StorageError};
+
+use super::components::{PlayerProfile, SavedProfileIds};
+
+/// The directory the player profiles are saved in.
+const PROFILE_DIRECTORY: &str = "profiles";
+
+/// Returns the id of a profile with the name. The id is the file name of the profile so
+/// it only keeps letters and digits.
+pub fn profile_id_from_name(name: &str) -> String {
+    return name
+        .chars()
+        .map(|character| {
+            if character.is_ascii_alphanumeric() {
+                character.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect();
+}
+
+/// Returns the path of the profile file with the id.
+fn profile_path(profile_id: &str) -> PathBuf {
+    return PathBuf::from(PROFILE_DIRECTORY).join(format!("{}.ron", profile_id));
+}
+
+/// Loads every saved player profile with its id, sorted by id. Profiles that can not be
+/// read are skipped.
+pub fn load_profiles() -> Vec<(String, PlayerProfile)> {
+    let Ok(entries) = fs::read_dir(PROFILE_DIRECTORY) else {
+        return Vec::new();
+    };
+    let mut profiles: Vec<(String, PlayerProfile)> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "ron"))
+        .filter_map(|path| {
+            let profile_id = path.file_stem()?.to_str()?.to_string();
+            return match read_ron_file(&path) {
+                Ok(profile) => Some((profile_id, profile)),
+                Err(error) => {
+                    warn!(
+                        "Could not load the player profile at {}: {}",
+                        path.display(),
+                        error
+                    );
+                    None
+                }
+            };
+        })
+        .collect();
+    profiles.sort_by(|first, second| first.0.cmp(&second.0));
+    return profiles;
+}
+
+/// Loads the ids of the saved player profiles, including the ones that can not be read
+/// so new profiles do not overwrite them.
+pub fn load_saved_profile_ids() -> SavedProfileIds {
+    let Ok(entries) = fs::read_dir(PROFILE_DIRECTORY) else {
+        return SavedProfileIds::default();
+    };
+    return SavedProfileIds(
+        entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|extension| extension == "ron"))
+            .filter_map(|path| Some(path.file_stem()?.to_str()?.to_string()))
+            .collect(),
+    );
+}
+
+/// Saves the player profile with the id.
+pub fn save_profile(profile_id: &str, profile: &PlayerProfile) -> Result<(), StorageError> {
+    return write_ron_file(&profile_path(profile_id), profile);
+}
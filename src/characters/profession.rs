@@ -0,0 +1,5 @@
+/// profession module for the characters module. The professions of the community, which
+/// role every character fills and how players take over the roles of NPCs.
+pub(crate) mod components;
+pub(crate) mod control;
+pub(crate) mod start_up;
@@ -0,0 +1,134 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::{Component, Entity, Resource};
+use serde::{Deserialize, Serialize};
+
+use crate::ui::menu_navigation::MenuNavigation;
+
+/// A component with the id of the profession of a character.
+#[derive(Component)]
+pub struct Profession(pub String);
+
+/// A component with the world location a character works at.
+#[derive(Component)]
+pub struct Workplace {
+    pub location: String,
+}
+
+/// A component with the ids of the tools a character uses for their profession.
+#[derive(Component, Clone, Default)]
+pub struct ProfessionTools {
+    pub tools: Vec<String>,
+}
+
+/// A component with how far a character is along the quest lines they are part of, by
+/// the id of the quest line. Stage 0 is the start of the story.
+#[derive(Component, Clone, Default)]
+pub struct QuestLines(pub BTreeMap<String, u32>);
+
+/// The data of a profession. The NPCs are the ids of the NPC definitions that fill the
+/// role, in the order they join the community. The first one fills the role when the
+/// game starts.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ProfessionDefinition {
+    pub id: String,
+    pub name: String,
+    /// The world location the profession works at.
+    pub workplace: String,
+    pub tools: Vec<String>,
+    pub quest_lines: Vec<String>,
+    pub npcs: Vec<String>,
+}
+
+impl ProfessionDefinition {
+    /// Returns the quest lines of the profession at the start of their stories.
+    pub fn starting_quest_lines(&self) -> QuestLines {
+        return QuestLines(
+            self.quest_lines
+                .iter()
+                .map(|quest_line| (quest_line.clone(), 0))
+                .collect(),
+        );
+    }
+}
+
+/// A Bevy Engine resource with the professions of the community.
+#[derive(Resource, Default, Deserialize)]
+#[serde(transparent)]
+pub struct ProfessionRegistry {
+    pub professions: Vec<ProfessionDefinition>,
+}
+
+impl ProfessionRegistry {
+    /// Returns the profession with the id.
+    pub fn find(&self, id: &str) -> Option<&ProfessionDefinition> {
+        return self
+            .professions
+            .iter()
+            .find(|profession| profession.id == id);
+    }
+
+    /// Returns true when the NPC definition is the one that fills a role when the game
+    /// starts, or fills no role at all. The later NPCs of a role only join when the role
+    /// is vacated.
+    pub fn spawns_at_start(&self, npc_id: &str) -> bool {
+        return !self.professions.iter().any(|profession| {
+            profession
+                .npcs
+                .iter()
+                .skip(1)
+                .any(|successor| successor == npc_id)
+        });
+    }
+}
+
+/// Who fills the role of a profession.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RoleHolder {
+    #[default]
+    Vacant,
+    Npc(Entity),
+    Player(Entity),
+}
+
+/// Who fills the role of a profession, and which of the NPCs of the profession joins
+/// the community next when the role is vacated.
+#[derive(Default)]
+pub struct Role {
+    pub holder: RoleHolder,
+    pub next_npc: usize,
+}
+
+/// A Bevy Engine resource with the roles of the professions by the id of the profession.
+#[derive(Resource, Default)]
+pub struct ProfessionRoles(pub BTreeMap<String, Role>);
+
+impl ProfessionRoles {
+    /// Returns the id of the profession whose role the holder fills.
+    pub fn profession_of(&self, holder: RoleHolder) -> Option<String> {
+        return self
+            .0
+            .iter()
+            .find(|(_, role)| role.holder == holder)
+            .map(|(profession_id, _)| profession_id.clone());
+    }
+}
+
+/// An event to make a player take on a profession, or give up their profession when the
+/// profession is None.
+pub struct AssignProfessionEvent {
+    pub player: Entity,
+    pub profession: Option<String>,
+}
+
+/// A component on a player character that has the profession menu open. The player
+/// cycles through the professions with the move stick, confirms with the interact action
+/// and closes the menu with the choose profession action. A choice of None gives up the
+/// profession of the player.
+#[derive(Component)]
+pub struct ChoosingProfession {
+    pub choices: Vec<Option<String>>,
+    pub selected: usize,
+    pub navigation: MenuNavigation,
+    pub prompt: Entity,
+}
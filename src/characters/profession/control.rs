@@ -0,0 +1,396 @@
+use bevy::prelude::{
+    info, warn, Added, Color, Commands, DespawnRecursiveExt, DetectChangesMut, Entity, EventReader,
+    EventWriter, Query, Res, ResMut, Text, TextBundle, TextStyle, With, Without,
+};
+
+use crate::crafting::components::CraftingMenu;
+use crate::game_world::WorldLocations;
+use crate::items::components::{InventoryMenu, ItemRegistry};
+use crate::items::inventory::Inventory;
+use crate::ui::menu_navigation::{wrap_index, MenuNavigation};
+use crate::ui::player_overlay::{spawn_in_player_overlay, PlayerOverlayLink};
+use crate::ui::start_up::UiFont;
+
+use super::super::components::{MovementIntent, Name, NonPlayerCharacter};
+use super::super::npc::components::NpcCatalog;
+use super::super::npc::start_up::spawn_npc;
+use super::super::player::entity::components::PlayerCharacter;
+use super::super::player::input::components::{
+    ActionState, ButtonAction, ControlsMenu, StickAction,
+};
+use super::super::player::profile::components::{
+    ActiveProfile, ChoosingProfile, CustomizingAppearance,
+};
+use super::components::{
+    AssignProfessionEvent, ChoosingProfession, Profession, ProfessionRegistry, ProfessionRoles,
+    ProfessionTools, QuestLines, RoleHolder, Workplace,
+};
+
+/// Returns the text of the profession menu for the selected choice, with who fills the
+/// role of the profession now.
+fn profession_menu_text(
+    choices: &[Option<String>],
+    selected: usize,
+    registry: &ProfessionRegistry,
+    roles: &ProfessionRoles,
+    names: &Query<&Name>,
+) -> String {
+    let choice_text = match &choices[selected] {
+        None => "No profession".to_string(),
+        Some(profession_id) => {
+            let profession_name = registry
+                .find(profession_id)
+                .map_or(profession_id.as_str(), |profession| {
+                    profession.name.as_str()
+                });
+            let holder = roles
+                .0
+                .get(profession_id)
+                .map_or(RoleHolder::Vacant, |role| role.holder);
+            let holder_text = match holder {
+                RoleHolder::Vacant => "vacant".to_string(),
+                RoleHolder::Npc(entity) | RoleHolder::Player(entity) => names
+                    .get(entity)
+                    .map_or("vacant".to_string(), |name| format!("held by {}", name.0)),
+            };
+            format!("{} ({})", profession_name, holder_text)
+        }
+    };
+    return format!(
+        "Choose a profession ({}/{})\n<  {}  >\nInteract to confirm",
+        selected + 1,
+        choices.len(),
+        choice_text
+    );
+}
+
+/// A system that opens the profession menu of every player that presses the choose
+/// profession action while they are not in another menu. The player stops moving while
+/// the menu is open.
+pub fn open_profession_menus(
+    mut commands: Commands,
+    ui_font: Res<UiFont>,
+    registry: Res<ProfessionRegistry>,
+    roles: Res<ProfessionRoles>,
+    mut players: Query<
+        (
+            Entity,
+            &ActionState,
+            &PlayerOverlayLink,
+            &mut MovementIntent,
+        ),
+        (
+            With<ActiveProfile>,
+            Without<ChoosingProfession>,
+            Without<ChoosingProfile>,
+            Without<CustomizingAppearance>,
+            Without<InventoryMenu>,
+            Without<CraftingMenu>,
+            Without<ControlsMenu>,
+        ),
+    >,
+    names: Query<&Name>,
+) {
+    for (player_entity, action_state, overlay_link, mut movement_intent) in players.iter_mut() {
+        if !action_state.just_pressed(ButtonAction::ChooseProfession) {
+            continue;
+        }
+        let choices: Vec<Option<String>> = std::iter::once(None)
+            .chain(
+                registry
+                    .professions
+                    .iter()
+                    .map(|profession| Some(profession.id.clone())),
+            )
+            .collect();
+        let current_profession = roles.profession_of(RoleHolder::Player(player_entity));
+        let selected = choices
+            .iter()
+            .position(|choice| *choice == current_profession)
+            .unwrap_or(0);
+        let prompt = spawn_in_player_overlay(
+            &mut commands,
+            overlay_link,
+            TextBundle::from_section(
+                profession_menu_text(&choices, selected, &registry, &roles, &names),
+                TextStyle {
+                    font: ui_font.0.clone(),
+                    font_size: 28.0,
+                    color: Color::WHITE,
+                },
+            ),
+        );
+        *movement_intent = MovementIntent::default();
+        commands.entity(player_entity).insert(ChoosingProfession {
+            choices: choices,
+            selected: selected,
+            navigation: MenuNavigation::default(),
+            prompt: prompt,
+        });
+    }
+}
+
+/// A system that lets the players with the profession menu open cycle through the
+/// professions. Confirming a profession assigns it to the player, and the choose
+/// profession action closes the menu without changing the profession.
+pub fn choose_professions(
+    mut commands: Commands,
+    registry: Res<ProfessionRegistry>,
+    roles: Res<ProfessionRoles>,
+    mut players: Query<(Entity, &ActionState, &mut ChoosingProfession)>,
+    names: Query<&Name>,
+    mut prompts: Query<&mut Text>,
+    mut assign_events: EventWriter<AssignProfessionEvent>,
+) {
+    for (player_entity, action_state, mut choosing) in players.iter_mut() {
+        let confirmed = action_state.just_pressed(ButtonAction::Interact);
+        if confirmed || action_state.just_pressed(ButtonAction::ChooseProfession) {
+            if confirmed {
+                assign_events.send(AssignProfessionEvent {
+                    player: player_entity,
+                    profession: choosing.choices[choosing.selected].clone(),
+                });
+            }
+            commands.entity(choosing.prompt).despawn_recursive();
+            commands
+                .entity(player_entity)
+                .remove::<ChoosingProfession>();
+            continue;
+        }
+        let step = choosing
+            .navigation
+            .step(action_state.stick(StickAction::Move));
+        if let Some(step) = step.filter(|step| step.x != 0) {
+            choosing.selected = wrap_index(choosing.selected, step.x, choosing.choices.len());
+            if let Ok(mut prompt) = prompts.get_mut(choosing.prompt) {
+                prompt.sections[0].value = profession_menu_text(
+                    &choosing.choices,
+                    choosing.selected,
+                    &registry,
+                    &roles,
+                    &names,
+                );
+            }
+        }
+    }
+}
+
+/// A system that gives every player that chose a profile the quest progress saved in
+/// it, and takes on the profession saved in it.
+pub fn assign_profile_professions(
+    mut players: Query<(Entity, &ActiveProfile, &mut QuestLines), Added<ActiveProfile>>,
+    mut assign_events: EventWriter<AssignProfessionEvent>,
+) {
+    for (player_entity, active_profile, mut quest_lines) in players.iter_mut() {
+        quest_lines.0 = active_profile.profile.quest_lines.clone();
+        if active_profile.profile.profession.is_some() {
+            assign_events.send(AssignProfessionEvent {
+                player: player_entity,
+                profession: active_profile.profile.profession.clone(),
+            });
+        }
+    }
+}
+
+/// Brings the next NPC of the profession into the community to fill its vacated role,
+/// at the start of the story of the role. The NPCs of the profession take turns, so the
+/// first one comes back once all of them have had the role.
+fn bring_in_next_npc(
+    commands: &mut Commands,
+    profession_id: &str,
+    registry: &ProfessionRegistry,
+    catalog: &NpcCatalog,
+    locations: &WorldLocations,
+    roles: &mut ProfessionRoles,
+) {
+    let Some(role) = roles.0.get_mut(profession_id) else {
+        return;
+    };
+    role.holder = RoleHolder::Vacant;
+    let Some(profession) = registry.find(profession_id) else {
+        return;
+    };
+    if profession.npcs.is_empty() {
+        return;
+    }
+    let npc_id = &profession.npcs[role.next_npc % profession.npcs.len()];
+    role.next_npc += 1;
+    let Some(definition) = catalog.find(npc_id) else {
+        warn!(
+            "There is no NPC {} to fill the role {}",
+            npc_id, profession_id
+        );
+        return;
+    };
+    if let Some(npc_entity) = spawn_npc(commands, definition, registry, locations) {
+        info!(
+            "{} joined the community as the {}",
+            definition.name, profession.name
+        );
+        role.holder = RoleHolder::Npc(npc_entity);
+    }
+}
+
+/// A system that assigns professions to players. The player gives their old role to a
+/// fresh NPC along with the quest lines of the role. The NPC that held the new role
+/// retires from the community and hands its workplace, tools and quest progress over to
+/// the player. Progress the player saved in their profile takes priority over the
+/// progress of the NPC. The handed over tools the player does not have yet are put in
+/// their inventory.
+pub fn assign_professions(
+    mut commands: Commands,
+    mut assign_events: EventReader<AssignProfessionEvent>,
+    registry: Res<ProfessionRegistry>,
+    item_registry: Res<ItemRegistry>,
+    catalog: Res<NpcCatalog>,
+    locations: Res<WorldLocations>,
+    mut roles: ResMut<ProfessionRoles>,
+    mut players: Query<
+        (
+            &Name,
+            &mut QuestLines,
+            &mut ActiveProfile,
+            Option<&mut Inventory>,
+        ),
+        (With<PlayerCharacter>, Without<NonPlayerCharacter>),
+    >,
+    npcs: Query<
+        (&Name, &Workplace, &ProfessionTools, &QuestLines),
+        (With<NonPlayerCharacter>, Without<PlayerCharacter>),
+    >,
+) {
+    for event in assign_events.iter() {
+        let Ok((player_name, mut quest_lines, mut active_profile, mut inventory)) =
+            players.get_mut(event.player)
+        else {
+            continue;
+        };
+        let current_profession = roles.profession_of(RoleHolder::Player(event.player));
+        if current_profession == event.profession {
+            continue;
+        }
+        let new_role = match &event.profession {
+            None => None,
+            Some(profession_id) => {
+                let Some(profession) = registry.find(profession_id) else {
+                    warn!("There is no profession {}", profession_id);
+                    continue;
+                };
+                let holder = roles
+                    .0
+                    .get(profession_id)
+                    .map_or(RoleHolder::Vacant, |role| role.holder);
+                if let RoleHolder::Player(_) = holder {
+                    warn!(
+                        "{} can not become the {}, another player has the role",
+                        player_name.0, profession.name
+                    );
+                    continue;
+                }
+                Some((profession, holder))
+            }
+        };
+        if let Some(old_profession_id) = current_profession {
+            // The quest lines stay with the role for the NPC that takes it over.
+            if let Some(old_profession) = registry.find(&old_profession_id) {
+                quest_lines
+                    .0
+                    .retain(|quest_line, _| !old_profession.quest_lines.contains(quest_line));
+            }
+            commands
+                .entity(event.player)
+                .remove::<(Profession, Workplace, ProfessionTools)>();
+            info!("{} left the role {}", player_name.0, old_profession_id);
+            bring_in_next_npc(
+                &mut commands,
+                &old_profession_id,
+                &registry,
+                &catalog,
+                &locations,
+                &mut roles,
+            );
+        }
+        if let Some((profession, holder)) = new_role {
+            let mut workplace = profession.workplace.clone();
+            let mut tools = profession.tools.clone();
+            let mut handed_over_quest_lines = profession.starting_quest_lines();
+            if let RoleHolder::Npc(npc_entity) = holder {
+                if let Ok((npc_name, npc_workplace, npc_tools, npc_quest_lines)) =
+                    npcs.get(npc_entity)
+                {
+                    info!(
+                        "{} retired and handed the role of {} over to {}",
+                        npc_name.0, profession.name, player_name.0
+                    );
+                    workplace = npc_workplace.location.clone();
+                    tools = npc_tools.tools.clone();
+                    handed_over_quest_lines = npc_quest_lines.clone();
+                }
+                commands.entity(npc_entity).despawn_recursive();
+            }
+            for (quest_line, stage) in handed_over_quest_lines.0 {
+                quest_lines.0.entry(quest_line).or_insert(stage);
+            }
+            if let Some(inventory) = inventory.as_mut() {
+                for tool in tools.iter() {
+                    if inventory.count_of(tool) > 0 {
+                        continue;
+                    }
+                    if inventory.add(&item_registry, tool, 1) > 0 {
+                        warn!(
+                            "{} has no room for the {}",
+                            player_name.0,
+                            item_registry.name(tool)
+                        );
+                    }
+                }
+            }
+            commands.entity(event.player).insert((
+                Profession(profession.id.clone()),
+                Workplace {
+                    location: workplace,
+                },
+                ProfessionTools { tools: tools },
+            ));
+            if let Some(role) = roles.0.get_mut(&profession.id) {
+                role.holder = RoleHolder::Player(event.player);
+            }
+            info!("{} is now the {}", player_name.0, profession.name);
+        }
+        active_profile.profile.profession = event.profession.clone();
+        // Marks the quest lines as changed even when the roles had none, so the profile
+        // is saved with the new profession.
+        quest_lines.set_changed();
+    }
+}
+
+/// A system that brings in a fresh NPC for every role held by a player that left the
+/// game.
+pub fn refill_roles_of_departed_players(
+    mut commands: Commands,
+    registry: Res<ProfessionRegistry>,
+    catalog: Res<NpcCatalog>,
+    locations: Res<WorldLocations>,
+    mut roles: ResMut<ProfessionRoles>,
+    players: Query<(), With<PlayerCharacter>>,
+) {
+    let vacated: Vec<String> = roles
+        .0
+        .iter()
+        .filter(|(_, role)| match role.holder {
+            RoleHolder::Player(player_entity) => players.get(player_entity).is_err(),
+            _ => false,
+        })
+        .map(|(profession_id, _)| profession_id.clone())
+        .collect();
+    for profession_id in vacated {
+        bring_in_next_npc(
+            &mut commands,
+            &profession_id,
+            &registry,
+            &catalog,
+            &locations,
+            &mut roles,
+        );
+    }
+}
@@ -0,0 +1,13 @@
+use std::path::Path;
+
+use crate::storage::load_ron_or_default;
+
+use super::components::ProfessionRegistry;
+
+/// The file the professions of the community are loaded from.
+const PROFESSION_REGISTRY_PATH: &str = "assets/characters/professions.ron";
+
+/// Loads the professions of the community from their data file.
+pub fn load_profession_registry() -> ProfessionRegistry {
+    return load_ron_or_default(Path::new(PROFESSION_REGISTRY_PATH), "the professions");
+}
@@ -0,0 +1,21 @@
+use std::path::PathBuf;
+
+use crate::storage::load_ron_or_default;
+
+use super::components::MovementTuning;
+
+/// The directory the movement tuning files of the characters are loaded from.
+const MOVEMENT_TUNING_DIRECTORY: &str = "assets/characters/movement";
+
+/// Returns the path of the movement tuning file with the name.
+fn movement_tuning_path(name: &str) -> PathBuf {
+    return PathBuf::from(MOVEMENT_TUNING_DIRECTORY).join(format!("{}.ron", name));
+}
+
+/// Loads the movement tuning with the name from its data file.
+pub fn load_movement_tuning(name: &str) -> MovementTuning {
+    return load_ron_or_default(
+        &movement_tuning_path(name),
+        &format!("the movement tuning {}", name),
+    );
+}
@@ -0,0 +1,6 @@
+/// collision module for the crate. A small self-contained collision library for the
+/// static world geometry that does not depend on a physics engine.
+pub(crate) mod components;
+pub(crate) mod contact;
+pub(crate) mod kinematic;
+pub(crate) mod ray_cast;
@@ -0,0 +1,67 @@
+use bevy::prelude::{Component, GlobalTransform, Mat3, Quat, Vec3};
+
+/// The shape of a collider in the local space of its entity.
+#[derive(Clone, Copy)]
+pub enum ColliderShape {
+    /// A box with the provided half extents along each local axis.
+    Cuboid {
+        half_extents: Vec3,
+    },
+    Sphere {
+        radius: f32,
+    },
+}
+
+/// A component for world geometry that does not move, like the ground, buildings, trees
+/// and rocks. The shape is placed at the offset from the entity and follows the entity
+/// rotation and scale.
+#[derive(Component, Clone, Copy)]
+pub struct StaticCollider {
+    pub shape: ColliderShape,
+    pub offset: Vec3,
+}
+
+/// A collider shape placed in the world. The rotation and scale of the entity are
+/// applied to the shape so the collision queries only need to deal with the world
+/// position.
+#[derive(Clone, Copy)]
+pub struct WorldCollider {
+    pub shape: ColliderShape,
+    pub center: Vec3,
+    pub rotation: Quat,
+}
+
+impl WorldCollider {
+    /// Places the static collider in the world using the global transform of its entity.
+    pub fn from_static_collider(collider: &StaticCollider, transform: &GlobalTransform) -> Self {
+        let (scale, rotation, translation) = transform.to_scale_rotation_translation();
+        let shape = match collider.shape {
+            ColliderShape::Cuboid { half_extents } => ColliderShape::Cuboid {
+                half_extents: half_extents * scale,
+            },
+            ColliderShape::Sphere { radius } => ColliderShape::Sphere {
+                radius: radius * scale.max_element(),
+            },
+        };
+        return WorldCollider {
+            shape: shape,
+            center: translation + rotation * (collider.offset * scale),
+            rotation: rotation,
+        };
+    }
+
+    /// Returns the minimum and maximum corners of the axis aligned box around the
+    /// collider.
+    pub fn bounds(&self) -> (Vec3, Vec3) {
+        let half_size = match self.shape {
+            ColliderShape::Cuboid { half_extents } => {
+                let rotation = Mat3::from_quat(self.rotation);
+                rotation.x_axis.abs() * half_extents.x
+                    + rotation.y_axis.abs() * half_extents.y
+                    + rotation.z_axis.abs() * half_extents.z
+            }
+            ColliderShape::Sphere { radius } => Vec3::splat(radius),
+        };
+        return (self.center - half_size, self.center + half_size);
+    }
+}
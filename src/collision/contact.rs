@@ -0,0 +1,98 @@
+use bevy::prelude::Vec3;
+
+use super::components::{ColliderShape, WorldCollider};
+
+/// The information about how deep a shape is inside a collider.
+#[derive(Clone, Copy)]
+pub struct Contact {
+    /// The world direction that moves the shape out of the collider.
+    pub normal: Vec3,
+    /// How far the shape has to move along the normal to stop touching the collider.
+    pub depth: f32,
+}
+
+/// Returns the point of the collider closest to the point. Points inside the collider
+/// are their own closest point.
+pub fn closest_point_on_collider(point: Vec3, collider: &WorldCollider) -> Vec3 {
+    return match collider.shape {
+        ColliderShape::Cuboid { half_extents } => {
+            let local_point = collider.rotation.inverse() * (point - collider.center);
+            let clamped_point = local_point.clamp(-half_extents, half_extents);
+            collider.center + collider.rotation * clamped_point
+        }
+        ColliderShape::Sphere { radius } => {
+            let offset = point - collider.center;
+            if offset.length_squared() <= radius * radius {
+                point
+            } else {
+                collider.center + offset.normalize() * radius
+            }
+        }
+    };
+}
+
+/// Returns true when the point is inside the collider or on its surface.
+pub fn contains_point(collider: &WorldCollider, point: Vec3) -> bool {
+    return closest_point_on_collider(point, collider).distance_squared(point) <= f32::EPSILON;
+}
+
+/// Returns the contact of a sphere whose center is inside a collider. The normal points
+/// to the closest face of the collider.
+fn contact_from_inside(center: Vec3, radius: f32, collider: &WorldCollider) -> Contact {
+    return match collider.shape {
+        ColliderShape::Cuboid { half_extents } => {
+            let local_center = collider.rotation.inverse() * (center - collider.center);
+            let mut closest_axis = 0;
+            let mut closest_face_distance = f32::INFINITY;
+            for axis in 0..3 {
+                let face_distance = half_extents[axis] - local_center[axis].abs();
+                if face_distance < closest_face_distance {
+                    closest_axis = axis;
+                    closest_face_distance = face_distance;
+                }
+            }
+            let mut local_normal = Vec3::ZERO;
+            local_normal[closest_axis] = if local_center[closest_axis] < 0.0 {
+                -1.0
+            } else {
+                1.0
+            };
+            Contact {
+                normal: collider.rotation * local_normal,
+                depth: closest_face_distance + radius,
+            }
+        }
+        ColliderShape::Sphere {
+            radius: collider_radius,
+        } => {
+            let offset = center - collider.center;
+            let normal = if offset.length_squared() <= f32::EPSILON {
+                Vec3::Y
+            } else {
+                offset.normalize()
+            };
+            Contact {
+                normal: normal,
+                depth: collider_radius + radius - offset.length(),
+            }
+        }
+    };
+}
+
+/// Returns the contact of a sphere overlapping a collider, or None when they do not
+/// overlap.
+pub fn sphere_contact(center: Vec3, radius: f32, collider: &WorldCollider) -> Option<Contact> {
+    let closest_point = closest_point_on_collider(center, collider);
+    let offset = center - closest_point;
+    let distance = offset.length();
+    if distance >= radius {
+        return None;
+    }
+    if distance <= f32::EPSILON {
+        return Some(contact_from_inside(center, radius, collider));
+    }
+    return Some(Contact {
+        normal: offset / distance,
+        depth: radius - distance,
+    });
+}
@@ -0,0 +1,490 @@
+use bevy::prelude::Vec3;
+
+use super::components::WorldCollider;
+use super::contact::{sphere_contact, Contact};
+
+/// The number of times the overlaps of a shape are resolved after it moves.
+const MAX_RESOLVE_ITERATIONS: usize = 4;
+/// The distance below a shape that is checked for ground after it moves.
+const GROUND_PROBE_DISTANCE: f32 = 0.02;
+/// The most sub steps a move is split into.
+const MAX_MOVE_STEPS: usize = 32;
+
+/// An upright capsule centered on the position of a character.
+#[derive(Clone, Copy)]
+pub struct CapsuleShape {
+    pub radius: f32,
+    /// The distance from the center to the top and bottom of the capsule. A half height
+    /// equal to the radius makes the capsule a sphere.
+    pub half_height: f32,
+}
+
+impl CapsuleShape {
+    /// Returns the centers of the spheres used to approximate the capsule at the
+    /// position. The spheres are at most a radius apart along the capsule axis.
+    pub fn sphere_centers(&self, position: Vec3) -> Vec<Vec3> {
+        let segment_half_length = (self.half_height - self.radius).max(0.0);
+        let sphere_count =
+            (2.0 * segment_half_length / self.radius.max(f32::EPSILON)).ceil() as usize + 1;
+        if sphere_count <= 1 {
+            return vec![position];
+        }
+        return (0..sphere_count)
+            .map(|index| {
+                let blend = index as f32 / (sphere_count - 1) as f32;
+                position + Vec3::Y * (-segment_half_length + 2.0 * segment_half_length * blend)
+            })
+            .collect();
+    }
+}
+
+/// The settings of a kinematic move that decide what counts as ground and how the shape
+/// follows it.
+#[derive(Clone, Copy)]
+pub struct KinematicSettings {
+    /// The steepest slope in radians the shape can stand on and walk up.
+    pub max_slope_angle: f32,
+    /// The highest ledge the shape steps up on while walking into it.
+    pub step_height: f32,
+    /// How far down the shape is pulled to keep it on the ground while walking down
+    /// slopes and steps.
+    pub snap_distance: f32,
+}
+
+impl KinematicSettings {
+    /// Returns true when a surface with the normal can be stood on.
+    pub fn is_walkable(&self, normal: Vec3) -> bool {
+        return normal.y >= self.max_slope_angle.cos();
+    }
+}
+
+/// The result of a kinematic move.
+#[derive(Clone, Copy)]
+pub struct KinematicMove {
+    pub position: Vec3,
+    pub grounded: bool,
+    /// The normal of the ground under the shape, or up when the shape is not grounded.
+    pub ground_normal: Vec3,
+}
+
+/// The contact of a capsule with a collider.
+#[derive(Clone, Copy)]
+struct CapsuleContact {
+    contact: Contact,
+    /// If the contact is with the bottom sphere of the capsule, the only part of the
+    /// capsule that can stand on ground.
+    on_bottom: bool,
+}
+
+/// Returns the deepest contact of the capsule with the collider.
+fn capsule_contact(
+    position: Vec3,
+    capsule: &CapsuleShape,
+    collider: &WorldCollider,
+) -> Option<CapsuleContact> {
+    return capsule
+        .sphere_centers(position)
+        .into_iter()
+        .enumerate()
+        .filter_map(|(index, center)| {
+            return sphere_contact(center, capsule.radius, collider).map(|contact| {
+                return CapsuleContact {
+                    contact: contact,
+                    on_bottom: index == 0,
+                };
+            });
+        })
+        .max_by(|first, second| first.contact.depth.total_cmp(&second.contact.depth));
+}
+
+/// Returns the deepest contact of the capsule with any of the colliders.
+fn deepest_contact(
+    position: Vec3,
+    capsule: &CapsuleShape,
+    colliders: &[WorldCollider],
+) -> Option<CapsuleContact> {
+    return colliders
+        .iter()
+        .filter_map(|collider| capsule_contact(position, capsule, collider))
+        .max_by(|first, second| first.contact.depth.total_cmp(&second.contact.depth));
+}
+
+/// Moves the capsule out of the colliders it overlaps. Walkable ground under the bottom
+/// of the capsule pushes it straight up so it does not slide down slopes. Steep surfaces,
+/// and ledges the capsule walks into above its bottom, only push it sideways so it can
+/// not climb them. Returns the new position and the normal of the ground that was
+/// touched, if any.
+fn resolve_overlaps(
+    mut position: Vec3,
+    capsule: &CapsuleShape,
+    settings: &KinematicSettings,
+    colliders: &[WorldCollider],
+) -> (Vec3, Option<Vec3>) {
+    let mut ground_normal = None;
+    for _ in 0..MAX_RESOLVE_ITERATIONS {
+        let CapsuleContact { contact, on_bottom } =
+            match deepest_contact(position, capsule, colliders) {
+                Some(capsule_contact) => capsule_contact,
+                None => break,
+            };
+        let horizontal_normal = Vec3::new(contact.normal.x, 0.0, contact.normal.z);
+        if on_bottom && settings.is_walkable(contact.normal) {
+            position.y += contact.depth / contact.normal.y;
+            ground_normal = Some(contact.normal);
+        } else if contact.normal.y > 0.0 && horizontal_normal.length() > f32::EPSILON {
+            position += horizontal_normal.normalize() * contact.depth / horizontal_normal.length();
+        } else {
+            position += contact.normal * contact.depth;
+        }
+    }
+    return (position, ground_normal);
+}
+
+/// Moves the capsule by the displacement in steps no longer than half its radius so it
+/// does not pass through thin colliders, resolving its overlaps after each step.
+/// Returns the new position and the normal of the last ground that was touched.
+fn move_in_steps(
+    position: Vec3,
+    displacement: Vec3,
+    capsule: &CapsuleShape,
+    settings: &KinematicSettings,
+    colliders: &[WorldCollider],
+) -> (Vec3, Option<Vec3>) {
+    let step_length = (capsule.radius * 0.5).max(f32::EPSILON);
+    let step_count =
+        ((displacement.length() / step_length).ceil() as usize).clamp(1, MAX_MOVE_STEPS);
+    let step = displacement / step_count as f32;
+    let mut position = position;
+    let mut ground_normal = None;
+    for _ in 0..step_count {
+        let (resolved_position, step_ground_normal) =
+            resolve_overlaps(position + step, capsule, settings, colliders);
+        position = resolved_position;
+        ground_normal = step_ground_normal.or(ground_normal);
+    }
+    return (position, ground_normal);
+}
+
+/// Tries to walk the horizontal displacement on top of a ledge no higher than the step
+/// height. Returns the position on top of the ledge when the capsule could step on it.
+fn try_step_up(
+    position: Vec3,
+    horizontal_displacement: Vec3,
+    capsule: &CapsuleShape,
+    settings: &KinematicSettings,
+    colliders: &[WorldCollider],
+) -> Option<Vec3> {
+    let raised_position = position + Vec3::Y * settings.step_height;
+    if deepest_contact(raised_position, capsule, colliders).is_some() {
+        // There is no room above the capsule to step up.
+        return None;
+    }
+    let (moved_position, _) = move_in_steps(
+        raised_position,
+        horizontal_displacement,
+        capsule,
+        settings,
+        colliders,
+    );
+    let (landed_position, ground_normal) = move_in_steps(
+        moved_position,
+        Vec3::NEG_Y * settings.step_height,
+        capsule,
+        settings,
+        colliders,
+    );
+    // Walking into the rounded edge of a ledge can lift the capsule above the step
+    // height, so ledges it lands on higher than that are too high to step on.
+    if ground_normal.is_none() || landed_position.y > raised_position.y + f32::EPSILON {
+        return None;
+    }
+    return Some(landed_position);
+}
+
+/// Moves an upright capsule by the displacement through the static colliders. The
+/// capsule slides along the colliders it walks into, steps up low ledges and follows the
+/// ground down slopes and steps while it was grounded before the move. The horizontal
+/// and vertical parts of the displacement are moved separately so walking is not slowed
+/// by the ground.
+pub fn move_kinematic_capsule(
+    position: Vec3,
+    displacement: Vec3,
+    was_grounded: bool,
+    capsule: &CapsuleShape,
+    settings: &KinematicSettings,
+    colliders: &[WorldCollider],
+) -> KinematicMove {
+    let horizontal_displacement = Vec3::new(displacement.x, 0.0, displacement.z);
+    let (mut position_after_walk, _) = move_in_steps(
+        position,
+        horizontal_displacement,
+        capsule,
+        settings,
+        colliders,
+    );
+    let walk_length = horizontal_displacement.length();
+    if was_grounded && settings.step_height > 0.0 && walk_length > f32::EPSILON {
+        let walk_direction = horizontal_displacement / walk_length;
+        let progress = (position_after_walk - position).dot(walk_direction);
+        // Only step up when the walk was blocked.
+        if progress < walk_length * 0.9 {
+            if let Some(stepped_position) = try_step_up(
+                position,
+                horizontal_displacement,
+                capsule,
+                settings,
+                colliders,
+            ) {
+                if (stepped_position - position).dot(walk_direction) > progress + f32::EPSILON {
+                    position_after_walk = stepped_position;
+                }
+            }
+        }
+    }
+    let (mut final_position, mut ground_normal) = move_in_steps(
+        position_after_walk,
+        Vec3::Y * displacement.y,
+        capsule,
+        settings,
+        colliders,
+    );
+    if ground_normal.is_none() && displacement.y <= 0.0 {
+        // Look for ground right below the capsule. When it was grounded before the move
+        // look farther down so it follows the ground down slopes and steps.
+        let probe_distance = if was_grounded {
+            settings.snap_distance.max(GROUND_PROBE_DISTANCE)
+        } else {
+            GROUND_PROBE_DISTANCE
+        };
+        let (snapped_position, snapped_ground_normal) = move_in_steps(
+            final_position,
+            Vec3::NEG_Y * probe_distance,
+            capsule,
+            settings,
+            colliders,
+        );
+        if snapped_ground_normal.is_some() {
+            final_position = snapped_position;
+            ground_normal = snapped_ground_normal;
+        }
+    }
+    return KinematicMove {
+        position: final_position,
+        grounded: ground_normal.is_some(),
+        ground_normal: ground_normal.unwrap_or(Vec3::Y),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::{Quat, Vec3};
+
+    use super::super::components::{ColliderShape, WorldCollider};
+    use super::{move_kinematic_capsule, CapsuleShape, KinematicSettings};
+
+    const CAPSULE: CapsuleShape = CapsuleShape {
+        radius: 0.4,
+        half_height: 0.9,
+    };
+    const SETTINGS: KinematicSettings = KinematicSettings {
+        max_slope_angle: std::f32::consts::FRAC_PI_4,
+        step_height: 0.35,
+        snap_distance: 0.3,
+    };
+
+    fn cuboid(center: Vec3, half_extents: Vec3, rotation: Quat) -> WorldCollider {
+        return WorldCollider {
+            shape: ColliderShape::Cuboid {
+                half_extents: half_extents,
+            },
+            center: center,
+            rotation: rotation,
+        };
+    }
+
+    /// A flat ground with its top at a height of zero.
+    fn ground() -> WorldCollider {
+        return cuboid(
+            Vec3::new(0.0, -0.5, 0.0),
+            Vec3::new(50.0, 0.5, 50.0),
+            Quat::IDENTITY,
+        );
+    }
+
+    /// A box standing on the ground in front of the capsule, along the positive x axis,
+    /// with its near face at x 1.
+    fn block(height: f32) -> WorldCollider {
+        return cuboid(
+            Vec3::new(2.0, height * 0.5, 0.0),
+            Vec3::new(1.0, height * 0.5, 5.0),
+            Quat::IDENTITY,
+        );
+    }
+
+    #[test]
+    fn approximates_the_capsule_with_spheres_along_its_axis() {
+        let centers = CAPSULE.sphere_centers(Vec3::ZERO);
+        assert!((centers.first().unwrap().y + 0.5).abs() < 1e-6);
+        assert!((centers.last().unwrap().y - 0.5).abs() < 1e-6);
+        assert!(centers
+            .windows(2)
+            .all(|pair| pair[1].y - pair[0].y <= CAPSULE.radius + 1e-6));
+        let sphere = CapsuleShape {
+            radius: 0.5,
+            half_height: 0.5,
+        };
+        assert_eq!(sphere.sphere_centers(Vec3::ONE), vec![Vec3::ONE]);
+    }
+
+    #[test]
+    fn lands_on_the_ground() {
+        let result = move_kinematic_capsule(
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(0.0, -0.5, 0.0),
+            false,
+            &CAPSULE,
+            &SETTINGS,
+            &[ground()],
+        );
+        assert!(result.grounded);
+        assert!((result.position.y - 0.9).abs() < 1e-3);
+        assert!(result.ground_normal.abs_diff_eq(Vec3::Y, 1e-4));
+    }
+
+    #[test]
+    fn falls_without_ground() {
+        let result = move_kinematic_capsule(
+            Vec3::new(0.0, 5.0, 0.0),
+            Vec3::new(0.0, -0.5, 0.0),
+            false,
+            &CAPSULE,
+            &SETTINGS,
+            &[ground()],
+        );
+        assert!(!result.grounded);
+        assert!(result.position.abs_diff_eq(Vec3::new(0.0, 4.5, 0.0), 1e-4));
+        assert_eq!(result.ground_normal, Vec3::Y);
+    }
+
+    #[test]
+    fn slides_along_a_wall() {
+        let result = move_kinematic_capsule(
+            Vec3::new(0.0, 0.9, 0.0),
+            Vec3::new(1.0, 0.0, 1.0),
+            true,
+            &CAPSULE,
+            &SETTINGS,
+            &[ground(), block(3.0)],
+        );
+        // The wall stops the capsule at its radius from the wall face, but the motion
+        // along the wall goes on.
+        assert!((result.position.x - 0.6).abs() < 1e-3);
+        assert!((result.position.z - 1.0).abs() < 1e-3);
+        assert!((result.position.y - 0.9).abs() < 1e-3);
+        assert!(result.grounded);
+    }
+
+    #[test]
+    fn steps_up_a_low_ledge() {
+        let result = move_kinematic_capsule(
+            Vec3::new(0.4, 0.9, 0.0),
+            Vec3::new(0.5, 0.0, 0.0),
+            true,
+            &CAPSULE,
+            &SETTINGS,
+            &[ground(), block(0.3)],
+        );
+        // The capsule is on the rounded edge of the ledge, just below the top of it.
+        assert!(result.grounded);
+        assert!(result.position.y > 1.15 && result.position.y <= 1.2 + 1e-3);
+        assert!(result.position.x > 0.8);
+    }
+
+    #[test]
+    fn does_not_step_up_while_in_the_air() {
+        let result = move_kinematic_capsule(
+            Vec3::new(0.4, 0.9, 0.0),
+            Vec3::new(0.5, 0.0, 0.0),
+            false,
+            &CAPSULE,
+            &SETTINGS,
+            &[ground(), block(0.3)],
+        );
+        assert!((result.position.y - 0.9).abs() < 1e-3);
+        assert!(result.position.x < 0.65);
+    }
+
+    #[test]
+    fn does_not_step_up_a_ledge_higher_than_the_step_height() {
+        let result = move_kinematic_capsule(
+            Vec3::new(0.4, 0.9, 0.0),
+            Vec3::new(0.5, 0.0, 0.0),
+            true,
+            &CAPSULE,
+            &SETTINGS,
+            &[ground(), block(0.5)],
+        );
+        assert!((result.position.y - 0.9).abs() < 1e-3);
+        assert!((result.position.x - 0.6).abs() < 1e-3);
+    }
+
+    #[test]
+    fn snaps_down_a_step_only_when_it_was_grounded() {
+        let top_of_block = Vec3::new(2.0, 1.1, 0.0);
+        let walk_off = Vec3::new(1.5, 0.0, 0.0);
+        let grounded = move_kinematic_capsule(
+            top_of_block,
+            walk_off,
+            true,
+            &CAPSULE,
+            &SETTINGS,
+            &[ground(), block(0.2)],
+        );
+        assert!(grounded.grounded);
+        assert!((grounded.position.y - 0.9).abs() < 1e-3);
+        let airborne = move_kinematic_capsule(
+            top_of_block,
+            walk_off,
+            false,
+            &CAPSULE,
+            &SETTINGS,
+            &[ground(), block(0.2)],
+        );
+        assert!(!airborne.grounded);
+        assert!((airborne.position.y - 1.1).abs() < 1e-3);
+    }
+
+    #[test]
+    fn stands_on_gentle_slopes_but_not_on_steep_ones() {
+        let slope = |angle: f32| {
+            return cuboid(
+                Vec3::ZERO,
+                Vec3::new(20.0, 0.01, 20.0),
+                Quat::from_rotation_z(angle),
+            );
+        };
+        let gentle = move_kinematic_capsule(
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(0.0, -0.5, 0.0),
+            false,
+            &CAPSULE,
+            &SETTINGS,
+            &[slope(0.3)],
+        );
+        assert!(gentle.grounded);
+        // Walkable ground pushes the capsule straight up so it does not slide down.
+        assert!(gentle.position.x.abs() < 1e-3);
+        assert!((gentle.ground_normal.angle_between(Vec3::Y) - 0.3).abs() < 1e-3);
+        let steep = move_kinematic_capsule(
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(0.0, -0.5, 0.0),
+            false,
+            &CAPSULE,
+            &SETTINGS,
+            &[slope(1.2)],
+        );
+        assert!(!steep.grounded);
+        // Steep ground only pushes the capsule sideways, down the slope.
+        assert!(steep.position.x.abs() > 0.1);
+    }
+}
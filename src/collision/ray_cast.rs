@@ -0,0 +1,133 @@
+use bevy::prelude::{Entity, Vec3};
+
+use super::components::{ColliderShape, WorldCollider};
+
+/// The information about where a ray hit a collider.
+#[derive(Clone, Copy)]
+pub struct RayHit {
+    pub entity: Entity,
+    /// The distance along the ray to the hit point.
+    pub distance: f32,
+}
+
+/// Intersects a ray with a box centered on the origin. The ray is in the local space of
+/// the box. Returns the distance to the hit and the local normal of the face that was
+/// hit.
+fn ray_cast_cuboid(origin: Vec3, direction: Vec3, half_extents: Vec3) -> Option<(f32, Vec3)> {
+    let mut entry_distance = f32::NEG_INFINITY;
+    let mut exit_distance = f32::INFINITY;
+    let mut normal = Vec3::ZERO;
+    for axis in 0..3 {
+        let axis_origin = origin[axis];
+        let axis_direction = direction[axis];
+        let half_extent = half_extents[axis];
+        if axis_direction.abs() < f32::EPSILON {
+            // The ray is parallel to the faces on this axis so it can only hit the box
+            // if it is between them.
+            if axis_origin.abs() > half_extent {
+                return None;
+            }
+            continue;
+        }
+        let mut near = (-half_extent - axis_origin) / axis_direction;
+        let mut far = (half_extent - axis_origin) / axis_direction;
+        // A ray going in the positive direction enters through the negative face.
+        let mut face_sign = -1.0;
+        if near > far {
+            std::mem::swap(&mut near, &mut far);
+            face_sign = 1.0;
+        }
+        if near > entry_distance {
+            entry_distance = near;
+            normal = Vec3::ZERO;
+            normal[axis] = face_sign;
+        }
+        exit_distance = exit_distance.min(far);
+        if entry_distance > exit_distance {
+            return None;
+        }
+    }
+    if entry_distance < 0.0 {
+        // The box is behind the ray or the ray starts inside it.
+        return None;
+    }
+    return Some((entry_distance, normal));
+}
+
+/// Intersects a ray with a sphere centered on the origin. Returns the distance to the
+/// hit and the normal at the hit point.
+fn ray_cast_sphere(origin: Vec3, direction: Vec3, radius: f32) -> Option<(f32, Vec3)> {
+    let projection = origin.dot(direction);
+    let distance_squared_outside = origin.length_squared() - radius * radius;
+    if distance_squared_outside < 0.0 || projection > 0.0 {
+        // The ray starts inside the sphere or points away from it.
+        return None;
+    }
+    let discriminant = projection * projection - distance_squared_outside;
+    if discriminant < 0.0 {
+        return None;
+    }
+    let distance = -projection - discriminant.sqrt();
+    let normal = (origin + direction * distance).normalize_or_zero();
+    return Some((distance, normal));
+}
+
+/// Intersects a ray with a collider placed in the world. The direction must be
+/// normalized. Rays that start inside the collider do not hit it. Returns the distance
+/// to the hit and the world normal at the hit point when the hit is within the max
+/// distance.
+pub fn ray_cast_collider(
+    origin: Vec3,
+    direction: Vec3,
+    max_distance: f32,
+    collider: &WorldCollider,
+) -> Option<(f32, Vec3)> {
+    let inverse_rotation = collider.rotation.inverse();
+    let local_origin = inverse_rotation * (origin - collider.center);
+    let local_direction = inverse_rotation * direction;
+    let local_hit = match collider.shape {
+        ColliderShape::Cuboid { half_extents } => {
+            ray_cast_cuboid(local_origin, local_direction, half_extents)
+        }
+        ColliderShape::Sphere { radius } => ray_cast_sphere(local_origin, local_direction, radius),
+    };
+    return local_hit
+        .filter(|(distance, _)| *distance <= max_distance)
+        .map(|(distance, local_normal)| (distance, collider.rotation * local_normal));
+}
+
+/// Casts a ray against all the colliders and returns every hit within the max distance
+/// sorted from the closest to the farthest. The direction must be normalized.
+pub fn cast_ray_all(
+    origin: Vec3,
+    direction: Vec3,
+    max_distance: f32,
+    colliders: impl IntoIterator<Item = (Entity, WorldCollider)>,
+) -> Vec<RayHit> {
+    let mut hits: Vec<RayHit> = colliders
+        .into_iter()
+        .filter_map(|(entity, collider)| {
+            return ray_cast_collider(origin, direction, max_distance, &collider).map(
+                |(distance, _)| RayHit {
+                    entity: entity,
+                    distance: distance,
+                },
+            );
+        })
+        .collect();
+    hits.sort_by(|first, second| first.distance.total_cmp(&second.distance));
+    return hits;
+}
+
+/// Casts a ray against all the colliders and returns the closest hit within the max
+/// distance. The direction must be normalized.
+pub fn cast_ray(
+    origin: Vec3,
+    direction: Vec3,
+    max_distance: f32,
+    colliders: impl IntoIterator<Item = (Entity, WorldCollider)>,
+) -> Option<RayHit> {
+    return cast_ray_all(origin, direction, max_distance, colliders)
+        .into_iter()
+        .next();
+}
@@ -0,0 +1,5 @@
+/// crafting module for the crate. The recipes that turn items into other items, and the
+/// workstations that craft them as timed jobs in the background.
+pub(crate) mod components;
+pub(crate) mod control;
+pub(crate) mod start_up;
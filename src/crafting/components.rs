@@ -0,0 +1,122 @@
+use bevy::prelude::{Component, Entity, Resource};
+use serde::{Deserialize, Serialize};
+
+use crate::characters::profession::components::{Profession, QuestLines};
+use crate::items::inventory::{Inventory, ItemStack};
+use crate::ui::menu_navigation::MenuNavigation;
+
+/// The number of jobs that can wait in the queue of a workstation.
+pub const CRAFTING_QUEUE_SIZE: usize = 5;
+/// The number of slots for the crafted items of a workstation.
+pub const WORKSTATION_OUTPUT_SIZE: usize = 8;
+
+/// The kinds of workstations recipes are crafted at.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WorkstationKind {
+    Anvil,
+    Oven,
+    AlchemyTable,
+    Loom,
+}
+
+impl WorkstationKind {
+    /// Returns the name of the kind of workstation shown to players.
+    pub fn name(&self) -> &'static str {
+        return match self {
+            WorkstationKind::Anvil => "Anvil",
+            WorkstationKind::Oven => "Oven",
+            WorkstationKind::AlchemyTable => "Alchemy Table",
+            WorkstationKind::Loom => "Loom",
+        };
+    }
+}
+
+/// The stage of a quest line a character has to have reached to craft a recipe.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct QuestUnlock {
+    pub quest_line: String,
+    pub stage: u32,
+}
+
+/// The data definition of a recipe.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RecipeDefinition {
+    pub id: String,
+    pub name: String,
+    pub workstation: WorkstationKind,
+    /// The items the recipe takes when the job is queued.
+    pub inputs: Vec<ItemStack>,
+    /// The items the recipe makes when the job is done.
+    pub outputs: Vec<ItemStack>,
+    /// How long the job takes, in seconds.
+    pub craft_seconds: f32,
+    /// The profession a character needs to craft the recipe, or None when anyone can.
+    #[serde(default)]
+    pub profession: Option<String>,
+    /// The story progress a character needs to craft the recipe.
+    #[serde(default)]
+    pub unlock: Option<QuestUnlock>,
+}
+
+impl RecipeDefinition {
+    /// Returns true when a character with the profession and quest lines can craft the
+    /// recipe.
+    pub fn is_unlocked(&self, profession: Option<&Profession>, quest_lines: &QuestLines) -> bool {
+        let has_profession = self
+            .profession
+            .as_ref()
+            .is_none_or(|required| profession.is_some_and(|profession| profession.0 == *required));
+        let has_progress = self.unlock.as_ref().is_none_or(|unlock| {
+            quest_lines
+                .0
+                .get(&unlock.quest_line)
+                .is_some_and(|stage| *stage >= unlock.stage)
+        });
+        return has_profession && has_progress;
+    }
+}
+
+/// A Bevy Engine resource with the definitions of every recipe, loaded from a data file.
+#[derive(Resource, Default, Serialize, Deserialize)]
+pub struct RecipeRegistry {
+    pub recipes: Vec<RecipeDefinition>,
+}
+
+impl RecipeRegistry {
+    /// Returns the definition of the recipe with the id.
+    pub fn find(&self, id: &str) -> Option<&RecipeDefinition> {
+        return self.recipes.iter().find(|recipe| recipe.id == id);
+    }
+}
+
+/// A recipe being crafted or waiting its turn at a workstation.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct CraftingJob {
+    /// The id of the recipe definition.
+    pub recipe: String,
+    /// The seconds until the job is done.
+    pub seconds_left: f32,
+}
+
+/// A component for a workstation that crafts the recipes of its kind. The first job of
+/// the queue is crafted while the others wait, and the crafted items wait in the output
+/// until a player collects them. The id is what the workstation is saved by.
+#[derive(Component)]
+pub struct Workstation {
+    pub id: String,
+    pub kind: WorkstationKind,
+    pub queue: Vec<CraftingJob>,
+    pub output: Inventory,
+}
+
+/// A component on a player character that has the crafting menu of a workstation open.
+/// The player picks a recipe with the move stick, queues it with the interact action,
+/// collects the crafted items with the use tool action and closes the menu with the
+/// open inventory action.
+#[derive(Component)]
+pub struct CraftingMenu {
+    pub workstation: Entity,
+    pub cursor: usize,
+    pub navigation: MenuNavigation,
+    pub prompt: Entity,
+}
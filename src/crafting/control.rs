@@ -0,0 +1,371 @@
+use bevy::prelude::{
+    warn, Color, Commands, DespawnRecursiveExt, Entity, EventReader, EventWriter, GlobalTransform,
+    Mut, Query, Res, Text, TextBundle, TextStyle, Time, With, Without,
+};
+
+use crate::characters::components::MovementIntent;
+use crate::characters::player::entity::components::PlayerCharacter;
+use crate::characters::player::input::components::{ActionState, ButtonAction, StickAction};
+use crate::characters::profession::components::{Profession, QuestLines};
+use crate::interaction::components::{Interactable, InteractionEvent, InteractionKind};
+use crate::items::components::{Chest, InventoryMenu, ItemRegistry};
+use crate::items::inventory::{Inventory, ItemStack};
+use crate::ui::menu_navigation::{wrap_index, MenuNavigation};
+use crate::ui::player_overlay::{spawn_in_player_overlay, PlayerMessageEvent, PlayerOverlayLink};
+use crate::ui::start_up::UiFont;
+
+use super::components::{
+    CraftingJob, CraftingMenu, RecipeDefinition, RecipeRegistry, Workstation, CRAFTING_QUEUE_SIZE,
+};
+
+/// How close a chest has to be to a workstation for its items to be used in crafting.
+const CHEST_REACH: f32 = 6.0;
+
+/// Returns how many of the item the inventory and the chests hold together.
+fn available_count(item: &str, inventory: &Inventory, chests: &[Mut<Inventory>]) -> u32 {
+    return inventory.count_of(item) + chests.iter().map(|chest| chest.count_of(item)).sum::<u32>();
+}
+
+/// Returns true when the inventory and the chests hold every input of the recipe.
+fn has_inputs(recipe: &RecipeDefinition, inventory: &Inventory, chests: &[Mut<Inventory>]) -> bool {
+    return recipe
+        .inputs
+        .iter()
+        .all(|input| available_count(&input.item, inventory, chests) >= input.count);
+}
+
+/// Takes the inputs of the recipe from the inventory first and the chests after. The
+/// inputs are only partly taken when they hold too few.
+fn take_inputs(
+    recipe: &RecipeDefinition,
+    inventory: &mut Inventory,
+    chests: &mut [Mut<Inventory>],
+) {
+    for input in recipe.inputs.iter() {
+        let from_inventory = inventory.count_of(&input.item).min(input.count);
+        inventory.remove(&input.item, from_inventory);
+        let mut left = input.count - from_inventory;
+        for chest in chests.iter_mut() {
+            if left == 0 {
+                break;
+            }
+            let from_chest = chest.count_of(&input.item).min(left);
+            chest.remove(&input.item, from_chest);
+            left -= from_chest;
+        }
+    }
+}
+
+/// Moves the crafted items of the output to the inventory. What does not fit stays in
+/// the output. Returns the stacks that were moved.
+fn collect_output(
+    registry: &ItemRegistry,
+    output: &mut Inventory,
+    inventory: &mut Inventory,
+) -> Vec<ItemStack> {
+    let mut collected = Vec::new();
+    for slot in output.slots.iter_mut() {
+        let Some(stack) = slot else {
+            continue;
+        };
+        let left = inventory.add(registry, &stack.item, stack.count);
+        if left < stack.count {
+            collected.push(ItemStack {
+                item: stack.item.clone(),
+                count: stack.count - left,
+            });
+        }
+        if left == 0 {
+            *slot = None;
+        } else {
+            stack.count = left;
+        }
+    }
+    return collected;
+}
+
+/// Returns the text listing the stacks, like "2 Bread, Rope".
+fn stacks_text(registry: &ItemRegistry, stacks: &[ItemStack]) -> String {
+    return stacks
+        .iter()
+        .map(|stack| {
+            if stack.count > 1 {
+                format!("{} {}", stack.count, registry.name(&stack.item))
+            } else {
+                registry.name(&stack.item).to_string()
+            }
+        })
+        .collect::<Vec<String>>()
+        .join(", ");
+}
+
+/// Returns the recipes of the workstation that a character with the profession and
+/// quest lines can craft.
+fn unlocked_recipes<'a>(
+    registry: &'a RecipeRegistry,
+    workstation: &Workstation,
+    profession: Option<&Profession>,
+    quest_lines: &QuestLines,
+) -> Vec<&'a RecipeDefinition> {
+    return registry
+        .recipes
+        .iter()
+        .filter(|recipe| recipe.workstation == workstation.kind)
+        .filter(|recipe| recipe.is_unlocked(profession, quest_lines))
+        .collect();
+}
+
+/// Returns the text of the crafting menu, with the job being crafted, the crafted items
+/// waiting in the output and every recipe the player can craft with how many of each
+/// input they have.
+fn crafting_menu_text(
+    item_registry: &ItemRegistry,
+    recipe_registry: &RecipeRegistry,
+    menu: &CraftingMenu,
+    workstation: &Workstation,
+    recipes: &[&RecipeDefinition],
+    inventory: &Inventory,
+    chests: &[Mut<Inventory>],
+) -> String {
+    let mut lines = vec![workstation.kind.name().to_string()];
+    lines.push(match workstation.queue.first() {
+        Some(job) => {
+            let name = recipe_registry
+                .find(&job.recipe)
+                .map_or(job.recipe.as_str(), |recipe| recipe.name.as_str());
+            format!(
+                "Crafting {} ({}s), {} waiting",
+                name,
+                job.seconds_left.ceil().max(0.0),
+                workstation.queue.len() - 1
+            )
+        }
+        None => "Crafting nothing".to_string(),
+    });
+    let ready: Vec<ItemStack> = workstation.output.slots.iter().flatten().cloned().collect();
+    if !ready.is_empty() {
+        lines.push(format!("Ready: {}", stacks_text(item_registry, &ready)));
+    }
+    if recipes.is_empty() {
+        lines.push("No recipes".to_string());
+    }
+    for (index, recipe) in recipes.iter().enumerate() {
+        let marker = if index == menu.cursor { ">" } else { " " };
+        let inputs: Vec<String> = recipe
+            .inputs
+            .iter()
+            .map(|input| {
+                format!(
+                    "{} {}/{}",
+                    item_registry.name(&input.item),
+                    available_count(&input.item, inventory, chests),
+                    input.count
+                )
+            })
+            .collect();
+        lines.push(format!("{}{}: {}", marker, recipe.name, inputs.join(", ")));
+    }
+    lines.push("Interact to craft, use tool to collect".to_string());
+    return lines.join("\n");
+}
+
+/// A system that opens the crafting menu of the workstation every player interacts
+/// with. The items the workstation has crafted go to the player first. The player stops
+/// moving while the menu is open.
+pub fn open_crafting_menus(
+    mut commands: Commands,
+    ui_font: Res<UiFont>,
+    item_registry: Res<ItemRegistry>,
+    mut interaction_events: EventReader<InteractionEvent>,
+    mut players: Query<
+        (&PlayerOverlayLink, &mut Inventory, &mut MovementIntent),
+        (
+            With<PlayerCharacter>,
+            Without<CraftingMenu>,
+            Without<InventoryMenu>,
+            Without<Chest>,
+        ),
+    >,
+    mut workstations: Query<&mut Workstation>,
+    mut messages: EventWriter<PlayerMessageEvent>,
+) {
+    for event in interaction_events.iter() {
+        if event.kind != InteractionKind::Craft {
+            continue;
+        }
+        let Ok((overlay_link, mut inventory, mut movement_intent)) = players.get_mut(event.player)
+        else {
+            continue;
+        };
+        let Ok(mut workstation) = workstations.get_mut(event.target) else {
+            continue;
+        };
+        let collected = collect_output(&item_registry, &mut workstation.output, &mut inventory);
+        if !collected.is_empty() {
+            messages.send(PlayerMessageEvent {
+                player: event.player,
+                text: format!("Collected {}", stacks_text(&item_registry, &collected)),
+            });
+        }
+        let prompt = spawn_in_player_overlay(
+            &mut commands,
+            overlay_link,
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font: ui_font.0.clone(),
+                    font_size: 20.0,
+                    color: Color::WHITE,
+                },
+            ),
+        );
+        *movement_intent = MovementIntent::default();
+        commands.entity(event.player).insert(CraftingMenu {
+            workstation: event.target,
+            cursor: 0,
+            navigation: MenuNavigation::default(),
+            prompt: prompt,
+        });
+    }
+}
+
+/// A system that lets the players with the crafting menu open pick a recipe and queue
+/// it at the workstation, taking its inputs from their inventory and the chests near the
+/// workstation, and collect the crafted items. The open inventory action closes the
+/// menu, and so does the workstation going away.
+pub fn use_crafting_menus(
+    mut commands: Commands,
+    item_registry: Res<ItemRegistry>,
+    recipe_registry: Res<RecipeRegistry>,
+    mut players: Query<
+        (
+            Entity,
+            &ActionState,
+            Option<&Profession>,
+            &QuestLines,
+            &mut CraftingMenu,
+            &mut Inventory,
+        ),
+        Without<Chest>,
+    >,
+    mut workstations: Query<(&mut Workstation, &GlobalTransform)>,
+    mut chests: Query<(&mut Inventory, &GlobalTransform), (With<Chest>, Without<CraftingMenu>)>,
+    mut prompts: Query<&mut Text>,
+    mut messages: EventWriter<PlayerMessageEvent>,
+) {
+    for (player_entity, action_state, profession, quest_lines, mut menu, mut inventory) in
+        players.iter_mut()
+    {
+        if action_state.just_pressed(ButtonAction::OpenInventory) {
+            commands.entity(menu.prompt).despawn_recursive();
+            commands.entity(player_entity).remove::<CraftingMenu>();
+            continue;
+        }
+        let Ok((mut workstation, workstation_transform)) = workstations.get_mut(menu.workstation)
+        else {
+            commands.entity(menu.prompt).despawn_recursive();
+            commands.entity(player_entity).remove::<CraftingMenu>();
+            continue;
+        };
+        let workstation_position = workstation_transform.translation();
+        let mut nearby_chests: Vec<Mut<Inventory>> = chests
+            .iter_mut()
+            .filter(|(_, chest_transform)| {
+                chest_transform.translation().distance(workstation_position) <= CHEST_REACH
+            })
+            .map(|(chest_inventory, _)| chest_inventory)
+            .collect();
+        let recipes = unlocked_recipes(&recipe_registry, &workstation, profession, quest_lines);
+        if let Some(step) = menu.navigation.step(action_state.stick(StickAction::Move)) {
+            menu.cursor = wrap_index(menu.cursor, -step.y, recipes.len());
+        }
+        menu.cursor = menu.cursor.min(recipes.len().saturating_sub(1));
+        if action_state.just_pressed(ButtonAction::Interact) {
+            if let Some(recipe) = recipes.get(menu.cursor) {
+                let text = if workstation.queue.len() >= CRAFTING_QUEUE_SIZE {
+                    format!("The {} is busy", workstation.kind.name())
+                } else if !has_inputs(recipe, &inventory, &nearby_chests) {
+                    format!("Not enough items for {}", recipe.name)
+                } else {
+                    take_inputs(recipe, &mut inventory, &mut nearby_chests);
+                    workstation.queue.push(CraftingJob {
+                        recipe: recipe.id.clone(),
+                        seconds_left: recipe.craft_seconds,
+                    });
+                    format!("Crafting {}", recipe.name)
+                };
+                messages.send(PlayerMessageEvent {
+                    player: player_entity,
+                    text: text,
+                });
+            }
+        }
+        if action_state.just_pressed(ButtonAction::UseTool) {
+            let collected = collect_output(&item_registry, &mut workstation.output, &mut inventory);
+            if !collected.is_empty() {
+                messages.send(PlayerMessageEvent {
+                    player: player_entity,
+                    text: format!("Collected {}", stacks_text(&item_registry, &collected)),
+                });
+            }
+        }
+        if let Ok(mut prompt) = prompts.get_mut(menu.prompt) {
+            let text = crafting_menu_text(
+                &item_registry,
+                &recipe_registry,
+                &menu,
+                &workstation,
+                &recipes,
+                &inventory,
+                &nearby_chests,
+            );
+            if prompt.sections[0].value != text {
+                prompt.sections[0].value = text;
+            }
+        }
+    }
+}
+
+/// A system that crafts the first job in the queue of every workstation. A finished job
+/// puts its items in the output of the workstation, and waits until there is room for
+/// them. The prompt of the workstation tells players when there are items to collect.
+pub fn advance_crafting_jobs(
+    timer: Res<Time>,
+    item_registry: Res<ItemRegistry>,
+    recipe_registry: Res<RecipeRegistry>,
+    mut workstations: Query<(&mut Workstation, &mut Interactable)>,
+) {
+    for (mut workstation, mut interactable) in workstations.iter_mut() {
+        let finished_recipe = workstation.queue.first_mut().and_then(|job| {
+            job.seconds_left = (job.seconds_left - timer.delta_seconds()).max(0.0);
+            return (job.seconds_left <= 0.0).then(|| job.recipe.clone());
+        });
+        if let Some(recipe_id) = finished_recipe {
+            match recipe_registry.find(&recipe_id) {
+                Some(recipe) => {
+                    let mut output = workstation.output.clone();
+                    let fits = recipe.outputs.iter().all(|stack| {
+                        return output.add(&item_registry, &stack.item, stack.count) == 0;
+                    });
+                    if fits {
+                        workstation.output = output;
+                        workstation.queue.remove(0);
+                    }
+                }
+                None => {
+                    warn!("There is no recipe {} to craft", recipe_id);
+                    workstation.queue.remove(0);
+                }
+            }
+        }
+        let has_output = workstation.output.slots.iter().any(|slot| slot.is_some());
+        let prompt = if has_output {
+            format!("Collect from {}", workstation.kind.name())
+        } else {
+            format!("Use {}", workstation.kind.name())
+        };
+        if interactable.prompt != prompt {
+            interactable.prompt = prompt;
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use std::path::Path;
+
+use bevy::prelude::{
+    default, shape, Assets, Color, Commands, Mesh, PbrBundle, Quat, ResMut, StandardMaterial,
+    Transform, Vec3,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::collision::components::{ColliderShape, StaticCollider};
+use crate::interaction::components::{Interactable, InteractionKind};
+use crate::items::inventory::Inventory;
+use crate::storage::load_ron_or_default;
+
+use super::components::{RecipeRegistry, Workstation, WorkstationKind, WORKSTATION_OUTPUT_SIZE};
+
+/// The file the recipe definitions are loaded from.
+const RECIPE_REGISTRY_PATH: &str = "assets/items/recipes.ron";
+/// The file the workstations of the world are loaded from.
+const WORKSTATION_PLACEMENTS_PATH: &str = "assets/world/workstations.ron";
+
+/// Where a workstation stands in the world. The rotation is the turn around the
+/// vertical axis in degrees.
+#[derive(Serialize, Deserialize)]
+struct WorkstationPlacement {
+    id: String,
+    kind: WorkstationKind,
+    position: Vec3,
+    #[serde(default)]
+    rotation: f32,
+}
+
+/// Returns the size and color of the box a workstation of the kind is shown as.
+fn workstation_look(kind: WorkstationKind) -> (Vec3, Color) {
+    return match kind {
+        WorkstationKind::Anvil => (Vec3::new(0.9, 0.8, 0.5), Color::rgb(0.25, 0.25, 0.28)),
+        WorkstationKind::Oven => (Vec3::new(1.4, 1.4, 1.2), Color::rgb(0.65, 0.3, 0.2)),
+        WorkstationKind::AlchemyTable => (Vec3::new(1.6, 0.9, 0.8), Color::rgb(0.35, 0.25, 0.45)),
+        WorkstationKind::Loom => (Vec3::new(1.4, 1.5, 0.9), Color::rgb(0.7, 0.55, 0.35)),
+    };
+}
+
+/// Loads the recipe definitions from their data file.
+pub fn load_recipe_registry() -> RecipeRegistry {
+    return load_ron_or_default(Path::new(RECIPE_REGISTRY_PATH), "the recipes");
+}
+
+/// Loads where the workstations stand from their data file.
+fn load_workstation_placements() -> Vec<WorkstationPlacement> {
+    return load_ron_or_default(Path::new(WORKSTATION_PLACEMENTS_PATH), "the workstations");
+}
+
+/// Initial system to add the workstations of the world with empty queues. A loaded game
+/// replaces their queues and crafted items.
+pub fn spawn_workstations(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    for placement in load_workstation_placements() {
+        let (size, color) = workstation_look(placement.kind);
+        commands.spawn((
+            PbrBundle {
+                mesh: meshes.add(shape::Box::new(size.x, size.y, size.z).into()),
+                material: materials.add(color.into()),
+                transform: Transform::from_translation(
+                    placement.position + Vec3::new(0.0, size.y / 2.0, 0.0),
+                )
+                .with_rotation(Quat::from_rotation_y(placement.rotation.to_radians())),
+                ..default()
+            },
+            StaticCollider {
+                shape: ColliderShape::Cuboid {
+                    half_extents: size / 2.0,
+                },
+                offset: Vec3::ZERO,
+            },
+            Interactable::new(
+                InteractionKind::Craft,
+                format!("Use {}", placement.kind.name()),
+                size.y / 2.0 + 0.5,
+            ),
+            Workstation {
+                id: placement.id,
+                kind: placement.kind,
+                queue: Vec::new(),
+                output: Inventory::new(WORKSTATION_OUTPUT_SIZE),
+            },
+        ));
+    }
+}
@@ -0,0 +1,7 @@
+/// farming module for the crate. A grid of farm tiles over the fields with the state of
+/// their soil, and the crops planted on them that grow with the in-game days.
+pub(crate) mod components;
+pub(crate) mod control;
+pub(crate) mod grid;
+pub(crate) mod loader;
+pub(crate) mod start_up;
@@ -0,0 +1,162 @@
+use bevy::prelude::{
+    Assets, Component, Entity, Handle, HandleUntyped, IVec2, Mesh, Resource, StandardMaterial,
+};
+use bevy::reflect::TypeUuid;
+use serde::{Deserialize, Serialize};
+
+use crate::game_time::Season;
+
+/// A growth stage of a crop, with how it looks in the stage.
+pub struct CropStage {
+    /// How many watered days the crop spends in the stage. The last stage is the ripe
+    /// crop and lasts until it is harvested.
+    pub days: u32,
+    pub mesh: Handle<Mesh>,
+    pub material: Handle<StandardMaterial>,
+    /// How high above the soil the mesh is placed so it stands on it.
+    pub height_offset: f32,
+}
+
+/// A Bevy Engine asset with the definition of a kind of crop, loaded from a crop file
+/// by the crop definition loader.
+#[derive(TypeUuid)]
+#[uuid = "dba49110-103d-415c-8902-9927fa2a7efa"]
+pub struct CropDefinition {
+    pub id: String,
+    pub name: String,
+    pub stages: Vec<CropStage>,
+    /// The seasons the crop grows in. It withers at the start of a day in any other
+    /// season.
+    pub seasons: Vec<Season>,
+    /// If the crop only grows on days its soil was watered.
+    pub needs_water: bool,
+    /// How many days in a row the crop can go without water before it withers, or None
+    /// when it never withers from the drought.
+    pub withers_after_dry_days: Option<u32>,
+    /// How many days a harvested crop takes to ripen again, or None when it is used up
+    /// by the harvest.
+    pub regrow_days: Option<u32>,
+    /// The smallest and largest number of crops a harvest gives.
+    pub yield_range: (u32, u32),
+}
+
+impl CropDefinition {
+    /// Returns the number of growth stages, including the ripe stage.
+    pub fn stage_count(&self) -> usize {
+        return self.stages.len();
+    }
+
+    /// Returns the stage, or the last stage when the crop is past it, like after its
+    /// definition was reloaded with fewer stages.
+    pub fn stage(&self, stage: usize) -> Option<&CropStage> {
+        return self.stages.get(stage).or(self.stages.last());
+    }
+
+    /// Returns how many watered days the crop spends in the stage. A crop that regrows
+    /// after a harvest spends its regrow days in the stage before the ripe one, and
+    /// fertilized soil takes a day off every stage.
+    pub fn days_in_stage(&self, stage: usize, fertilized: bool, regrowing: bool) -> u32 {
+        let days = match self.regrow_days {
+            Some(regrow_days) if regrowing && stage + 2 == self.stage_count() => regrow_days,
+            _ => self.stages.get(stage).map_or(0, |stage| stage.days),
+        };
+        if fertilized {
+            return days.saturating_sub(1).max(1);
+        }
+        return days;
+    }
+}
+
+/// A Bevy Engine resource with the handles of every crop definition, which are loaded
+/// from the crops asset folder and reloaded when their files change.
+#[derive(Resource, Default)]
+pub struct CropRegistry {
+    pub handles: Vec<HandleUntyped>,
+}
+
+impl CropRegistry {
+    /// Returns every crop definition that is loaded.
+    pub fn definitions<'a>(
+        &'a self,
+        definitions: &'a Assets<CropDefinition>,
+    ) -> impl Iterator<Item = &'a CropDefinition> {
+        return self
+            .handles
+            .iter()
+            .filter_map(|handle| definitions.get(&handle.typed_weak()));
+    }
+
+    /// Returns the definition of the crop with the id.
+    pub fn find<'a>(
+        &'a self,
+        definitions: &'a Assets<CropDefinition>,
+        id: &str,
+    ) -> Option<&'a CropDefinition> {
+        return self
+            .definitions(definitions)
+            .find(|definition| definition.id == id);
+    }
+}
+
+/// A component for a crop planted on a farm tile.
+#[derive(Component, Clone, Serialize, Deserialize)]
+pub struct Crop {
+    /// The id of the definition of the crop.
+    pub crop_id: String,
+    pub tile: IVec2,
+    pub stage: usize,
+    /// The watered days the crop has spent in its stage so far.
+    pub days_in_stage: u32,
+    /// The days in a row the crop has gone without water.
+    pub dry_days: u32,
+    /// If the crop was harvested before and is growing back.
+    pub regrowing: bool,
+    /// If the crop withered out of its season or from drought. Withered crops do not
+    /// grow and give nothing when harvested.
+    pub withered: bool,
+}
+
+impl Crop {
+    /// Returns true when the crop is in its last growth stage and can be harvested.
+    pub fn is_ripe(&self, definition: &CropDefinition) -> bool {
+        return !self.withered && self.stage + 1 >= definition.stage_count();
+    }
+}
+
+/// A component for the patch of soil that shows the state of a farm tile.
+#[derive(Component)]
+pub struct SoilPatch {
+    pub tile: IVec2,
+}
+
+/// The id of the item spent to fertilize a farm tile.
+pub const FERTILIZER_ITEM: &str = "fertilizer";
+
+/// Something done to a farm tile.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FarmAction {
+    Till,
+    Water,
+    Fertilize,
+    /// Plants the crop with the id.
+    Plant(String),
+    Harvest,
+}
+
+/// An event to do a farm action on a farm tile. The actor is the character doing it,
+/// whose inventory the seeds come from and the harvest goes to.
+pub struct FarmActionEvent {
+    pub tile: IVec2,
+    pub action: FarmAction,
+    pub actor: Option<Entity>,
+}
+
+/// A Bevy Engine resource with the meshes and materials of the soil and withered crops.
+#[derive(Resource)]
+pub struct FarmAssets {
+    pub soil_mesh: Handle<Mesh>,
+    pub tilled_material: Handle<StandardMaterial>,
+    pub watered_material: Handle<StandardMaterial>,
+    pub fertilized_material: Handle<StandardMaterial>,
+    pub withered_material: Handle<StandardMaterial>,
+}
@@ -0,0 +1,449 @@
+use bevy::prelude::{
+    default, info, warn, AssetEvent, Assets, Changed, Commands, DespawnRecursiveExt, DetectChanges,
+    DetectChangesMut, Entity, EventReader, Handle, Local, Mesh, PbrBundle, Query, Res, ResMut,
+    StandardMaterial, Transform, Visibility,
+};
+
+use crate::game_time::{GameClock, GameClockEvent};
+use crate::interaction::components::PickupAssets;
+use crate::interaction::start_up::spawn_pickup;
+use crate::items::components::ItemRegistry;
+use crate::items::inventory::{Inventory, ItemStack};
+use crate::random::SeededRandom;
+use crate::weather::components::Weather;
+
+use super::components::{
+    Crop, CropDefinition, CropRegistry, FarmAction, FarmActionEvent, FarmAssets, SoilPatch,
+    FERTILIZER_ITEM,
+};
+use super::grid::{FarmGrid, SoilState};
+
+/// Spawns the crop on its tile of the grid. Its looks are set once it is spawned.
+/// Returns the crop entity, or None when the tile has no room for a crop.
+pub fn spawn_crop(commands: &mut Commands, grid: &mut FarmGrid, crop: Crop) -> Option<Entity> {
+    let tile = crop.tile;
+    let plantable = grid
+        .tile(tile)
+        .is_some_and(|farm_tile| farm_tile.is_tilled() && farm_tile.crop.is_none());
+    if !plantable {
+        return None;
+    }
+    let crop_entity = commands
+        .spawn((
+            PbrBundle {
+                transform: Transform::from_translation(grid.tile_center(tile)),
+                ..default()
+            },
+            crop,
+        ))
+        .id();
+    grid.plant(tile, crop_entity);
+    return Some(crop_entity);
+}
+
+/// A system that does the farm actions on their tiles. Crops are only planted on empty
+/// tilled soil in their seasons, and only ripe or withered crops are harvested. Crops
+/// that regrow stay on their tile after a harvest. An actor with an inventory spends a
+/// seed to plant, spends fertilizer to fertilize and gets the harvest. The harvest that
+/// does not fit in the inventory is left as a pickup on the tile.
+pub fn apply_farm_actions(
+    mut commands: Commands,
+    mut farm_actions: EventReader<FarmActionEvent>,
+    mut grid: ResMut<FarmGrid>,
+    registry: Res<CropRegistry>,
+    definitions: Res<Assets<CropDefinition>>,
+    item_registry: Res<ItemRegistry>,
+    pickup_assets: Res<PickupAssets>,
+    clock: Res<GameClock>,
+    mut crops: Query<&mut Crop>,
+    mut inventories: Query<&mut Inventory>,
+    mut random: Local<SeededRandom>,
+) {
+    for event in farm_actions.iter() {
+        let tile = event.tile;
+        match &event.action {
+            FarmAction::Till => {
+                grid.till(tile);
+            }
+            FarmAction::Water => {
+                grid.water(tile);
+            }
+            FarmAction::Fertilize => {
+                let fertilizable = grid
+                    .tile(tile)
+                    .is_some_and(|farm_tile| farm_tile.soil == SoilState::Tilled);
+                if !fertilizable {
+                    continue;
+                }
+                if let Some(mut inventory) = event
+                    .actor
+                    .and_then(|actor| inventories.get_mut(actor).ok())
+                {
+                    if !inventory.remove(FERTILIZER_ITEM, 1) {
+                        continue;
+                    }
+                }
+                grid.fertilize(tile);
+            }
+            FarmAction::Plant(crop_id) => {
+                let Some(definition) = registry.find(&definitions, crop_id) else {
+                    warn!("There is no crop {} to plant", crop_id);
+                    continue;
+                };
+                if !definition.seasons.contains(&clock.season) {
+                    info!("{} does not grow in {:?}", definition.name, clock.season);
+                    continue;
+                }
+                let plantable = grid
+                    .tile(tile)
+                    .is_some_and(|farm_tile| farm_tile.is_tilled() && farm_tile.crop.is_none());
+                if !plantable {
+                    continue;
+                }
+                if let Some(mut inventory) = event
+                    .actor
+                    .and_then(|actor| inventories.get_mut(actor).ok())
+                {
+                    let seed = item_registry.seed_of_crop(crop_id).unwrap_or(crop_id);
+                    if !inventory.remove(seed, 1) {
+                        continue;
+                    }
+                }
+                spawn_crop(
+                    &mut commands,
+                    &mut grid,
+                    Crop {
+                        crop_id: crop_id.clone(),
+                        tile: tile,
+                        stage: 0,
+                        days_in_stage: 0,
+                        dry_days: 0,
+                        regrowing: false,
+                        withered: false,
+                    },
+                );
+            }
+            FarmAction::Harvest => {
+                let Some(crop_entity) = grid.tile(tile).and_then(|farm_tile| farm_tile.crop) else {
+                    continue;
+                };
+                let Ok(mut crop) = crops.get_mut(crop_entity) else {
+                    continue;
+                };
+                let definition = registry.find(&definitions, &crop.crop_id);
+                let ripe = definition.is_some_and(|definition| crop.is_ripe(definition));
+                if !ripe && !crop.withered {
+                    continue;
+                }
+                if let (true, Some(definition)) = (ripe, definition) {
+                    let (least, most) = definition.yield_range;
+                    let amount =
+                        least + (random.next_u64() % (most.max(least) - least + 1) as u64) as u32;
+                    info!("Harvested {} {}", amount, definition.name);
+                    let left = match event
+                        .actor
+                        .and_then(|actor| inventories.get_mut(actor).ok())
+                    {
+                        Some(mut inventory) => {
+                            inventory.add(&item_registry, &definition.id, amount)
+                        }
+                        None => amount,
+                    };
+                    if left > 0 {
+                        spawn_pickup(
+                            &mut commands,
+                            &pickup_assets,
+                            &item_registry,
+                            ItemStack {
+                                item: definition.id.clone(),
+                                count: left,
+                            },
+                            grid.tile_center(tile),
+                        );
+                    }
+                    if definition.regrow_days.is_some() && definition.stage_count() >= 2 {
+                        crop.stage = definition.stage_count() - 2;
+                        crop.days_in_stage = 0;
+                        crop.regrowing = true;
+                        continue;
+                    }
+                }
+                grid.clear_crop(tile);
+                commands.entity(crop_entity).despawn_recursive();
+            }
+        }
+    }
+}
+
+/// A system that grows the crops at the start of every in-game day. A crop grows a day
+/// when its soil was watered the day before, or every day when it does not need water.
+/// It withers when the new day is out of its seasons or after too many dry days. The
+/// soil of every tile dries out after.
+pub fn grow_crops(
+    mut clock_events: EventReader<GameClockEvent>,
+    clock: Res<GameClock>,
+    registry: Res<CropRegistry>,
+    definitions: Res<Assets<CropDefinition>>,
+    mut grid: ResMut<FarmGrid>,
+    mut crops: Query<&mut Crop>,
+) {
+    let new_day = clock_events
+        .iter()
+        .any(|event| *event == GameClockEvent::NewDay);
+    if !new_day {
+        return;
+    }
+    for mut crop in crops.iter_mut() {
+        let Some(definition) = registry.find(&definitions, &crop.crop_id) else {
+            continue;
+        };
+        if crop.withered {
+            continue;
+        }
+        if !definition.seasons.contains(&clock.season) {
+            crop.withered = true;
+            continue;
+        }
+        let Some(farm_tile) = grid.tile(crop.tile) else {
+            continue;
+        };
+        if farm_tile.watered || !definition.needs_water {
+            crop.dry_days = 0;
+        } else {
+            crop.dry_days += 1;
+            let too_dry = definition
+                .withers_after_dry_days
+                .is_some_and(|dry_days| crop.dry_days >= dry_days);
+            if too_dry {
+                crop.withered = true;
+            }
+            continue;
+        }
+        if crop.is_ripe(definition) {
+            continue;
+        }
+        crop.days_in_stage += 1;
+        let fertilized = farm_tile.soil == SoilState::Fertilized;
+        let stage_days = definition.days_in_stage(crop.stage, fertilized, crop.regrowing);
+        if crop.days_in_stage >= stage_days {
+            crop.stage += 1;
+            crop.days_in_stage = 0;
+        }
+    }
+    grid.dry_all();
+}
+
+/// A system that waters every tilled farm tile while the weather of the day waters the
+/// crops.
+pub fn water_farm_in_rain(weather: Res<Weather>, mut grid: ResMut<FarmGrid>) {
+    if !weather.today.waters_crops() {
+        return;
+    }
+    let has_dry_soil = grid
+        .tiles()
+        .any(|(_, farm_tile)| farm_tile.is_tilled() && !farm_tile.watered);
+    if has_dry_soil {
+        grid.water_all();
+    }
+}
+
+/// A system that shows the soil of every farm tile on its soil patch when the farm
+/// changes. Untilled tiles show the plain ground.
+pub fn update_soil_patches(
+    grid: Res<FarmGrid>,
+    farm_assets: Res<FarmAssets>,
+    mut patches: Query<(&SoilPatch, &mut Handle<StandardMaterial>, &mut Visibility)>,
+) {
+    if !grid.is_changed() {
+        return;
+    }
+    for (patch, mut material, mut visibility) in patches.iter_mut() {
+        let Some(farm_tile) = grid.tile(patch.tile) else {
+            continue;
+        };
+        *visibility = if farm_tile.is_tilled() {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+        let soil_material = if farm_tile.watered {
+            &farm_assets.watered_material
+        } else if farm_tile.soil == SoilState::Fertilized {
+            &farm_assets.fertilized_material
+        } else {
+            &farm_assets.tilled_material
+        };
+        if *material != *soil_material {
+            *material = soil_material.clone();
+        }
+    }
+}
+
+/// A system that marks every crop as changed when the crop definitions are reloaded, so
+/// they take the new look of their stage.
+pub fn refresh_reloaded_crops(
+    mut definition_events: EventReader<AssetEvent<CropDefinition>>,
+    mut crops: Query<&mut Crop>,
+) {
+    let mut reloaded = false;
+    for event in definition_events.iter() {
+        if let AssetEvent::Modified { .. } = event {
+            reloaded = true;
+        }
+    }
+    if !reloaded {
+        return;
+    }
+    info!("Reloaded the crop definitions");
+    for mut crop in crops.iter_mut() {
+        crop.set_changed();
+    }
+}
+
+/// A system that gives every crop the mesh and material of its growth stage when it
+/// changes. Withered crops keep their mesh and turn brown.
+pub fn update_crop_looks(
+    registry: Res<CropRegistry>,
+    definitions: Res<Assets<CropDefinition>>,
+    farm_assets: Res<FarmAssets>,
+    mut crops: Query<
+        (
+            &Crop,
+            &mut Transform,
+            &mut Handle<Mesh>,
+            &mut Handle<StandardMaterial>,
+        ),
+        Changed<Crop>,
+    >,
+) {
+    for (crop, mut transform, mut mesh, mut material) in crops.iter_mut() {
+        let Some(stage) = registry
+            .find(&definitions, &crop.crop_id)
+            .and_then(|definition| definition.stage(crop.stage))
+        else {
+            continue;
+        };
+        *mesh = stage.mesh.clone();
+        *material = if crop.withered {
+            farm_assets.withered_material.clone()
+        } else {
+            stage.material.clone()
+        };
+        transform.translation.y = stage.height_offset;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use bevy::prelude::{
+        AddAsset, App, AssetPlugin, Assets, Handle, IVec2, TaskPoolPlugin, Transform,
+    };
+
+    use crate::game_time::GameClock;
+    use crate::interaction::components::{Pickup, PickupAssets};
+    use crate::items::components::{ItemCategory, ItemDefinition, ItemRegistry};
+    use crate::items::inventory::Inventory;
+
+    use super::super::components::{
+        Crop, CropDefinition, CropRegistry, CropStage, FarmAction, FarmActionEvent,
+    };
+    use super::super::grid::{FarmGrid, FarmPlotSettings};
+    use super::apply_farm_actions;
+
+    #[test]
+    fn leaves_the_harvest_that_does_not_fit_as_a_pickup() {
+        let mut app = App::new();
+        app.add_plugin(TaskPoolPlugin::default())
+            .add_plugin(AssetPlugin::default())
+            .add_asset::<CropDefinition>()
+            .add_event::<FarmActionEvent>()
+            .insert_resource(GameClock::default())
+            .insert_resource(PickupAssets {
+                mesh: Handle::default(),
+                material: Handle::default(),
+            })
+            .insert_resource(ItemRegistry {
+                items: vec![ItemDefinition {
+                    id: "turnip".to_string(),
+                    name: "Turnip".to_string(),
+                    category: ItemCategory::Crop,
+                    max_stack: 10,
+                    icon: None,
+                    metadata: BTreeMap::new(),
+                }],
+                starting_items: Vec::new(),
+            })
+            .add_system(apply_farm_actions);
+        let stage = |days: u32| CropStage {
+            days: days,
+            mesh: Handle::default(),
+            material: Handle::default(),
+            height_offset: 0.0,
+        };
+        let definition = app
+            .world
+            .resource_mut::<Assets<CropDefinition>>()
+            .add(CropDefinition {
+                id: "turnip".to_string(),
+                name: "Turnip".to_string(),
+                stages: vec![stage(2), stage(0)],
+                seasons: Vec::new(),
+                needs_water: true,
+                withers_after_dry_days: None,
+                regrow_days: None,
+                yield_range: (3, 3),
+            });
+        app.insert_resource(CropRegistry {
+            handles: vec![definition.clone_untyped()],
+        });
+
+        let tile = IVec2::new(1, 1);
+        let mut grid = FarmGrid::new(FarmPlotSettings::default());
+        grid.till(tile);
+        let crop = app
+            .world
+            .spawn(Crop {
+                crop_id: "turnip".to_string(),
+                tile: tile,
+                stage: 1,
+                days_in_stage: 0,
+                dry_days: 0,
+                regrowing: false,
+                withered: false,
+            })
+            .id();
+        grid.plant(tile, crop);
+        let tile_center = grid.tile_center(tile);
+        app.insert_resource(grid);
+
+        let mut inventory = Inventory::new(1);
+        inventory.add(app.world.resource::<ItemRegistry>(), "turnip", 9);
+        let player = app.world.spawn(inventory).id();
+        app.world.send_event(FarmActionEvent {
+            tile: tile,
+            action: FarmAction::Harvest,
+            actor: Some(player),
+        });
+        app.update();
+
+        let inventory = app.world.get::<Inventory>(player).unwrap();
+        assert_eq!(inventory.count_of("turnip"), 10);
+        let pickups: Vec<(u32, f32)> = app
+            .world
+            .query::<(&Pickup, &Transform)>()
+            .iter(&app.world)
+            .map(|(pickup, transform)| {
+                return (
+                    pickup.stack.count,
+                    transform.translation.distance(tile_center),
+                );
+            })
+            .collect();
+        assert_eq!(pickups.len(), 1);
+        assert_eq!(pickups[0].0, 2);
+        assert!(pickups[0].1 < 0.5);
+        assert!(app.world.get_entity(crop).is_none());
+    }
+}
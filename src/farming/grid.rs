@@ -0,0 +1,317 @@
+use bevy::prelude::{Entity, IVec2, Resource, Vec2, Vec3};
+use serde::{Deserialize, Serialize};
+
+/// The state of the soil of a farm tile. Fertilized soil is tilled soil that crops grow
+/// faster in.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SoilState {
+    #[default]
+    Untilled,
+    Tilled,
+    Fertilized,
+}
+
+/// A tile of the farm grid.
+#[derive(Clone, Copy, Default)]
+pub struct FarmTile {
+    pub soil: SoilState,
+    /// If the soil was watered today. The soil dries out at the start of every day.
+    pub watered: bool,
+    /// The crop planted on the tile.
+    pub crop: Option<Entity>,
+}
+
+impl FarmTile {
+    /// Returns true when the soil is tilled, with or without fertilizer.
+    pub fn is_tilled(&self) -> bool {
+        return self.soil != SoilState::Untilled;
+    }
+}
+
+/// The layout of the farm grid, loaded from a data file.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct FarmPlotSettings {
+    /// The center of the plot on the ground plane, as x and z.
+    pub center: Vec2,
+    pub columns: i32,
+    pub rows: i32,
+    /// The length of the sides of a tile.
+    pub tile_size: f32,
+}
+
+impl Default for FarmPlotSettings {
+    fn default() -> Self {
+        return FarmPlotSettings {
+            center: Vec2::new(4.0, 4.0),
+            columns: 8,
+            rows: 8,
+            tile_size: 1.0,
+        };
+    }
+}
+
+/// A Bevy Engine resource with the grid of farm tiles. It maps world positions to tiles
+/// and holds the soil of every tile, so the farming systems change the tiles through it.
+#[derive(Resource)]
+pub struct FarmGrid {
+    /// The corner of the grid with the lowest x and z.
+    pub origin: Vec2,
+    pub tile_size: f32,
+    pub columns: i32,
+    pub rows: i32,
+    tiles: Vec<FarmTile>,
+}
+
+impl FarmGrid {
+    /// Creates a grid of untilled tiles with the layout of the settings.
+    pub fn new(settings: FarmPlotSettings) -> Self {
+        let size = Vec2::new(settings.columns as f32, settings.rows as f32) * settings.tile_size;
+        return FarmGrid {
+            origin: settings.center - size / 2.0,
+            tile_size: settings.tile_size,
+            columns: settings.columns,
+            rows: settings.rows,
+            tiles: vec![FarmTile::default(); (settings.columns * settings.rows) as usize],
+        };
+    }
+
+    /// Returns true when the tile is inside the grid.
+    pub fn contains(&self, tile: IVec2) -> bool {
+        return tile.x >= 0 && tile.y >= 0 && tile.x < self.columns && tile.y < self.rows;
+    }
+
+    /// Returns the tile the world position is over, or None when it is off the farm.
+    pub fn tile_at(&self, position: Vec3) -> Option<IVec2> {
+        let local = (Vec2::new(position.x, position.z) - self.origin) / self.tile_size;
+        let tile = local.floor().as_ivec2();
+        return self.contains(tile).then_some(tile);
+    }
+
+    /// Returns the position of the center of the tile on the ground.
+    pub fn tile_center(&self, tile: IVec2) -> Vec3 {
+        let center = self.origin + (tile.as_vec2() + Vec2::splat(0.5)) * self.tile_size;
+        return Vec3::new(center.x, 0.0, center.y);
+    }
+
+    /// Returns the tile, or None when it is outside the grid.
+    pub fn tile(&self, tile: IVec2) -> Option<&FarmTile> {
+        if !self.contains(tile) {
+            return None;
+        }
+        return self.tiles.get((tile.y * self.columns + tile.x) as usize);
+    }
+
+    fn tile_mut(&mut self, tile: IVec2) -> Option<&mut FarmTile> {
+        if !self.contains(tile) {
+            return None;
+        }
+        return self
+            .tiles
+            .get_mut((tile.y * self.columns + tile.x) as usize);
+    }
+
+    /// Returns every tile of the grid with its coordinates.
+    pub fn tiles(&self) -> impl Iterator<Item = (IVec2, &FarmTile)> {
+        let columns = self.columns;
+        return self.tiles.iter().enumerate().map(move |(index, tile)| {
+            (
+                IVec2::new(index as i32 % columns, index as i32 / columns),
+                tile,
+            )
+        });
+    }
+
+    /// Tills untilled soil. Returns true when the soil changed.
+    pub fn till(&mut self, tile: IVec2) -> bool {
+        let Some(farm_tile) = self.tile_mut(tile) else {
+            return false;
+        };
+        if farm_tile.is_tilled() {
+            return false;
+        }
+        farm_tile.soil = SoilState::Tilled;
+        return true;
+    }
+
+    /// Waters tilled soil. Returns true when the soil changed.
+    pub fn water(&mut self, tile: IVec2) -> bool {
+        let Some(farm_tile) = self.tile_mut(tile) else {
+            return false;
+        };
+        if !farm_tile.is_tilled() || farm_tile.watered {
+            return false;
+        }
+        farm_tile.watered = true;
+        return true;
+    }
+
+    /// Fertilizes tilled soil. Returns true when the soil changed.
+    pub fn fertilize(&mut self, tile: IVec2) -> bool {
+        let Some(farm_tile) = self.tile_mut(tile) else {
+            return false;
+        };
+        if farm_tile.soil != SoilState::Tilled {
+            return false;
+        }
+        farm_tile.soil = SoilState::Fertilized;
+        return true;
+    }
+
+    /// Puts the crop on tilled soil without a crop. Returns true when the crop was put on
+    /// the tile.
+    pub fn plant(&mut self, tile: IVec2, crop: Entity) -> bool {
+        let Some(farm_tile) = self.tile_mut(tile) else {
+            return false;
+        };
+        if !farm_tile.is_tilled() || farm_tile.crop.is_some() {
+            return false;
+        }
+        farm_tile.crop = Some(crop);
+        return true;
+    }
+
+    /// Takes the crop off the tile. The soil goes back to plain tilled soil, as the crop
+    /// used up the fertilizer.
+    pub fn clear_crop(&mut self, tile: IVec2) -> Option<Entity> {
+        let farm_tile = self.tile_mut(tile)?;
+        let crop = farm_tile.crop.take()?;
+        farm_tile.soil = SoilState::Tilled;
+        return Some(crop);
+    }
+
+    /// Takes every crop off the grid and turns all the soil back into untilled, dry soil.
+    pub fn clear(&mut self) {
+        self.tiles.fill(FarmTile::default());
+    }
+
+    /// Puts the soil of the tile back into a saved state. Returns true when the tile is
+    /// inside the grid.
+    pub fn restore_soil(&mut self, tile: IVec2, soil: SoilState, watered: bool) -> bool {
+        let Some(farm_tile) = self.tile_mut(tile) else {
+            return false;
+        };
+        farm_tile.soil = soil;
+        farm_tile.watered = watered && soil != SoilState::Untilled;
+        return true;
+    }
+
+    /// Waters every tilled tile, like rain does.
+    pub fn water_all(&mut self) {
+        for farm_tile in self.tiles.iter_mut().filter(|tile| tile.is_tilled()) {
+            farm_tile.watered = true;
+        }
+    }
+
+    /// Dries out the soil of every tile at the start of a day.
+    pub fn dry_all(&mut self) {
+        for farm_tile in self.tiles.iter_mut() {
+            farm_tile.watered = false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::{Entity, IVec2, Vec2, Vec3};
+
+    use super::{FarmGrid, FarmPlotSettings, SoilState};
+
+    /// A grid of 4 by 3 tiles of one unit with its lowest corner at the origin.
+    fn grid() -> FarmGrid {
+        return FarmGrid::new(FarmPlotSettings {
+            center: Vec2::new(2.0, 1.5),
+            columns: 4,
+            rows: 3,
+            tile_size: 1.0,
+        });
+    }
+
+    #[test]
+    fn maps_world_positions_to_tiles() {
+        let grid = grid();
+        assert_eq!(grid.origin, Vec2::ZERO);
+        assert_eq!(
+            grid.tile_at(Vec3::new(0.5, 3.0, 0.5)),
+            Some(IVec2::new(0, 0))
+        );
+        assert_eq!(
+            grid.tile_at(Vec3::new(3.9, 0.0, 2.1)),
+            Some(IVec2::new(3, 2))
+        );
+        assert_eq!(grid.tile_at(Vec3::new(4.1, 0.0, 1.0)), None);
+        assert_eq!(grid.tile_at(Vec3::new(1.0, 0.0, -0.1)), None);
+        assert_eq!(grid.tile_center(IVec2::new(2, 1)), Vec3::new(2.5, 0.0, 1.5));
+        assert!(grid.tile(IVec2::new(4, 0)).is_none());
+        assert_eq!(grid.tiles().count(), 12);
+    }
+
+    #[test]
+    fn tills_waters_and_fertilizes_in_order() {
+        let mut grid = grid();
+        let tile = IVec2::new(1, 2);
+        assert!(!grid.water(tile));
+        assert!(!grid.fertilize(tile));
+        assert!(grid.till(tile));
+        assert!(!grid.till(tile));
+        assert!(grid.water(tile));
+        assert!(!grid.water(tile));
+        assert!(grid.fertilize(tile));
+        assert!(!grid.fertilize(tile));
+        let farm_tile = grid.tile(tile).unwrap();
+        assert_eq!(farm_tile.soil, SoilState::Fertilized);
+        assert!(farm_tile.watered);
+        assert!(!grid.till(IVec2::new(-1, 0)));
+    }
+
+    #[test]
+    fn plants_one_crop_on_tilled_soil_and_clears_it() {
+        let mut grid = grid();
+        let tile = IVec2::new(2, 0);
+        let crop = Entity::from_raw(7);
+        assert!(!grid.plant(tile, crop));
+        grid.till(tile);
+        grid.fertilize(tile);
+        assert!(grid.plant(tile, crop));
+        assert!(!grid.plant(tile, Entity::from_raw(8)));
+        assert_eq!(grid.clear_crop(tile), Some(crop));
+        assert_eq!(grid.clear_crop(tile), None);
+        let farm_tile = grid.tile(tile).unwrap();
+        assert_eq!(farm_tile.soil, SoilState::Tilled);
+        assert!(farm_tile.crop.is_none());
+    }
+
+    #[test]
+    fn waters_and_dries_every_tilled_tile() {
+        let mut grid = grid();
+        grid.till(IVec2::new(0, 0));
+        grid.till(IVec2::new(3, 1));
+        grid.water_all();
+        let watered: Vec<IVec2> = grid
+            .tiles()
+            .filter(|(_, farm_tile)| farm_tile.watered)
+            .map(|(tile, _)| tile)
+            .collect();
+        assert_eq!(watered, vec![IVec2::new(0, 0), IVec2::new(3, 1)]);
+        grid.dry_all();
+        assert!(grid.tiles().all(|(_, farm_tile)| !farm_tile.watered));
+    }
+
+    #[test]
+    fn clears_and_restores_saved_soil() {
+        let mut grid = grid();
+        let tile = IVec2::new(1, 1);
+        grid.till(tile);
+        grid.plant(tile, Entity::from_raw(3));
+        grid.clear();
+        assert!(grid
+            .tiles()
+            .all(|(_, farm_tile)| !farm_tile.is_tilled() && farm_tile.crop.is_none()));
+        assert!(grid.restore_soil(tile, SoilState::Fertilized, true));
+        assert!(grid.restore_soil(IVec2::new(0, 1), SoilState::Untilled, true));
+        assert!(!grid.restore_soil(IVec2::new(9, 9), SoilState::Tilled, false));
+        let farm_tile = grid.tile(tile).unwrap();
+        assert_eq!(farm_tile.soil, SoilState::Fertilized);
+        assert!(farm_tile.watered);
+        assert!(!grid.tile(IVec2::new(0, 1)).unwrap().watered);
+    }
+}
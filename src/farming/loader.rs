@@ -0,0 +1,158 @@
+use bevy::asset::{AssetLoader, AssetPath, BoxedFuture, Error, LoadContext, LoadedAsset};
+use bevy::prelude::{shape, Color, Handle, Mesh, StandardMaterial, Vec3};
+use serde::Deserialize;
+
+use crate::game_time::Season;
+
+use super::components::{CropDefinition, CropStage};
+
+/// The mesh of a crop growth stage in a crop file. The shapes are built by the loader
+/// and stand on the soil, while models are loaded from their own asset files.
+#[derive(Deserialize)]
+enum CropMeshFile {
+    Sphere {
+        radius: f32,
+    },
+    Box {
+        size: Vec3,
+    },
+    Cylinder {
+        radius: f32,
+        height: f32,
+    },
+    /// The asset path of a mesh, like "models/crops/corn.glb#Mesh0/Primitive0".
+    Model(String),
+}
+
+/// A crop growth stage in a crop file.
+#[derive(Deserialize)]
+struct CropStageFile {
+    #[serde(default)]
+    days: u32,
+    mesh: CropMeshFile,
+    color: Color,
+}
+
+fn default_needs_water() -> bool {
+    return true;
+}
+
+fn default_yield_range() -> (u32, u32) {
+    return (1, 1);
+}
+
+/// The contents of a crop file.
+#[derive(Deserialize)]
+struct CropFile {
+    id: String,
+    name: String,
+    seasons: Vec<Season>,
+    #[serde(default = "default_needs_water")]
+    needs_water: bool,
+    #[serde(default)]
+    withers_after_dry_days: Option<u32>,
+    #[serde(default)]
+    regrow_days: Option<u32>,
+    #[serde(default = "default_yield_range")]
+    yield_range: (u32, u32),
+    stages: Vec<CropStageFile>,
+}
+
+/// A Bevy Engine asset loader for crop files, with the `.crop.ron` extension. It turns
+/// the growth stages into meshes and materials that are part of the crop asset, so the
+/// crop reloads with them when its file changes.
+#[derive(Default)]
+pub struct CropDefinitionLoader;
+
+/// Returns the mesh of a growth stage and how high it is placed to stand on the soil.
+/// Models are loaded as dependencies of the crop.
+fn load_stage_mesh(
+    load_context: &mut LoadContext,
+    dependencies: &mut Vec<AssetPath<'static>>,
+    index: usize,
+    mesh: CropMeshFile,
+) -> Result<(Handle<Mesh>, f32), Error> {
+    let (mesh, height_offset) = match mesh {
+        CropMeshFile::Model(path) => {
+            let asset_path = AssetPath::from(path.as_str()).to_owned();
+            let handle = load_context.get_handle(asset_path.clone());
+            dependencies.push(asset_path);
+            return Ok((handle, 0.0));
+        }
+        CropMeshFile::Sphere { radius } => (
+            Mesh::try_from(shape::Icosphere {
+                radius: radius,
+                subdivisions: 2,
+            })?,
+            radius,
+        ),
+        CropMeshFile::Box { size } => {
+            (shape::Box::new(size.x, size.y, size.z).into(), size.y / 2.0)
+        }
+        CropMeshFile::Cylinder { radius, height } => (
+            shape::Cylinder {
+                radius: radius,
+                height: height,
+                ..Default::default()
+            }
+            .into(),
+            height / 2.0,
+        ),
+    };
+    let handle =
+        load_context.set_labeled_asset(&format!("stage{}_mesh", index), LoadedAsset::new(mesh));
+    return Ok((handle, height_offset));
+}
+
+impl AssetLoader for CropDefinitionLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), Error>> {
+        return Box::pin(async move {
+            let crop_file: CropFile = ron::de::from_bytes(bytes)?;
+            if crop_file.stages.is_empty() {
+                return Err(Error::msg(format!(
+                    "The crop {} has no growth stages",
+                    crop_file.id
+                )));
+            }
+            let mut dependencies = Vec::new();
+            let mut stages = Vec::new();
+            for (index, stage) in crop_file.stages.into_iter().enumerate() {
+                let (mesh, height_offset) =
+                    load_stage_mesh(load_context, &mut dependencies, index, stage.mesh)?;
+                let material = load_context.set_labeled_asset(
+                    &format!("stage{}_material", index),
+                    LoadedAsset::new(StandardMaterial::from(stage.color)),
+                );
+                stages.push(CropStage {
+                    days: stage.days,
+                    mesh: mesh,
+                    material: material,
+                    height_offset: height_offset,
+                });
+            }
+            let mut crop = LoadedAsset::new(CropDefinition {
+                id: crop_file.id,
+                name: crop_file.name,
+                stages: stages,
+                seasons: crop_file.seasons,
+                needs_water: crop_file.needs_water,
+                withers_after_dry_days: crop_file.withers_after_dry_days,
+                regrow_days: crop_file.regrow_days,
+                yield_range: crop_file.yield_range,
+            });
+            for dependency in dependencies {
+                crop = crop.with_dependency(dependency);
+            }
+            load_context.set_default_asset(crop);
+            return Ok(());
+        });
+    }
+
+    fn extensions(&self) -> &[&str] {
+        return &["crop.ron"];
+    }
+}
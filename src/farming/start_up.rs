@@ -0,0 +1,77 @@
+use std::path::Path;
+
+use bevy::prelude::{
+    default, info, shape, warn, AssetServer, Assets, Color, Commands, Mesh, PbrBundle, Res, ResMut,
+    StandardMaterial, Transform, Vec3, Visibility,
+};
+
+use crate::storage::load_ron_or_default;
+
+use super::components::{CropRegistry, FarmAssets, SoilPatch};
+use super::grid::FarmGrid;
+
+/// The file the layout of the farm grid is loaded from.
+const FARM_PLOT_PATH: &str = "assets/world/farm.ron";
+/// The asset folder the crop definitions are loaded from.
+const CROP_FOLDER: &str = "crops";
+/// How thick the soil patches are, so they show above the ground plane.
+const SOIL_THICKNESS: f32 = 0.04;
+
+/// Loads the farm grid with the layout from its data file.
+pub fn load_farm_grid() -> FarmGrid {
+    return FarmGrid::new(load_ron_or_default(
+        Path::new(FARM_PLOT_PATH),
+        "the farm plot",
+    ));
+}
+
+/// Initial system to load every crop definition in the crops asset folder. The asset
+/// server reloads a crop when its file changes.
+pub fn load_crop_definitions(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let handles = match asset_server.load_folder(CROP_FOLDER) {
+        Ok(handles) => handles,
+        Err(error) => {
+            warn!(
+                "Could not load the crop definitions in {}: {}",
+                CROP_FOLDER, error
+            );
+            Vec::new()
+        }
+    };
+    info!("Loading {} crop definitions", handles.len());
+    commands.insert_resource(CropRegistry { handles: handles });
+}
+
+/// Initial system to add the meshes and materials of the farm and a soil patch on every
+/// farm tile. The patches stay hidden until their tile is tilled.
+pub fn add_farm_plot(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    grid: Res<FarmGrid>,
+) {
+    let farm_assets = FarmAssets {
+        soil_mesh: meshes.add(
+            shape::Box::new(grid.tile_size * 0.95, SOIL_THICKNESS, grid.tile_size * 0.95).into(),
+        ),
+        tilled_material: materials.add(Color::rgb(0.45, 0.3, 0.18).into()),
+        watered_material: materials.add(Color::rgb(0.25, 0.16, 0.1).into()),
+        fertilized_material: materials.add(Color::rgb(0.35, 0.25, 0.12).into()),
+        withered_material: materials.add(Color::rgb(0.45, 0.4, 0.25).into()),
+    };
+    for (tile, _) in grid.tiles() {
+        commands.spawn((
+            PbrBundle {
+                mesh: farm_assets.soil_mesh.clone(),
+                material: farm_assets.tilled_material.clone(),
+                transform: Transform::from_translation(
+                    grid.tile_center(tile) + Vec3::new(0.0, SOIL_THICKNESS / 2.0 - 0.01, 0.0),
+                ),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            SoilPatch { tile: tile },
+        ));
+    }
+    commands.insert_resource(farm_assets);
+}
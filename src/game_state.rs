@@ -0,0 +1,24 @@
+use bevy::prelude::{info, Query, Res, ResMut, Resource};
+
+use super::characters::player::input::components::{ActionState, ButtonAction};
+
+/// A Bevy Engine resource that tells if the game is paused. Gameplay systems do not run
+/// while the game is paused.
+#[derive(Resource, Default)]
+pub struct GamePaused(pub bool);
+
+/// A system that pauses or resumes the game when any player presses the pause action.
+pub fn toggle_pause(players: Query<&ActionState>, mut game_paused: ResMut<GamePaused>) {
+    if players
+        .iter()
+        .any(|action_state| action_state.just_pressed(ButtonAction::Pause))
+    {
+        game_paused.0 = !game_paused.0;
+        info!("Game paused: {}", game_paused.0);
+    }
+}
+
+/// A run condition for systems that only run while the game is not paused.
+pub fn game_is_running(game_paused: Res<GamePaused>) -> bool {
+    return !game_paused.0;
+}
@@ -0,0 +1,265 @@
+use std::path::Path;
+
+use bevy::prelude::{info, EventWriter, Res, ResMut, Resource, Time};
+use serde::{Deserialize, Serialize};
+
+use super::storage::load_ron_or_default;
+
+/// The number of in-game hours in a day.
+pub const HOURS_PER_DAY: f32 = 24.0;
+/// The file the settings of the in-game clock are loaded from.
+const CLOCK_SETTINGS_PATH: &str = "assets/world/clock.ron";
+
+/// The seasons of the in-game year, in order.
+#[derive(
+    Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub enum Season {
+    #[default]
+    Spring,
+    Summer,
+    Autumn,
+    Winter,
+}
+
+impl Season {
+    /// Returns the season after this one.
+    pub fn next(self) -> Season {
+        return match self {
+            Season::Spring => Season::Summer,
+            Season::Summer => Season::Autumn,
+            Season::Autumn => Season::Winter,
+            Season::Winter => Season::Spring,
+        };
+    }
+
+    /// Returns the hours of the day the sun rises and sets at in the season.
+    pub fn daylight_hours(self) -> (f32, f32) {
+        return match self {
+            Season::Spring => (6.0, 19.0),
+            Season::Summer => (5.0, 21.0),
+            Season::Autumn => (6.5, 18.5),
+            Season::Winter => (7.5, 17.0),
+        };
+    }
+}
+
+/// The settings of the in-game clock, loaded from a data file so the pace of the game
+/// can be tuned without a rebuild.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct ClockSettings {
+    /// How many real seconds an in-game day lasts.
+    pub day_length_seconds: f32,
+    pub days_per_season: u32,
+    /// The hour of the first day a new game starts at.
+    pub start_hour: f32,
+}
+
+impl Default for ClockSettings {
+    fn default() -> Self {
+        return ClockSettings {
+            day_length_seconds: 720.0,
+            days_per_season: 28,
+            start_hour: 6.0,
+        };
+    }
+}
+
+/// The events of the in-game calendar, sent as the clock passes them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameClockEvent {
+    /// The sun rose.
+    Dawn,
+    /// The sun set.
+    Dusk,
+    /// The clock passed midnight.
+    NewDay,
+    /// The clock passed midnight into the first day of the season.
+    NewSeason(Season),
+}
+
+/// A Bevy Engine resource with the in-game date and time. The clock only advances while
+/// the game is running, so everything driven by it stops while the game is paused.
+#[derive(Resource)]
+pub struct GameClock {
+    /// The year, starting at 1.
+    pub year: u32,
+    pub season: Season,
+    /// The day of the season, starting at 1.
+    pub day: u32,
+    /// The hour of the day, from 0 up to 24.
+    pub hour: f32,
+    pub settings: ClockSettings,
+}
+
+impl Default for GameClock {
+    fn default() -> Self {
+        return GameClock::new(ClockSettings::default());
+    }
+}
+
+impl GameClock {
+    /// Creates a clock at the start of the first day of the first year.
+    pub fn new(settings: ClockSettings) -> Self {
+        return GameClock {
+            year: 1,
+            season: Season::Spring,
+            day: 1,
+            hour: settings.start_hour,
+            settings: settings,
+        };
+    }
+
+    /// Moves the clock on to the next day, and the next season and year when the day
+    /// rolls over.
+    fn start_next_day(&mut self, events: &mut Vec<GameClockEvent>) {
+        self.day += 1;
+        events.push(GameClockEvent::NewDay);
+        if self.day > self.settings.days_per_season {
+            self.day = 1;
+            self.season = self.season.next();
+            if self.season == Season::Spring {
+                self.year += 1;
+            }
+            events.push(GameClockEvent::NewSeason(self.season));
+        }
+    }
+
+    /// Advances the clock by a number of in-game hours, rolling over into the next days.
+    /// Returns the events the clock passed, in order, so even a long skip like sleeping
+    /// through the night sends every dawn, dusk and new day.
+    pub fn advance_hours(&mut self, hours: f32) -> Vec<GameClockEvent> {
+        let mut events = Vec::new();
+        let mut hours_left = hours;
+        while hours_left > 0.0 {
+            let (sunrise, sunset) = self.season.daylight_hours();
+            let hours_to_midnight = HOURS_PER_DAY - self.hour;
+            let reaches_midnight = hours_left >= hours_to_midnight;
+            let end_hour = if reaches_midnight {
+                HOURS_PER_DAY
+            } else {
+                self.hour + hours_left
+            };
+            if self.hour < sunrise && end_hour >= sunrise {
+                events.push(GameClockEvent::Dawn);
+            }
+            if self.hour < sunset && end_hour >= sunset {
+                events.push(GameClockEvent::Dusk);
+            }
+            if reaches_midnight {
+                hours_left -= hours_to_midnight;
+                self.hour = 0.0;
+                self.start_next_day(&mut events);
+            } else {
+                hours_left = 0.0;
+                self.hour = end_hour;
+            }
+        }
+        return events;
+    }
+}
+
+/// Loads the in-game clock with the settings from their data file.
+pub fn load_game_clock() -> GameClock {
+    return GameClock::new(load_ron_or_default(
+        Path::new(CLOCK_SETTINGS_PATH),
+        "the clock settings",
+    ));
+}
+
+/// A system that advances the in-game clock with the real time and sends the calendar
+/// events the clock passed.
+pub fn advance_game_clock(
+    timer: Res<Time>,
+    mut clock: ResMut<GameClock>,
+    mut clock_events: EventWriter<GameClockEvent>,
+) {
+    if clock.settings.day_length_seconds <= f32::EPSILON {
+        return;
+    }
+    let hours = timer.delta_seconds() / clock.settings.day_length_seconds * HOURS_PER_DAY;
+    for event in clock.advance_hours(hours) {
+        match event {
+            GameClockEvent::NewDay => info!(
+                "Day {} of {:?} in year {}",
+                clock.day, clock.season, clock.year
+            ),
+            GameClockEvent::NewSeason(season) => info!("{:?} has begun", season),
+            _ => {}
+        }
+        clock_events.send(event);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ClockSettings, GameClock, GameClockEvent, Season};
+
+    fn clock_at(season: Season, day: u32, hour: f32) -> GameClock {
+        return GameClock {
+            season: season,
+            day: day,
+            hour: hour,
+            ..GameClock::new(ClockSettings {
+                days_per_season: 2,
+                ..ClockSettings::default()
+            })
+        };
+    }
+
+    #[test]
+    fn advances_within_the_day_without_events() {
+        let mut clock = clock_at(Season::Spring, 1, 8.0);
+        assert!(clock.advance_hours(2.5).is_empty());
+        assert_eq!(clock.hour, 10.5);
+        assert_eq!(clock.day, 1);
+    }
+
+    #[test]
+    fn sends_dawn_and_dusk_as_the_clock_passes_them() {
+        let mut clock = clock_at(Season::Summer, 1, 4.0);
+        assert_eq!(clock.advance_hours(1.5), vec![GameClockEvent::Dawn]);
+        assert_eq!(clock.advance_hours(16.0), vec![GameClockEvent::Dusk]);
+        assert_eq!(clock.hour, 21.5);
+    }
+
+    #[test]
+    fn rolls_over_into_the_next_day_at_midnight() {
+        let mut clock = clock_at(Season::Spring, 1, 22.0);
+        assert_eq!(clock.advance_hours(2.0), vec![GameClockEvent::NewDay]);
+        assert_eq!(clock.hour, 0.0);
+        assert_eq!(clock.day, 2);
+    }
+
+    #[test]
+    fn sends_every_event_of_a_long_skip_in_order() {
+        let mut clock = clock_at(Season::Spring, 1, 20.0);
+        assert_eq!(
+            clock.advance_hours(30.0),
+            vec![
+                GameClockEvent::NewDay,
+                GameClockEvent::Dawn,
+                GameClockEvent::Dusk,
+                GameClockEvent::NewDay,
+                GameClockEvent::NewSeason(Season::Summer),
+            ]
+        );
+        assert_eq!(clock.hour, 2.0);
+        assert_eq!((clock.season, clock.day), (Season::Summer, 1));
+    }
+
+    #[test]
+    fn starts_a_new_year_after_winter() {
+        let mut clock = clock_at(Season::Winter, 2, 23.0);
+        assert_eq!(
+            clock.advance_hours(2.0),
+            vec![
+                GameClockEvent::NewDay,
+                GameClockEvent::NewSeason(Season::Spring)
+            ]
+        );
+        assert_eq!(clock.year, 2);
+        assert_eq!((clock.season, clock.day), (Season::Spring, 1));
+        assert_eq!(clock.hour, 1.0);
+    }
+}
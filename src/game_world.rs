@@ -1,16 +1,206 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
 use bevy::prelude::{
-    default, shape, Assets, Color, Commands, Mesh, PbrBundle, ResMut, StandardMaterial,
+    default, shape, Assets, Color, Commands, Handle, Mesh, PbrBundle, Quat, ResMut, Resource,
+    StandardMaterial, Transform, TransformBundle, Vec3,
 };
+use serde::Deserialize;
+
+use super::characters::player::camera::components::FadeWhenOccluding;
+use super::collision::components::{ColliderShape, StaticCollider};
+use super::storage::load_ron_or_default;
+
+/// The length of the sides of the square ground plane.
+pub const GROUND_SIZE: f32 = 50.0;
+/// The file the named locations of the world are loaded from.
+const WORLD_LOCATIONS_PATH: &str = "assets/world/locations.ron";
 
-/// Initial system to render a flat ground plane.
+/// A Bevy Engine resource with the named locations of the world, like homes and
+/// workplaces, that characters can head to.
+#[derive(Resource, Default, Deserialize)]
+#[serde(transparent)]
+pub struct WorldLocations(pub BTreeMap<String, Vec3>);
+
+/// Loads the named locations of the world from their data file.
+pub fn load_world_locations() -> WorldLocations {
+    return load_ron_or_default(Path::new(WORLD_LOCATIONS_PATH), "the world locations");
+}
+
+/// Initial system to render a flat ground plane. The ground has a collider that is a
+/// thin box with its top face level with the plane.
 pub fn add_ground_plane(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
 ) {
-    commands.spawn(PbrBundle {
-        mesh: meshes.add(shape::Plane::from_size(50.0).into()),
-        material: materials.add(Color::SILVER.into()),
-        ..default()
-    });
+    let ground_thickness = 1.0;
+    commands.spawn((
+        PbrBundle {
+            mesh: meshes.add(shape::Plane::from_size(GROUND_SIZE).into()),
+            material: materials.add(Color::SILVER.into()),
+            ..default()
+        },
+        StaticCollider {
+            shape: ColliderShape::Cuboid {
+                half_extents: Vec3::new(GROUND_SIZE, ground_thickness, GROUND_SIZE) / 2.0,
+            },
+            offset: Vec3::new(0.0, -ground_thickness / 2.0, 0.0),
+        },
+    ));
+}
+
+/// Spawns a piece of scenery that blocks the cameras and fades out when it hides a
+/// player.
+fn spawn_scenery_piece(
+    commands: &mut Commands,
+    mesh: Handle<Mesh>,
+    material: Handle<StandardMaterial>,
+    transform: Transform,
+    shape: ColliderShape,
+) {
+    commands.spawn((
+        PbrBundle {
+            mesh: mesh,
+            material: material,
+            transform: transform,
+            ..default()
+        },
+        StaticCollider {
+            shape: shape,
+            offset: Vec3::ZERO,
+        },
+        FadeWhenOccluding::default(),
+    ));
+}
+
+/// Initial system to add some scenery to the ground plane, a shed, a couple of trees and
+/// a raised deck with a ramp and a step up to it.
+pub fn add_scenery(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let shed_size = Vec3::new(4.0, 3.0, 5.0);
+    spawn_scenery_piece(
+        &mut commands,
+        meshes.add(shape::Box::new(shed_size.x, shed_size.y, shed_size.z).into()),
+        materials.add(Color::hex("#a0522d").unwrap().into()),
+        Transform::from_xyz(10.0, shed_size.y / 2.0, -8.0),
+        ColliderShape::Cuboid {
+            half_extents: shed_size / 2.0,
+        },
+    );
+    let trunk_size = Vec3::new(0.6, 3.0, 0.6);
+    let canopy_radius = 1.8;
+    let trunk_mesh = meshes.add(shape::Box::new(trunk_size.x, trunk_size.y, trunk_size.z).into());
+    let trunk_material = materials.add(Color::hex("#6b4423").unwrap().into());
+    let canopy_mesh = meshes.add(
+        Mesh::try_from(shape::Icosphere {
+            radius: canopy_radius,
+            subdivisions: 8,
+        })
+        .unwrap(),
+    );
+    let canopy_material = materials.add(Color::hex("#3a7d44").unwrap().into());
+    for tree_location in [Vec3::new(-8.0, 0.0, 6.0), Vec3::new(-12.0, 0.0, -10.0)] {
+        spawn_scenery_piece(
+            &mut commands,
+            trunk_mesh.clone(),
+            trunk_material.clone(),
+            Transform::from_translation(tree_location + Vec3::new(0.0, trunk_size.y / 2.0, 0.0)),
+            ColliderShape::Cuboid {
+                half_extents: trunk_size / 2.0,
+            },
+        );
+        spawn_scenery_piece(
+            &mut commands,
+            canopy_mesh.clone(),
+            canopy_material.clone(),
+            Transform::from_translation(
+                tree_location + Vec3::new(0.0, trunk_size.y + canopy_radius * 0.7, 0.0),
+            ),
+            ColliderShape::Sphere {
+                radius: canopy_radius,
+            },
+        );
+    }
+    let wood_material = materials.add(Color::hex("#c8a165").unwrap().into());
+    let deck_size = Vec3::new(6.0, 0.6, 6.0);
+    let deck_center = Vec3::new(-2.0, deck_size.y / 2.0, -14.0);
+    spawn_scenery_piece(
+        &mut commands,
+        meshes.add(shape::Box::new(deck_size.x, deck_size.y, deck_size.z).into()),
+        wood_material.clone(),
+        Transform::from_translation(deck_center),
+        ColliderShape::Cuboid {
+            half_extents: deck_size / 2.0,
+        },
+    );
+    // A ramp from the ground up to the east side of the deck.
+    let ramp_angle = f32::atan2(deck_size.y, 4.0);
+    let ramp_size = Vec3::new(4.0 / ramp_angle.cos(), 0.2, 3.0);
+    let ramp_center = deck_center + Vec3::new(deck_size.x / 2.0 + 2.0, 0.0, 0.0);
+    spawn_scenery_piece(
+        &mut commands,
+        meshes.add(shape::Box::new(ramp_size.x, ramp_size.y, ramp_size.z).into()),
+        wood_material.clone(),
+        Transform::from_translation(ramp_center).with_rotation(Quat::from_rotation_z(-ramp_angle)),
+        ColliderShape::Cuboid {
+            half_extents: ramp_size / 2.0,
+        },
+    );
+    // A step lower than the deck on its south side.
+    let step_size = Vec3::new(3.0, deck_size.y / 2.0, 1.0);
+    spawn_scenery_piece(
+        &mut commands,
+        meshes.add(shape::Box::new(step_size.x, step_size.y, step_size.z).into()),
+        wood_material,
+        Transform::from_translation(Vec3::new(
+            deck_center.x,
+            step_size.y / 2.0,
+            deck_center.z + deck_size.z / 2.0 + step_size.z / 2.0,
+        )),
+        ColliderShape::Cuboid {
+            half_extents: step_size / 2.0,
+        },
+    );
+}
+
+/// Initial system to add invisible walls around the edges of the ground plane so the
+/// characters can not walk off it.
+pub fn add_world_bounds(mut commands: Commands) {
+    let ground_half_size = GROUND_SIZE / 2.0;
+    let wall_half_thickness = 0.5;
+    let wall_half_height = 10.0;
+    for (direction, half_extents) in [
+        (
+            Vec3::X,
+            Vec3::new(wall_half_thickness, wall_half_height, ground_half_size),
+        ),
+        (
+            Vec3::NEG_X,
+            Vec3::new(wall_half_thickness, wall_half_height, ground_half_size),
+        ),
+        (
+            Vec3::Z,
+            Vec3::new(ground_half_size, wall_half_height, wall_half_thickness),
+        ),
+        (
+            Vec3::NEG_Z,
+            Vec3::new(ground_half_size, wall_half_height, wall_half_thickness),
+        ),
+    ] {
+        commands.spawn((
+            TransformBundle::from_transform(Transform::from_translation(
+                direction * (ground_half_size + wall_half_thickness) + Vec3::Y * wall_half_height,
+            )),
+            StaticCollider {
+                shape: ColliderShape::Cuboid {
+                    half_extents: half_extents,
+                },
+                offset: Vec3::ZERO,
+            },
+        ));
+    }
 }
@@ -0,0 +1,7 @@
+/// interaction module for the crate. World objects and characters players interact
+/// with, the focus every player has on the one in front of them, and the handlers of
+/// the interactions.
+pub(crate) mod components;
+pub(crate) mod control;
+pub(crate) mod handlers;
+pub(crate) mod start_up;
@@ -0,0 +1,109 @@
+use bevy::prelude::{Component, Entity, Handle, Mesh, Resource, StandardMaterial};
+
+use crate::items::inventory::ItemStack;
+
+/// How far from a player the things they interact with can be, unless they have their
+/// own reach.
+pub const INTERACTION_REACH: f32 = 2.0;
+
+/// The kinds of interactions, each handled by its own system.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InteractionKind {
+    Door,
+    Talk,
+    PickUp,
+    Sit,
+    OpenChest,
+    Craft,
+}
+
+/// A component for a world object or character that players interact with. Players
+/// focus the interactable in reach that they face the most, and see its prompt.
+#[derive(Component)]
+pub struct Interactable {
+    pub kind: InteractionKind,
+    /// What the interaction does, shown after the button in the prompt.
+    pub prompt: String,
+    /// How far from a player the interactable can be for them to focus it.
+    pub reach: f32,
+    /// How high above the interactable the focus marker floats.
+    pub marker_height: f32,
+    /// If players can focus the interactable, like a seat nobody sits on.
+    pub enabled: bool,
+}
+
+impl Interactable {
+    /// Creates an enabled interactable with the default reach.
+    pub fn new(kind: InteractionKind, prompt: impl Into<String>, marker_height: f32) -> Self {
+        return Interactable {
+            kind: kind,
+            prompt: prompt.into(),
+            reach: INTERACTION_REACH,
+            marker_height: marker_height,
+            enabled: true,
+        };
+    }
+}
+
+/// A component on a player with the interactable they focus and the prompt that shows
+/// it. Every player has a focus of their own.
+#[derive(Component)]
+pub struct InteractionFocus {
+    pub target: Option<Entity>,
+    /// The prompt text node in the overlay of the player.
+    pub prompt: Entity,
+}
+
+/// A component for the marker floating over the interactable a player focuses. Every
+/// player has a marker of their own.
+#[derive(Component)]
+pub struct FocusMarker {
+    pub player: Entity,
+}
+
+/// A Bevy Engine resource with the mesh and material of the focus markers.
+#[derive(Resource)]
+pub struct FocusMarkerAssets {
+    pub mesh: Handle<Mesh>,
+    pub material: Handle<StandardMaterial>,
+}
+
+/// A Bevy Engine resource with the mesh and material of the pickups.
+#[derive(Resource)]
+pub struct PickupAssets {
+    pub mesh: Handle<Mesh>,
+    pub material: Handle<StandardMaterial>,
+}
+
+/// An event sent when a player interacts with the interactable they focus.
+pub struct InteractionEvent {
+    pub player: Entity,
+    pub target: Entity,
+    pub kind: InteractionKind,
+}
+
+/// A component for a door that swings open and closed around its hinge.
+#[derive(Component)]
+pub struct Door {
+    pub open: bool,
+    /// The turn around the hinge of the closed door, in radians.
+    pub closed_angle: f32,
+}
+
+/// A component for a seat a character sits on.
+#[derive(Component)]
+pub struct Seat {
+    pub occupant: Option<Entity>,
+}
+
+/// A component on a character with the seat they sit on.
+#[derive(Component)]
+pub struct SittingOn {
+    pub seat: Entity,
+}
+
+/// A component for a stack of items lying in the world for players to pick up.
+#[derive(Component)]
+pub struct Pickup {
+    pub stack: ItemStack,
+}
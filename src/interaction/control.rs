@@ -0,0 +1,259 @@
+use bevy::pbr::NotShadowCaster;
+use bevy::prelude::{
+    default, Color, Commands, Entity, EventWriter, GlobalTransform, Or, PbrBundle, PositionType,
+    Quat, Query, Res, Style, Text, TextBundle, TextStyle, Time, Transform, UiRect, Val, Vec3,
+    Visibility, With, Without,
+};
+
+use crate::characters::animation::components::CharacterActivity;
+use crate::characters::player::control::Controller;
+use crate::characters::player::entity::components::PlayerCharacter;
+use crate::characters::player::input::bindings::InputBindings;
+use crate::characters::player::input::components::{ActionState, ButtonAction, InputProfile};
+use crate::characters::player::profile::components::{ChoosingProfile, CustomizingAppearance};
+use crate::characters::profession::components::ChoosingProfession;
+use crate::crafting::components::CraftingMenu;
+use crate::items::components::InventoryMenu;
+use crate::ui::player_overlay::{spawn_in_player_overlay, PlayerOverlayLink};
+use crate::ui::start_up::UiFont;
+
+use super::components::{
+    FocusMarker, FocusMarkerAssets, Interactable, InteractionEvent, InteractionFocus,
+};
+
+/// How far a player can face away from an interactable, as the cosine of the angle, for
+/// them to focus it.
+const FOCUS_FACING: f32 = 0.2;
+/// How close an interactable can be to a player to be focused whichever way they face.
+const FOCUS_ANY_FACING_DISTANCE: f32 = 0.4;
+/// How far the focus markers bob up and down.
+const MARKER_BOB_HEIGHT: f32 = 0.08;
+
+/// Returns how well the player at the transform focuses the interactable at the
+/// position, lower is better, or None when it is out of reach or behind the player.
+/// Interactables straight ahead are preferred over closer ones off to the side.
+fn focus_score(transform: &Transform, position: Vec3, reach: f32) -> Option<f32> {
+    let offset = (position - transform.translation) * Vec3::new(1.0, 0.0, 1.0);
+    let distance = offset.length();
+    if distance > reach {
+        return None;
+    }
+    let forward = (transform.forward() * Vec3::new(1.0, 0.0, 1.0)).normalize_or_zero();
+    let facing = forward.dot(offset.normalize_or_zero());
+    if distance > FOCUS_ANY_FACING_DISTANCE && facing < FOCUS_FACING {
+        return None;
+    }
+    return Some(distance * (2.0 - facing));
+}
+
+/// A system that adds an interaction focus to every player that does not have one,
+/// with a hidden prompt in their overlay and a hidden marker in the world.
+pub fn spawn_interaction_focuses(
+    mut commands: Commands,
+    ui_font: Res<UiFont>,
+    marker_assets: Res<FocusMarkerAssets>,
+    players: Query<
+        (Entity, &PlayerOverlayLink),
+        (With<PlayerCharacter>, Without<InteractionFocus>),
+    >,
+) {
+    for (player_entity, overlay_link) in players.iter() {
+        let prompt = spawn_in_player_overlay(
+            &mut commands,
+            overlay_link,
+            TextBundle {
+                visibility: Visibility::Hidden,
+                ..TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: ui_font.0.clone(),
+                        font_size: 20.0,
+                        color: Color::WHITE,
+                    },
+                )
+                .with_style(Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        bottom: Val::Px(84.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+            },
+        );
+        commands.spawn((
+            PbrBundle {
+                mesh: marker_assets.mesh.clone(),
+                material: marker_assets.material.clone(),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            FocusMarker {
+                player: player_entity,
+            },
+            NotShadowCaster,
+        ));
+        commands.entity(player_entity).insert(InteractionFocus {
+            target: None,
+            prompt: prompt,
+        });
+    }
+}
+
+/// A system that picks the interactable every player focuses, the enabled one in reach
+/// they face the most. Players in a menu or busy with an activity other than carrying
+/// focus nothing.
+pub fn update_interaction_focuses(
+    mut players: Query<
+        (
+            Entity,
+            &Transform,
+            &CharacterActivity,
+            &mut InteractionFocus,
+        ),
+        With<PlayerCharacter>,
+    >,
+    busy_players: Query<
+        (),
+        Or<(
+            With<InventoryMenu>,
+            With<CraftingMenu>,
+            With<ChoosingProfession>,
+            With<ChoosingProfile>,
+            With<CustomizingAppearance>,
+        )>,
+    >,
+    interactables: Query<(Entity, &Interactable, &GlobalTransform)>,
+) {
+    for (player_entity, transform, activity, mut focus) in players.iter_mut() {
+        let mut target = None;
+        let free = matches!(
+            activity,
+            CharacterActivity::None | CharacterActivity::Carrying
+        );
+        if free && !busy_players.contains(player_entity) {
+            let mut best_score = f32::MAX;
+            for (entity, interactable, interactable_transform) in interactables.iter() {
+                if !interactable.enabled || entity == player_entity {
+                    continue;
+                }
+                let Some(score) = focus_score(
+                    transform,
+                    interactable_transform.translation(),
+                    interactable.reach,
+                ) else {
+                    continue;
+                };
+                if score < best_score {
+                    best_score = score;
+                    target = Some(entity);
+                }
+            }
+        }
+        if focus.target != target {
+            focus.target = target;
+        }
+    }
+}
+
+/// A system that sends an interaction event for every player that presses the interact
+/// action while they focus an interactable.
+pub fn dispatch_interactions(
+    players: Query<(Entity, &ActionState, &InteractionFocus)>,
+    interactables: Query<&Interactable>,
+    mut interaction_events: EventWriter<InteractionEvent>,
+) {
+    for (player_entity, action_state, focus) in players.iter() {
+        if !action_state.just_pressed(ButtonAction::Interact) {
+            continue;
+        }
+        let Some(target) = focus.target else {
+            continue;
+        };
+        let Ok(interactable) = interactables.get(target) else {
+            continue;
+        };
+        interaction_events.send(InteractionEvent {
+            player: player_entity,
+            target: target,
+            kind: interactable.kind,
+        });
+    }
+}
+
+/// A system that shows the prompt of the focused interactable to every player, with the
+/// button they have bound to the interact action on their input source.
+pub fn update_interaction_prompts(
+    input_bindings: Res<InputBindings>,
+    players: Query<(&Controller, &InputProfile, &InteractionFocus)>,
+    interactables: Query<&Interactable>,
+    mut prompts: Query<(&mut Text, &mut Visibility)>,
+) {
+    for (controller, input_profile, focus) in players.iter() {
+        let Ok((mut text, mut visibility)) = prompts.get_mut(focus.prompt) else {
+            continue;
+        };
+        let interactable = focus
+            .target
+            .and_then(|target| interactables.get(target).ok());
+        let Some(interactable) = interactable else {
+            if *visibility != Visibility::Hidden {
+                *visibility = Visibility::Hidden;
+            }
+            continue;
+        };
+        let button = input_bindings
+            .button_label(input_profile, controller.source, ButtonAction::Interact)
+            .unwrap_or_else(|| "Interact".to_string());
+        let prompt = format!("[{}] {}", button, interactable.prompt);
+        if text.sections[0].value != prompt {
+            text.sections[0].value = prompt;
+        }
+        if *visibility != Visibility::Inherited {
+            *visibility = Visibility::Inherited;
+        }
+    }
+}
+
+/// A system that floats the marker of every player over the interactable they focus,
+/// bobbing and spinning so it stands out, and hides it when they focus nothing.
+pub fn update_focus_markers(
+    timer: Res<Time>,
+    players: Query<&InteractionFocus>,
+    interactables: Query<(&Interactable, &GlobalTransform)>,
+    mut markers: Query<(&FocusMarker, &mut Transform, &mut Visibility)>,
+) {
+    let seconds = timer.elapsed_seconds();
+    for (marker, mut transform, mut visibility) in markers.iter_mut() {
+        let target = players
+            .get(marker.player)
+            .ok()
+            .and_then(|focus| focus.target)
+            .and_then(|target| interactables.get(target).ok());
+        let Some((interactable, target_transform)) = target else {
+            if *visibility != Visibility::Hidden {
+                *visibility = Visibility::Hidden;
+            }
+            continue;
+        };
+        transform.translation = target_transform.translation()
+            + Vec3::Y * (interactable.marker_height + MARKER_BOB_HEIGHT * (seconds * 3.0).sin());
+        transform.rotation = Quat::from_rotation_y(seconds * 2.0);
+        if *visibility != Visibility::Visible {
+            *visibility = Visibility::Visible;
+        }
+    }
+}
+
+/// A system that despawns the focus markers of players that left the game.
+pub fn despawn_orphaned_focus_markers(
+    mut commands: Commands,
+    players: Query<&InteractionFocus>,
+    markers: Query<(Entity, &FocusMarker)>,
+) {
+    for (marker_entity, marker) in markers.iter() {
+        if players.get(marker.player).is_err() {
+            commands.entity(marker_entity).despawn();
+        }
+    }
+}
@@ -0,0 +1,259 @@
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::prelude::{
+    Commands, DespawnRecursiveExt, Entity, EventReader, EventWriter, GlobalTransform, Quat, Query,
+    Res, Time, Transform, Vec3,
+};
+
+use crate::characters::animation::components::CharacterActivity;
+use crate::characters::components::MovementIntent;
+use crate::characters::control::turn_toward;
+use crate::characters::player::input::components::{ActionState, ButtonAction, StickAction};
+use crate::items::components::ItemRegistry;
+use crate::items::inventory::{Inventory, ItemStack};
+use crate::ui::player_overlay::PlayerMessageEvent;
+
+use super::components::{
+    Door, Interactable, InteractionEvent, InteractionKind, Pickup, Seat, SittingOn,
+};
+
+/// How fast doors swing, in radians per second.
+const DOOR_SWING_RATE: f32 = 4.0;
+/// How far in front of a seat a character sitting on it is placed.
+const SEAT_DEPTH: f32 = 0.55;
+/// How far the move stick has to be pushed for a sitting player to stand up.
+const STAND_UP_DEFLECTION: f32 = 0.3;
+
+/// Returns the prompt of a pickup of the stack.
+pub fn pickup_prompt(registry: &ItemRegistry, stack: &ItemStack) -> String {
+    if stack.count > 1 {
+        return format!("Pick up {} {}", stack.count, registry.name(&stack.item));
+    }
+    return format!("Pick up {}", registry.name(&stack.item));
+}
+
+/// A system that opens and closes the doors players interact with.
+pub fn toggle_doors(
+    mut interaction_events: EventReader<InteractionEvent>,
+    mut doors: Query<(&mut Door, &mut Interactable)>,
+) {
+    for event in interaction_events.iter() {
+        if event.kind != InteractionKind::Door {
+            continue;
+        }
+        let Ok((mut door, mut interactable)) = doors.get_mut(event.target) else {
+            continue;
+        };
+        door.open = !door.open;
+        interactable.prompt = if door.open {
+            "Close door".to_string()
+        } else {
+            "Open door".to_string()
+        };
+    }
+}
+
+/// A system that swings every door around its hinge toward its open or closed angle.
+/// Doors swing outward, away from the side their hinge faces.
+pub fn swing_doors(timer: Res<Time>, mut doors: Query<(&Door, &mut Transform)>) {
+    for (door, mut transform) in doors.iter_mut() {
+        let angle = if door.open {
+            door.closed_angle - FRAC_PI_2
+        } else {
+            door.closed_angle
+        };
+        let target_rotation = Quat::from_rotation_y(angle);
+        if transform.rotation != target_rotation {
+            transform.rotation = turn_toward(
+                transform.rotation,
+                target_rotation,
+                DOOR_SWING_RATE * timer.delta_seconds(),
+            );
+        }
+    }
+}
+
+/// A system that sits the players who interact with a free seat down on it, in front of
+/// the seat and facing the way it faces.
+pub fn sit_on_seats(
+    mut commands: Commands,
+    mut interaction_events: EventReader<InteractionEvent>,
+    mut seats: Query<(&mut Seat, &mut Interactable, &GlobalTransform)>,
+    mut players: Query<(&mut Transform, &mut CharacterActivity, &mut MovementIntent)>,
+) {
+    for event in interaction_events.iter() {
+        if event.kind != InteractionKind::Sit {
+            continue;
+        }
+        let Ok((mut seat, mut interactable, seat_transform)) = seats.get_mut(event.target) else {
+            continue;
+        };
+        let Ok((mut transform, mut activity, mut movement_intent)) = players.get_mut(event.player)
+        else {
+            continue;
+        };
+        if seat.occupant.is_some() {
+            continue;
+        }
+        let (_, seat_rotation, seat_position) = seat_transform.to_scale_rotation_translation();
+        let position = seat_position + seat_rotation * Vec3::NEG_Z * SEAT_DEPTH;
+        transform.translation.x = position.x;
+        transform.translation.z = position.z;
+        transform.rotation = seat_rotation;
+        *activity = CharacterActivity::Sitting;
+        *movement_intent = MovementIntent::default();
+        seat.occupant = Some(event.player);
+        interactable.enabled = false;
+        commands
+            .entity(event.player)
+            .insert(SittingOn { seat: event.target });
+    }
+}
+
+/// A system that stands the sitting players up when they push the move stick or press
+/// the interact action, and frees the seats of characters that are gone.
+pub fn stand_up_from_seats(
+    mut commands: Commands,
+    mut players: Query<(Entity, &ActionState, &mut CharacterActivity, &SittingOn)>,
+    mut seats: Query<(&mut Seat, &mut Interactable)>,
+) {
+    for (player_entity, action_state, mut activity, sitting_on) in players.iter_mut() {
+        let stands_up = *activity != CharacterActivity::Sitting
+            || action_state.stick(StickAction::Move).length() > STAND_UP_DEFLECTION
+            || action_state.just_pressed(ButtonAction::Interact);
+        if !stands_up {
+            continue;
+        }
+        if *activity == CharacterActivity::Sitting {
+            *activity = CharacterActivity::None;
+        }
+        commands.entity(player_entity).remove::<SittingOn>();
+        if let Ok((mut seat, mut interactable)) = seats.get_mut(sitting_on.seat) {
+            seat.occupant = None;
+            interactable.enabled = true;
+        }
+    }
+    for (mut seat, mut interactable) in seats.iter_mut() {
+        let occupant_gone = seat
+            .occupant
+            .is_some_and(|occupant| players.get(occupant).is_err());
+        if occupant_gone {
+            seat.occupant = None;
+            interactable.enabled = true;
+        }
+    }
+}
+
+/// A system that puts the items of the pickups players interact with in their
+/// inventory. What does not fit stays on the ground.
+pub fn pick_up_items(
+    mut commands: Commands,
+    registry: Res<ItemRegistry>,
+    mut interaction_events: EventReader<InteractionEvent>,
+    mut pickups: Query<(&mut Pickup, &mut Interactable)>,
+    mut inventories: Query<&mut Inventory>,
+    mut messages: EventWriter<PlayerMessageEvent>,
+) {
+    for event in interaction_events.iter() {
+        if event.kind != InteractionKind::PickUp {
+            continue;
+        }
+        let Ok((mut pickup, mut interactable)) = pickups.get_mut(event.target) else {
+            continue;
+        };
+        // A pickup emptied by another player this frame is only despawned at the end of
+        // the frame.
+        if pickup.stack.count == 0 {
+            continue;
+        }
+        let Ok(mut inventory) = inventories.get_mut(event.player) else {
+            continue;
+        };
+        let stack = pickup.stack.clone();
+        let left = inventory.add(&registry, &stack.item, stack.count);
+        let picked_up = stack.count - left;
+        messages.send(PlayerMessageEvent {
+            player: event.player,
+            text: if picked_up > 0 {
+                format!("Picked up {} {}", picked_up, registry.name(&stack.item))
+            } else {
+                format!("No room for {}", registry.name(&stack.item))
+            },
+        });
+        pickup.stack.count = left;
+        if left == 0 {
+            interactable.enabled = false;
+            commands.entity(event.target).despawn_recursive();
+            continue;
+        }
+        interactable.prompt = pickup_prompt(&registry, &pickup.stack);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use bevy::prelude::App;
+
+    use crate::items::components::{ItemCategory, ItemDefinition, ItemRegistry};
+    use crate::items::inventory::{Inventory, ItemStack};
+    use crate::ui::player_overlay::PlayerMessageEvent;
+
+    use super::super::components::{Interactable, InteractionEvent, InteractionKind, Pickup};
+    use super::pick_up_items;
+
+    #[test]
+    fn gives_a_pickup_to_only_one_of_the_players_taking_it_at_once() {
+        let mut app = App::new();
+        app.insert_resource(ItemRegistry {
+            items: vec![ItemDefinition {
+                id: "turnip".to_string(),
+                name: "Turnip".to_string(),
+                category: ItemCategory::Crop,
+                max_stack: 99,
+                icon: None,
+                metadata: BTreeMap::new(),
+            }],
+            starting_items: Vec::new(),
+        })
+        .add_event::<InteractionEvent>()
+        .add_event::<PlayerMessageEvent>()
+        .add_system(pick_up_items);
+        let players = [
+            app.world.spawn(Inventory::new(4)).id(),
+            app.world.spawn(Inventory::new(4)).id(),
+        ];
+        let pickup = app
+            .world
+            .spawn((
+                Pickup {
+                    stack: ItemStack {
+                        item: "turnip".to_string(),
+                        count: 5,
+                    },
+                },
+                Interactable::new(InteractionKind::PickUp, "Pick up 5 Turnip", 0.5),
+            ))
+            .id();
+        for player in players {
+            app.world.send_event(InteractionEvent {
+                player: player,
+                target: pickup,
+                kind: InteractionKind::PickUp,
+            });
+        }
+        app.update();
+        let counts: Vec<u32> = players
+            .iter()
+            .map(|player| {
+                app.world
+                    .get::<Inventory>(*player)
+                    .unwrap()
+                    .count_of("turnip")
+            })
+            .collect();
+        assert_eq!(counts, vec![5, 0]);
+        assert!(app.world.get_entity(pickup).is_none());
+    }
+}
@@ -0,0 +1,199 @@
+use std::path::Path;
+
+use bevy::prelude::{
+    default, shape, warn, Assets, BuildChildren, Color, Commands, Entity, Mesh, PbrBundle, Quat,
+    Res, ResMut, SpatialBundle, StandardMaterial, Transform, Vec3,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::collision::components::{ColliderShape, StaticCollider};
+use crate::items::components::ItemRegistry;
+use crate::items::inventory::ItemStack;
+use crate::storage::load_ron_or_default;
+
+use super::components::{
+    Door, FocusMarkerAssets, Interactable, InteractionKind, Pickup, PickupAssets, Seat,
+};
+use super::handlers::pickup_prompt;
+
+/// The file the doors, seats and pickups of the world are loaded from.
+const INTERACTABLES_PATH: &str = "assets/world/interactables.ron";
+/// The size of a door panel.
+const DOOR_SIZE: Vec3 = Vec3::new(1.2, 2.2, 0.1);
+/// The size of a bench seat.
+const BENCH_SIZE: Vec3 = Vec3::new(1.6, 0.45, 0.5);
+/// The size of the box a pickup lies in.
+const PICKUP_SIZE: f32 = 0.35;
+
+/// Where a door, seat or pickup is placed in the world. The rotation is the turn around
+/// the vertical axis in degrees.
+#[derive(Serialize, Deserialize)]
+struct Placement {
+    position: Vec3,
+    #[serde(default)]
+    rotation: f32,
+}
+
+/// Where a pickup lies in the world and what it holds.
+#[derive(Serialize, Deserialize)]
+struct PickupPlacement {
+    position: Vec3,
+    item: String,
+    count: u32,
+}
+
+/// The doors, seats and pickups of the world, as they are in their data file. Doors are
+/// placed by their hinge and swing their panel toward the positive x axis.
+#[derive(Default, Serialize, Deserialize)]
+struct InteractablesFile {
+    #[serde(default)]
+    doors: Vec<Placement>,
+    #[serde(default)]
+    seats: Vec<Placement>,
+    #[serde(default)]
+    pickups: Vec<PickupPlacement>,
+}
+
+/// Loads the interactables of the world from their data file.
+fn load_interactables() -> InteractablesFile {
+    return load_ron_or_default(Path::new(INTERACTABLES_PATH), "the interactables");
+}
+
+/// Initial system to add the mesh and material of the markers over focused
+/// interactables.
+pub fn add_focus_marker_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(FocusMarkerAssets {
+        mesh: meshes.add(
+            Mesh::try_from(shape::Icosphere {
+                radius: 0.12,
+                subdivisions: 0,
+            })
+            .unwrap(),
+        ),
+        material: materials.add(StandardMaterial {
+            base_color: Color::rgb(1.0, 0.85, 0.2),
+            unlit: true,
+            ..default()
+        }),
+    });
+}
+
+/// Spawns a pickup holding the stack lying on the ground at the position.
+pub fn spawn_pickup(
+    commands: &mut Commands,
+    assets: &PickupAssets,
+    registry: &ItemRegistry,
+    stack: ItemStack,
+    position: Vec3,
+) -> Entity {
+    return commands
+        .spawn((
+            PbrBundle {
+                mesh: assets.mesh.clone(),
+                material: assets.material.clone(),
+                transform: Transform::from_translation(
+                    position + Vec3::new(0.0, PICKUP_SIZE / 2.0, 0.0),
+                ),
+                ..default()
+            },
+            Interactable::new(
+                InteractionKind::PickUp,
+                pickup_prompt(registry, &stack),
+                0.5,
+            ),
+            Pickup { stack: stack },
+        ))
+        .id();
+}
+
+/// Initial system to add the doors, seats and pickups of the world that players
+/// interact with, and the mesh and material of the pickups dropped later on.
+pub fn spawn_interactables(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    registry: Res<ItemRegistry>,
+) {
+    let file = load_interactables();
+    let door_mesh = meshes.add(shape::Box::new(DOOR_SIZE.x, DOOR_SIZE.y, DOOR_SIZE.z).into());
+    let door_material = materials.add(Color::hex("#7a4a2a").unwrap().into());
+    let door_center = Vec3::new(DOOR_SIZE.x / 2.0, DOOR_SIZE.y / 2.0, 0.0);
+    for placement in file.doors {
+        let closed_angle = placement.rotation.to_radians();
+        commands
+            .spawn((
+                SpatialBundle::from_transform(
+                    Transform::from_translation(placement.position)
+                        .with_rotation(Quat::from_rotation_y(closed_angle)),
+                ),
+                StaticCollider {
+                    shape: ColliderShape::Cuboid {
+                        half_extents: DOOR_SIZE / 2.0,
+                    },
+                    offset: door_center,
+                },
+                Door {
+                    open: false,
+                    closed_angle: closed_angle,
+                },
+                Interactable::new(InteractionKind::Door, "Open door", DOOR_SIZE.y + 0.3),
+            ))
+            .with_children(|parent| {
+                parent.spawn(PbrBundle {
+                    mesh: door_mesh.clone(),
+                    material: door_material.clone(),
+                    transform: Transform::from_translation(door_center),
+                    ..default()
+                });
+            });
+    }
+    let bench_mesh = meshes.add(shape::Box::new(BENCH_SIZE.x, BENCH_SIZE.y, BENCH_SIZE.z).into());
+    let bench_material = materials.add(Color::hex("#c8a165").unwrap().into());
+    for placement in file.seats {
+        commands.spawn((
+            PbrBundle {
+                mesh: bench_mesh.clone(),
+                material: bench_material.clone(),
+                transform: Transform::from_translation(
+                    placement.position + Vec3::new(0.0, BENCH_SIZE.y / 2.0, 0.0),
+                )
+                .with_rotation(Quat::from_rotation_y(placement.rotation.to_radians())),
+                ..default()
+            },
+            StaticCollider {
+                shape: ColliderShape::Cuboid {
+                    half_extents: BENCH_SIZE / 2.0,
+                },
+                offset: Vec3::ZERO,
+            },
+            Seat { occupant: None },
+            Interactable::new(InteractionKind::Sit, "Sit", 0.6),
+        ));
+    }
+    let pickup_assets = PickupAssets {
+        mesh: meshes.add(shape::Cube::new(PICKUP_SIZE).into()),
+        material: materials.add(Color::hex("#d9b45a").unwrap().into()),
+    };
+    for placement in file.pickups {
+        if registry.find(&placement.item).is_none() {
+            warn!("There is no item {} to place", placement.item);
+            continue;
+        }
+        let stack = ItemStack {
+            item: placement.item,
+            count: placement.count,
+        };
+        spawn_pickup(
+            &mut commands,
+            &pickup_assets,
+            &registry,
+            stack,
+            placement.position,
+        );
+    }
+    commands.insert_resource(pickup_assets);
+}
@@ -0,0 +1,6 @@
+/// items module for the crate. The registry of every item, the inventories that hold
+/// them for players and storage chests, and the hotbar and menu players use them with.
+pub(crate) mod components;
+pub(crate) mod control;
+pub(crate) mod inventory;
+pub(crate) mod start_up;
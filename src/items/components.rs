@@ -0,0 +1,136 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::{Component, Entity, Resource};
+use serde::{Deserialize, Serialize};
+
+use crate::ui::menu_navigation::MenuNavigation;
+
+use super::inventory::{Inventory, ItemStack};
+
+/// The number of slots at the start of a player inventory that make up their hotbar.
+pub const HOTBAR_SIZE: usize = 8;
+
+/// The kinds of items.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ItemCategory {
+    Tool,
+    Seed,
+    Crop,
+    Material,
+    Food,
+}
+
+/// The data definition of an item.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ItemDefinition {
+    pub id: String,
+    pub name: String,
+    pub category: ItemCategory,
+    /// The most items of this kind that fit in one inventory slot.
+    pub max_stack: u32,
+    /// The asset path of the image shown for the item, if it has one.
+    #[serde(default)]
+    pub icon: Option<String>,
+    /// Extra values for the systems that use the item, like the crop a seed grows.
+    #[serde(default)]
+    pub metadata: BTreeMap<String, String>,
+}
+
+/// A Bevy Engine resource with the definitions of every item and the items a new player
+/// starts with, loaded from a data file.
+#[derive(Resource, Default, Serialize, Deserialize)]
+pub struct ItemRegistry {
+    pub items: Vec<ItemDefinition>,
+    #[serde(default)]
+    pub starting_items: Vec<ItemStack>,
+}
+
+impl ItemRegistry {
+    /// Returns the definition of the item with the id.
+    pub fn find(&self, id: &str) -> Option<&ItemDefinition> {
+        return self.items.iter().find(|item| item.id == id);
+    }
+
+    /// Returns the max stack of the item. Unknown items do not stack.
+    pub fn max_stack(&self, id: &str) -> u32 {
+        return self.find(id).map_or(1, |item| item.max_stack.max(1));
+    }
+
+    /// Returns the name of the item, or its id when it is unknown.
+    pub fn name<'a>(&'a self, id: &'a str) -> &'a str {
+        return self.find(id).map_or(id, |item| item.name.as_str());
+    }
+
+    /// Returns the id of the crop the item grows when it is a seed.
+    pub fn crop_of_seed(&self, id: &str) -> Option<&str> {
+        return self
+            .find(id)
+            .filter(|item| item.category == ItemCategory::Seed)
+            .and_then(|item| item.metadata.get("crop"))
+            .map(|crop| crop.as_str());
+    }
+
+    /// Returns the id of the seed item that grows the crop.
+    pub fn seed_of_crop(&self, crop: &str) -> Option<&str> {
+        return self
+            .items
+            .iter()
+            .find(|item| {
+                item.category == ItemCategory::Seed
+                    && item
+                        .metadata
+                        .get("crop")
+                        .is_some_and(|seed_crop| seed_crop == crop)
+            })
+            .map(|item| item.id.as_str());
+    }
+}
+
+/// A component with the slot of the hotbar a player has selected. The hotbar is the
+/// first slots of the inventory of the player, and the selected item is the tool or
+/// seed they use.
+#[derive(Component, Clone, Copy, Default)]
+pub struct Hotbar {
+    pub selected: usize,
+}
+
+impl Hotbar {
+    /// Returns the stack in the selected hotbar slot of the inventory.
+    pub fn selected_stack<'a>(&self, inventory: &'a Inventory) -> Option<&'a ItemStack> {
+        return inventory
+            .slots
+            .get(self.selected)
+            .and_then(|slot| slot.as_ref());
+    }
+}
+
+/// A component for a storage chest that every player can put items in and take items
+/// out of. The id is what the chest is saved by.
+#[derive(Component)]
+pub struct Chest {
+    pub id: String,
+}
+
+/// A component for a slot of the hotbar display of a player in their overlay.
+#[derive(Component)]
+pub struct HotbarSlotDisplay {
+    pub player: Entity,
+    pub slot: usize,
+}
+
+/// A component on a player whose hotbar display was added to their overlay.
+#[derive(Component)]
+pub struct HasHotbarDisplay;
+
+/// A component on a player that has their inventory menu open. The slots of the menu
+/// are the slots of the player followed by the slots of the open chest, if any.
+#[derive(Component)]
+pub struct InventoryMenu {
+    pub chest: Option<Entity>,
+    /// The slot of the menu the cursor is on.
+    pub cursor: usize,
+    /// The slot of the menu picked up to be moved to the cursor.
+    pub held: Option<usize>,
+    pub navigation: MenuNavigation,
+    pub prompt: Entity,
+}
@@ -0,0 +1,421 @@
+use std::collections::HashMap;
+
+use bevy::prelude::{
+    default, warn, Added, AlignItems, AssetServer, BackgroundColor, BuildChildren, Children, Color,
+    Commands, DespawnRecursiveExt, DetectChanges, Entity, EventReader, FlexDirection, Handle,
+    ImageBundle, JustifyContent, NodeBundle, PositionType, Query, Ref, Res, Size, Style, Text,
+    TextBundle, TextStyle, UiImage, UiRect, Val, With, Without,
+};
+
+use crate::characters::animation::components::CharacterActivity;
+use crate::characters::components::MovementIntent;
+use crate::characters::player::entity::components::PlayerCharacter;
+use crate::characters::player::input::components::{
+    ActionState, ButtonAction, ControlsMenu, StickAction,
+};
+use crate::characters::player::profile::components::{
+    ActiveProfile, ChoosingProfile, CustomizingAppearance,
+};
+use crate::characters::profession::components::ChoosingProfession;
+use crate::crafting::components::CraftingMenu;
+use crate::interaction::components::{InteractionEvent, InteractionKind};
+use crate::save::control::SavedPlayers;
+use crate::ui::menu_navigation::{wrap_index, MenuNavigation};
+use crate::ui::player_overlay::{spawn_in_player_overlay, PlayerOverlayLink};
+use crate::ui::start_up::UiFont;
+
+use super::components::{
+    Chest, HasHotbarDisplay, Hotbar, HotbarSlotDisplay, InventoryMenu, ItemCategory, ItemRegistry,
+    HOTBAR_SIZE,
+};
+use super::inventory::{move_between_inventories, Inventory, ItemStack};
+
+/// The size of a hotbar slot on screen, in pixels.
+const HOTBAR_SLOT_SIZE: f32 = 52.0;
+
+/// A system that gives a player the starting items when they pick a profile that has no
+/// saved state. Players with a saved state get their saved inventory instead.
+pub fn give_starting_items(
+    registry: Res<ItemRegistry>,
+    saved_players: Res<SavedPlayers>,
+    mut players: Query<(&ActiveProfile, &mut Inventory), Added<ActiveProfile>>,
+) {
+    for (active_profile, mut inventory) in players.iter_mut() {
+        if saved_players.0.contains_key(&active_profile.id) {
+            continue;
+        }
+        for stack in registry.starting_items.iter() {
+            inventory.add(&registry, &stack.item, stack.count);
+        }
+    }
+}
+
+/// A system that moves the selected hotbar slot of every player to the next or previous
+/// slot, wrapping around the ends of the hotbar.
+pub fn select_hotbar_slots(
+    mut players: Query<(&ActionState, &mut Hotbar), Without<InventoryMenu>>,
+) {
+    for (action_state, mut hotbar) in players.iter_mut() {
+        let step = action_state.just_pressed(ButtonAction::NextHotbarSlot) as i32
+            - action_state.just_pressed(ButtonAction::PreviousHotbarSlot) as i32;
+        if step != 0 {
+            hotbar.selected = wrap_index(hotbar.selected, step, HOTBAR_SIZE);
+        }
+    }
+}
+
+/// A system that makes every player carry the harvest in their arms while a crop is
+/// selected on their hotbar. Sitting and using a tool take priority over carrying.
+pub fn carry_selected_crops(
+    registry: Res<ItemRegistry>,
+    mut players: Query<(&Inventory, &Hotbar, &mut CharacterActivity)>,
+) {
+    for (inventory, hotbar, mut activity) in players.iter_mut() {
+        if !matches!(
+            *activity,
+            CharacterActivity::None | CharacterActivity::Carrying
+        ) {
+            continue;
+        }
+        let carrying = hotbar
+            .selected_stack(inventory)
+            .and_then(|stack| registry.find(&stack.item))
+            .is_some_and(|item| item.category == ItemCategory::Crop);
+        let new_activity = if carrying {
+            CharacterActivity::Carrying
+        } else {
+            CharacterActivity::None
+        };
+        if *activity != new_activity {
+            *activity = new_activity;
+        }
+    }
+}
+
+/// A system that adds a hotbar display along the bottom of the overlay of every player
+/// that does not have one.
+pub fn spawn_hotbar_displays(
+    mut commands: Commands,
+    ui_font: Res<UiFont>,
+    players: Query<(Entity, &PlayerOverlayLink), (With<Hotbar>, Without<HasHotbarDisplay>)>,
+) {
+    for (player_entity, overlay_link) in players.iter() {
+        let display = spawn_in_player_overlay(
+            &mut commands,
+            overlay_link,
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        bottom: Val::Px(12.0),
+                        ..default()
+                    },
+                    flex_direction: FlexDirection::Row,
+                    ..default()
+                },
+                ..default()
+            },
+        );
+        for slot in 0..HOTBAR_SIZE {
+            let slot_text = commands
+                .spawn(TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: ui_font.0.clone(),
+                        font_size: 14.0,
+                        color: Color::WHITE,
+                    },
+                ))
+                .id();
+            let slot_node = commands
+                .spawn((
+                    ImageBundle {
+                        style: Style {
+                            size: Size::new(Val::Px(HOTBAR_SLOT_SIZE), Val::Px(HOTBAR_SLOT_SIZE)),
+                            margin: UiRect::all(Val::Px(2.0)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::FlexEnd,
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    HotbarSlotDisplay {
+                        player: player_entity,
+                        slot: slot,
+                    },
+                ))
+                .add_child(slot_text)
+                .id();
+            commands.entity(display).add_child(slot_node);
+        }
+        commands.entity(player_entity).insert(HasHotbarDisplay);
+    }
+}
+
+/// Returns the short label of a stack for a hotbar slot or menu cell.
+fn stack_label(registry: &ItemRegistry, stack: &ItemStack) -> String {
+    let name: String = registry.name(&stack.item).chars().take(6).collect();
+    if stack.count > 1 {
+        return format!("{} {}", name, stack.count);
+    }
+    return name;
+}
+
+/// A system that shows the hotbar slots of every player on their hotbar display when
+/// the inventory or selected slot changes. Items with an icon show it, and the selected
+/// slot is lit up.
+pub fn update_hotbar_displays(
+    asset_server: Res<AssetServer>,
+    registry: Res<ItemRegistry>,
+    players: Query<(Ref<Inventory>, Ref<Hotbar>)>,
+    mut slot_displays: Query<(
+        Ref<HotbarSlotDisplay>,
+        &Children,
+        &mut BackgroundColor,
+        &mut UiImage,
+    )>,
+    mut texts: Query<&mut Text>,
+) {
+    for (slot_display, children, mut background_color, mut image) in slot_displays.iter_mut() {
+        let Ok((inventory, hotbar)) = players.get(slot_display.player) else {
+            continue;
+        };
+        if !slot_display.is_added() && !inventory.is_changed() && !hotbar.is_changed() {
+            continue;
+        }
+        let stack = inventory
+            .slots
+            .get(slot_display.slot)
+            .and_then(|slot| slot.as_ref());
+        let icon = stack
+            .and_then(|stack| registry.find(&stack.item))
+            .and_then(|item| item.icon.as_ref());
+        let selected = hotbar.selected == slot_display.slot;
+        *background_color = match (icon.is_some(), selected) {
+            (true, true) => Color::WHITE.into(),
+            (true, false) => Color::rgb(0.7, 0.7, 0.7).into(),
+            (false, true) => Color::rgba(0.9, 0.8, 0.4, 0.8).into(),
+            (false, false) => Color::rgba(0.1, 0.1, 0.1, 0.6).into(),
+        };
+        image.texture = icon.map_or(Handle::default(), |icon| asset_server.load(icon.as_str()));
+        for child in children.iter() {
+            if let Ok(mut text) = texts.get_mut(*child) {
+                text.sections[0].value =
+                    stack.map_or(String::new(), |stack| stack_label(&registry, stack));
+            }
+        }
+    }
+}
+
+/// Returns the text of the inventory menu, with the slots of the player and the open
+/// chest in rows of the hotbar size.
+fn inventory_menu_text(
+    registry: &ItemRegistry,
+    menu: &InventoryMenu,
+    inventory: &Inventory,
+    chest_inventory: Option<&Inventory>,
+) -> String {
+    let slots: Vec<&Option<ItemStack>> = inventory
+        .slots
+        .iter()
+        .chain(
+            chest_inventory
+                .into_iter()
+                .flat_map(|chest| chest.slots.iter()),
+        )
+        .collect();
+    let mut lines = vec!["Inventory".to_string()];
+    for (row, row_slots) in slots.chunks(HOTBAR_SIZE).enumerate() {
+        if row * HOTBAR_SIZE == inventory.slots.len() {
+            lines.push("Chest".to_string());
+        }
+        let cells: Vec<String> = row_slots
+            .iter()
+            .enumerate()
+            .map(|(column, slot)| {
+                let index = row * HOTBAR_SIZE + column;
+                let marker = if index == menu.cursor {
+                    ">"
+                } else if menu.held == Some(index) {
+                    "*"
+                } else {
+                    " "
+                };
+                let label = slot
+                    .as_ref()
+                    .map_or("-".to_string(), |stack| stack_label(registry, stack));
+                format!("{}{}", marker, label)
+            })
+            .collect();
+        lines.push(cells.join("  "));
+    }
+    let cursor_text = slots
+        .get(menu.cursor)
+        .and_then(|slot| slot.as_ref())
+        .map_or("Empty".to_string(), |stack| {
+            format!("{} x{}", registry.name(&stack.item), stack.count)
+        });
+    lines.push(cursor_text);
+    lines.push("Interact to move, use tool to split".to_string());
+    return lines.join("\n");
+}
+
+/// A system that opens the inventory menu of every player that presses the open
+/// inventory action while they are not in another menu. A player who interacts with a
+/// chest opens the menu with the chest beside their inventory. The player stops moving
+/// while the menu is open.
+pub fn open_inventory_menus(
+    mut commands: Commands,
+    ui_font: Res<UiFont>,
+    registry: Res<ItemRegistry>,
+    mut interaction_events: EventReader<InteractionEvent>,
+    mut players: Query<
+        (
+            Entity,
+            &ActionState,
+            &PlayerOverlayLink,
+            &Inventory,
+            &mut MovementIntent,
+        ),
+        (
+            With<PlayerCharacter>,
+            With<ActiveProfile>,
+            Without<InventoryMenu>,
+            Without<CraftingMenu>,
+            Without<ControlsMenu>,
+            Without<ChoosingProfession>,
+            Without<ChoosingProfile>,
+            Without<CustomizingAppearance>,
+        ),
+    >,
+    chests: Query<&Inventory, With<Chest>>,
+) {
+    let opened_chests: HashMap<Entity, Entity> = interaction_events
+        .iter()
+        .filter(|event| event.kind == InteractionKind::OpenChest)
+        .map(|event| (event.player, event.target))
+        .collect();
+    for (player_entity, action_state, overlay_link, inventory, mut movement_intent) in
+        players.iter_mut()
+    {
+        let chest = match opened_chests.get(&player_entity) {
+            Some(chest_entity) => match chests.get(*chest_entity) {
+                Ok(chest_inventory) => Some((*chest_entity, chest_inventory)),
+                Err(_) => continue,
+            },
+            None if action_state.just_pressed(ButtonAction::OpenInventory) => None,
+            None => continue,
+        };
+        let mut menu = InventoryMenu {
+            chest: chest.map(|(chest_entity, _)| chest_entity),
+            cursor: 0,
+            held: None,
+            navigation: MenuNavigation::default(),
+            prompt: Entity::PLACEHOLDER,
+        };
+        menu.prompt = spawn_in_player_overlay(
+            &mut commands,
+            overlay_link,
+            TextBundle::from_section(
+                inventory_menu_text(
+                    &registry,
+                    &menu,
+                    inventory,
+                    chest.map(|(_, chest_inventory)| chest_inventory),
+                ),
+                TextStyle {
+                    font: ui_font.0.clone(),
+                    font_size: 20.0,
+                    color: Color::WHITE,
+                },
+            ),
+        );
+        *movement_intent = MovementIntent::default();
+        commands.entity(player_entity).insert(menu);
+    }
+}
+
+/// A system that lets the players with the inventory menu open move the cursor over
+/// the slots, pick up a stack and put it down on another slot, in their inventory or
+/// the open chest, and split stacks in half. The open inventory action closes the menu.
+pub fn use_inventory_menus(
+    mut commands: Commands,
+    registry: Res<ItemRegistry>,
+    mut players: Query<(Entity, &ActionState, &mut InventoryMenu, &mut Inventory)>,
+    mut chests: Query<&mut Inventory, (With<Chest>, Without<InventoryMenu>)>,
+    mut prompts: Query<&mut Text>,
+) {
+    for (player_entity, action_state, mut menu, mut inventory) in players.iter_mut() {
+        if action_state.just_pressed(ButtonAction::OpenInventory) {
+            commands.entity(menu.prompt).despawn_recursive();
+            commands.entity(player_entity).remove::<InventoryMenu>();
+            continue;
+        }
+        let mut chest_inventory = menu.chest.and_then(|chest| chests.get_mut(chest).ok());
+        let player_slots = inventory.slots.len();
+        let slot_count = player_slots
+            + chest_inventory
+                .as_ref()
+                .map_or(0, |chest| chest.slots.len());
+        if let Some(step) = menu.navigation.step(action_state.stick(StickAction::Move)) {
+            let step = step.x - step.y * HOTBAR_SIZE as i32;
+            menu.cursor = wrap_index(menu.cursor, step, slot_count);
+        }
+        let cursor = menu.cursor;
+        if action_state.just_pressed(ButtonAction::Interact) {
+            match menu.held {
+                None => {
+                    let occupied = if cursor < player_slots {
+                        inventory.slots[cursor].is_some()
+                    } else {
+                        chest_inventory
+                            .as_ref()
+                            .is_some_and(|chest| chest.slots[cursor - player_slots].is_some())
+                    };
+                    if occupied {
+                        menu.held = Some(cursor);
+                    }
+                }
+                Some(held) => {
+                    match (
+                        held < player_slots,
+                        cursor < player_slots,
+                        &mut chest_inventory,
+                    ) {
+                        (true, true, _) => inventory.move_stack(&registry, held, cursor),
+                        (false, false, Some(chest)) => {
+                            chest.move_stack(&registry, held - player_slots, cursor - player_slots)
+                        }
+                        (true, false, Some(chest)) => move_between_inventories(
+                            &registry,
+                            &mut inventory,
+                            held,
+                            chest,
+                            cursor - player_slots,
+                        ),
+                        (false, true, Some(chest)) => move_between_inventories(
+                            &registry,
+                            chest,
+                            held - player_slots,
+                            &mut inventory,
+                            cursor,
+                        ),
+                        _ => warn!("The chest of the inventory menu is gone"),
+                    }
+                    menu.held = None;
+                }
+            }
+        }
+        if action_state.just_pressed(ButtonAction::UseTool) && menu.held.is_none() {
+            if cursor < player_slots {
+                inventory.split(cursor);
+            } else if let Some(chest) = &mut chest_inventory {
+                chest.split(cursor - player_slots);
+            }
+        }
+        if let Ok(mut prompt) = prompts.get_mut(menu.prompt) {
+            prompt.sections[0].value =
+                inventory_menu_text(&registry, &menu, &inventory, chest_inventory.as_deref());
+        }
+    }
+}
@@ -0,0 +1,297 @@
+use bevy::prelude::Component;
+use serde::{Deserialize, Serialize};
+
+use super::components::ItemRegistry;
+
+/// A number of the same item in an inventory slot.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ItemStack {
+    /// The id of the item definition.
+    pub item: String,
+    pub count: u32,
+}
+
+/// A component with the slots of items an entity holds, like a player or a storage
+/// chest. Every slot holds one stack of up to the max stack of its item.
+#[derive(Component, Clone, Default, Serialize, Deserialize)]
+pub struct Inventory {
+    pub slots: Vec<Option<ItemStack>>,
+}
+
+impl Inventory {
+    /// Creates an inventory with the number of empty slots.
+    pub fn new(size: usize) -> Self {
+        return Inventory {
+            slots: vec![None; size],
+        };
+    }
+
+    /// Returns how many of the item the inventory holds in all its slots.
+    pub fn count_of(&self, item: &str) -> u32 {
+        return self
+            .slots
+            .iter()
+            .flatten()
+            .filter(|stack| stack.item == item)
+            .map(|stack| stack.count)
+            .sum();
+    }
+
+    /// Adds the items, topping up the stacks of the item before filling empty slots.
+    /// Returns how many did not fit.
+    pub fn add(&mut self, registry: &ItemRegistry, item: &str, count: u32) -> u32 {
+        let max_stack = registry.max_stack(item);
+        let mut left = count;
+        for stack in self.slots.iter_mut().flatten() {
+            if left == 0 {
+                break;
+            }
+            if stack.item == item && stack.count < max_stack {
+                let moved = left.min(max_stack - stack.count);
+                stack.count += moved;
+                left -= moved;
+            }
+        }
+        for slot in self.slots.iter_mut().filter(|slot| slot.is_none()) {
+            if left == 0 {
+                break;
+            }
+            let moved = left.min(max_stack);
+            *slot = Some(ItemStack {
+                item: item.to_string(),
+                count: moved,
+            });
+            left -= moved;
+        }
+        return left;
+    }
+
+    /// Removes the number of the item from the inventory, taking from the last stacks
+    /// first. Nothing is removed when the inventory holds fewer. Returns true when the
+    /// items were removed.
+    pub fn remove(&mut self, item: &str, count: u32) -> bool {
+        if self.count_of(item) < count {
+            return false;
+        }
+        let mut left = count;
+        for slot in self.slots.iter_mut().rev() {
+            let Some(stack) = slot else {
+                continue;
+            };
+            if left == 0 {
+                break;
+            }
+            if stack.item != item {
+                continue;
+            }
+            let taken = left.min(stack.count);
+            stack.count -= taken;
+            left -= taken;
+            if stack.count == 0 {
+                *slot = None;
+            }
+        }
+        return true;
+    }
+
+    /// Splits half of the stack in the slot off into the first empty slot. Returns true
+    /// when the stack was split.
+    pub fn split(&mut self, slot: usize) -> bool {
+        let Some(Some(stack)) = self.slots.get(slot) else {
+            return false;
+        };
+        if stack.count < 2 {
+            return false;
+        }
+        let Some(empty_slot) = self.slots.iter().position(|slot| slot.is_none()) else {
+            return false;
+        };
+        let split_count = stack.count / 2;
+        let item = stack.item.clone();
+        if let Some(stack) = &mut self.slots[slot] {
+            stack.count -= split_count;
+        }
+        self.slots[empty_slot] = Some(ItemStack {
+            item: item,
+            count: split_count,
+        });
+        return true;
+    }
+
+    /// Moves the stack in a slot onto another slot of the inventory. Stacks of the same
+    /// item are merged up to the max stack, and stacks of different items swap slots.
+    pub fn move_stack(&mut self, registry: &ItemRegistry, from: usize, to: usize) {
+        if from == to || from >= self.slots.len() || to >= self.slots.len() {
+            return;
+        }
+        let mut from_slot = self.slots[from].take();
+        let mut to_slot = self.slots[to].take();
+        merge_or_swap(registry, &mut from_slot, &mut to_slot);
+        self.slots[from] = from_slot;
+        self.slots[to] = to_slot;
+    }
+}
+
+/// Moves the stack in a slot of an inventory onto a slot of another inventory, like
+/// from a player to a storage chest. Stacks are merged or swapped as when moving within
+/// an inventory.
+pub fn move_between_inventories(
+    registry: &ItemRegistry,
+    from: &mut Inventory,
+    from_slot: usize,
+    to: &mut Inventory,
+    to_slot: usize,
+) {
+    let (Some(from_slot), Some(to_slot)) =
+        (from.slots.get_mut(from_slot), to.slots.get_mut(to_slot))
+    else {
+        return;
+    };
+    merge_or_swap(registry, from_slot, to_slot);
+}
+
+/// Moves the stack of the from slot onto the to slot. A stack of the same item is
+/// topped up to the max stack with the rest left behind, and any other stack swaps.
+fn merge_or_swap(
+    registry: &ItemRegistry,
+    from_slot: &mut Option<ItemStack>,
+    to_slot: &mut Option<ItemStack>,
+) {
+    match (from_slot.as_mut(), to_slot.as_mut()) {
+        (Some(from_stack), Some(to_stack)) if from_stack.item == to_stack.item => {
+            let max_stack = registry.max_stack(&to_stack.item);
+            let moved = from_stack
+                .count
+                .min(max_stack.saturating_sub(to_stack.count));
+            to_stack.count += moved;
+            from_stack.count -= moved;
+            if from_stack.count == 0 {
+                *from_slot = None;
+            }
+        }
+        _ => std::mem::swap(from_slot, to_slot),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::super::components::{ItemCategory, ItemDefinition, ItemRegistry};
+    use super::{move_between_inventories, Inventory, ItemStack};
+
+    fn item(id: &str, category: ItemCategory, max_stack: u32) -> ItemDefinition {
+        return ItemDefinition {
+            id: id.to_string(),
+            name: id.to_string(),
+            category: category,
+            max_stack: max_stack,
+            icon: None,
+            metadata: BTreeMap::new(),
+        };
+    }
+
+    /// A registry with seeds that stack up to 10 and a hoe that does not stack.
+    fn registry() -> ItemRegistry {
+        return ItemRegistry {
+            items: vec![
+                item("seeds", ItemCategory::Seed, 10),
+                item("hoe", ItemCategory::Tool, 1),
+            ],
+            starting_items: Vec::new(),
+        };
+    }
+
+    fn stack(item: &str, count: u32) -> Option<ItemStack> {
+        return Some(ItemStack {
+            item: item.to_string(),
+            count: count,
+        });
+    }
+
+    #[test]
+    fn adds_onto_stacks_before_empty_slots() {
+        let registry = registry();
+        let mut inventory = Inventory::new(3);
+        inventory.slots[1] = stack("seeds", 8);
+        assert_eq!(inventory.add(&registry, "seeds", 15), 0);
+        assert_eq!(
+            inventory.slots,
+            vec![stack("seeds", 10), stack("seeds", 10), stack("seeds", 3)]
+        );
+        assert_eq!(inventory.count_of("seeds"), 23);
+    }
+
+    #[test]
+    fn returns_what_does_not_fit() {
+        let registry = registry();
+        let mut inventory = Inventory::new(2);
+        assert_eq!(inventory.add(&registry, "hoe", 3), 1);
+        assert_eq!(inventory.slots, vec![stack("hoe", 1), stack("hoe", 1)]);
+    }
+
+    #[test]
+    fn removes_from_the_last_stacks_first() {
+        let mut inventory = Inventory {
+            slots: vec![stack("seeds", 4), stack("hoe", 1), stack("seeds", 3)],
+        };
+        assert!(inventory.remove("seeds", 5));
+        assert_eq!(
+            inventory.slots,
+            vec![stack("seeds", 2), stack("hoe", 1), None]
+        );
+        assert!(!inventory.remove("seeds", 3));
+        assert_eq!(inventory.count_of("seeds"), 2);
+    }
+
+    #[test]
+    fn splits_half_a_stack_into_the_first_empty_slot() {
+        let mut inventory = Inventory {
+            slots: vec![stack("hoe", 1), None, stack("seeds", 7)],
+        };
+        assert!(inventory.split(2));
+        assert_eq!(
+            inventory.slots,
+            vec![stack("hoe", 1), stack("seeds", 3), stack("seeds", 4)]
+        );
+        assert!(!inventory.split(0));
+        assert!(!inventory.split(2));
+        assert!(!inventory.split(5));
+    }
+
+    #[test]
+    fn merges_stacks_of_the_same_item_and_swaps_others() {
+        let registry = registry();
+        let mut inventory = Inventory {
+            slots: vec![stack("seeds", 6), stack("seeds", 7), stack("hoe", 1), None],
+        };
+        inventory.move_stack(&registry, 0, 1);
+        assert_eq!(inventory.slots[0], stack("seeds", 3));
+        assert_eq!(inventory.slots[1], stack("seeds", 10));
+        inventory.move_stack(&registry, 2, 0);
+        assert_eq!(inventory.slots[0], stack("hoe", 1));
+        assert_eq!(inventory.slots[2], stack("seeds", 3));
+        inventory.move_stack(&registry, 2, 3);
+        assert_eq!(inventory.slots[2], None);
+        assert_eq!(inventory.slots[3], stack("seeds", 3));
+        inventory.move_stack(&registry, 3, 9);
+        assert_eq!(inventory.slots[3], stack("seeds", 3));
+    }
+
+    #[test]
+    fn moves_stacks_between_inventories() {
+        let registry = registry();
+        let mut player = Inventory {
+            slots: vec![stack("seeds", 5)],
+        };
+        let mut chest = Inventory {
+            slots: vec![stack("seeds", 9), None],
+        };
+        move_between_inventories(&registry, &mut player, 0, &mut chest, 0);
+        assert_eq!(player.slots[0], stack("seeds", 4));
+        assert_eq!(chest.slots[0], stack("seeds", 10));
+        move_between_inventories(&registry, &mut player, 0, &mut chest, 1);
+        assert_eq!(player.slots[0], None);
+        assert_eq!(chest.slots[1], stack("seeds", 4));
+    }
+}
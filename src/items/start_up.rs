@@ -0,0 +1,80 @@
+use std::path::Path;
+
+use bevy::prelude::{
+    default, shape, Assets, Color, Commands, Mesh, PbrBundle, Res, ResMut, StandardMaterial,
+    Transform, Vec3,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::collision::components::{ColliderShape, StaticCollider};
+use crate::interaction::components::{Interactable, InteractionKind};
+use crate::storage::load_ron_or_default;
+
+use super::components::{Chest, ItemRegistry};
+use super::inventory::{Inventory, ItemStack};
+
+/// The file the item definitions are loaded from.
+const ITEM_REGISTRY_PATH: &str = "assets/items/items.ron";
+/// The file the storage chests of the world are loaded from.
+const CHEST_PLACEMENTS_PATH: &str = "assets/world/chests.ron";
+/// The number of slots of a storage chest.
+pub const CHEST_SIZE: usize = 24;
+/// The size of the box of a storage chest.
+const CHEST_BOX_SIZE: Vec3 = Vec3::new(1.0, 0.7, 0.6);
+
+/// Where a storage chest stands in the world and what it holds in a new game.
+#[derive(Serialize, Deserialize)]
+struct ChestPlacement {
+    id: String,
+    position: Vec3,
+    #[serde(default)]
+    items: Vec<ItemStack>,
+}
+
+/// Loads the item definitions from their data file.
+pub fn load_item_registry() -> ItemRegistry {
+    return load_ron_or_default(Path::new(ITEM_REGISTRY_PATH), "the items");
+}
+
+/// Loads where the storage chests stand from their data file.
+fn load_chest_placements() -> Vec<ChestPlacement> {
+    return load_ron_or_default(Path::new(CHEST_PLACEMENTS_PATH), "the chests");
+}
+
+/// Initial system to add the storage chests of the world with the items they hold in a
+/// new game. A loaded game replaces what they hold.
+pub fn spawn_chests(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    registry: Res<ItemRegistry>,
+) {
+    let mesh =
+        meshes.add(shape::Box::new(CHEST_BOX_SIZE.x, CHEST_BOX_SIZE.y, CHEST_BOX_SIZE.z).into());
+    let material = materials.add(Color::rgb(0.5, 0.32, 0.15).into());
+    for placement in load_chest_placements() {
+        let mut inventory = Inventory::new(CHEST_SIZE);
+        for stack in placement.items.iter() {
+            inventory.add(&registry, &stack.item, stack.count);
+        }
+        commands.spawn((
+            PbrBundle {
+                mesh: mesh.clone(),
+                material: material.clone(),
+                transform: Transform::from_translation(
+                    placement.position + Vec3::new(0.0, CHEST_BOX_SIZE.y / 2.0, 0.0),
+                ),
+                ..default()
+            },
+            StaticCollider {
+                shape: ColliderShape::Cuboid {
+                    half_extents: CHEST_BOX_SIZE / 2.0,
+                },
+                offset: Vec3::ZERO,
+            },
+            Chest { id: placement.id },
+            inventory,
+            Interactable::new(InteractionKind::OpenChest, "Open chest", 0.9),
+        ));
+    }
+}
@@ -1,15 +1,193 @@
-use bevy::prelude::{default, Commands, PointLight, PointLightBundle, Transform};
-
-/// Initial system to add light to see the objects rendered.
-pub fn add_light(mut commands: Commands) {
-    commands.spawn(PointLightBundle {
-        point_light: PointLight {
-            intensity: 600000.,
-            range: 100.,
-            shadows_enabled: true,
+use std::f32::consts::PI;
+
+use bevy::pbr::CascadeShadowConfigBuilder;
+use bevy::prelude::{
+    default, AmbientLight, ClearColor, Color, Commands, Component, DetectChanges, DirectionalLight,
+    DirectionalLightBundle, Query, Res, ResMut, Transform, Vec3, With, Without,
+};
+
+use super::game_time::{GameClock, HOURS_PER_DAY};
+use super::weather::components::Weather;
+
+/// The illuminance of the sun straight overhead, in lux.
+const SUN_NOON_ILLUMINANCE: f32 = 60000.0;
+/// The illuminance of the moon straight overhead, in lux.
+const MOON_ZENITH_ILLUMINANCE: f32 = 4000.0;
+/// The color temperatures of the sun at the horizon and overhead, in kelvin.
+const SUN_HORIZON_TEMPERATURE: f32 = 2000.0;
+const SUN_ZENITH_TEMPERATURE: f32 = 5800.0;
+/// The color temperature of the moon, bluer than daylight.
+const MOON_TEMPERATURE: f32 = 9000.0;
+/// How far toward the south the sun and moon pass, in radians from straight overhead.
+const SKY_PATH_TILT: f32 = 0.35;
+/// How far the sky lights are placed from the center of the world.
+const SKY_LIGHT_DISTANCE: f32 = 50.0;
+/// The brightness of the ambient light in the day and at night.
+const DAY_AMBIENT_BRIGHTNESS: f32 = 0.35;
+const NIGHT_AMBIENT_BRIGHTNESS: f32 = 0.06;
+
+/// A component for the directional light of the sun.
+#[derive(Component)]
+pub struct Sun;
+
+/// A component for the directional light of the moon.
+#[derive(Component)]
+pub struct Moon;
+
+/// Returns the color of light with the color temperature in kelvin, using the curve fit
+/// of the black body colors by Tanner Helland.
+fn color_from_temperature(kelvin: f32) -> Color {
+    let temperature = kelvin.clamp(1000.0, 40000.0) / 100.0;
+    let red = if temperature <= 66.0 {
+        255.0
+    } else {
+        329.698_73 * (temperature - 60.0).powf(-0.133_204_76)
+    };
+    let green = if temperature <= 66.0 {
+        99.470_8 * temperature.ln() - 161.119_57
+    } else {
+        288.122_16 * (temperature - 60.0).powf(-0.075_514_85)
+    };
+    let blue = if temperature >= 66.0 {
+        255.0
+    } else if temperature <= 19.0 {
+        0.0
+    } else {
+        138.517_73 * (temperature - 10.0).ln() - 305.044_8
+    };
+    return Color::rgb(
+        red.clamp(0.0, 255.0) / 255.0,
+        green.clamp(0.0, 255.0) / 255.0,
+        blue.clamp(0.0, 255.0) / 255.0,
+    );
+}
+
+/// Returns the color between the colors at the blend, from 0 for the first color to 1
+/// for the second.
+fn mix_colors(from: Color, to: Color, blend: f32) -> Color {
+    let from = from.as_rgba_f32();
+    let to = to.as_rgba_f32();
+    let blend = blend.clamp(0.0, 1.0);
+    let mix = |channel: usize| from[channel] + (to[channel] - from[channel]) * blend;
+    return Color::rgba(mix(0), mix(1), mix(2), mix(3));
+}
+
+/// Returns the direction toward a sky light that is the progress of the way along its
+/// path across the sky, rising in the east at 0 and setting in the west at 1.
+fn sky_direction(progress: f32) -> Vec3 {
+    let angle = progress * PI;
+    return Vec3::new(
+        angle.cos(),
+        angle.sin() * SKY_PATH_TILT.cos(),
+        angle.sin() * SKY_PATH_TILT.sin(),
+    )
+    .normalize();
+}
+
+/// Returns how far a light is along the part of the day between the start and end
+/// hours, wrapping past midnight. The progress is between 0 and 1 while the light is
+/// up and greater than 1 while it is down.
+fn progress_between(hour: f32, start_hour: f32, end_hour: f32) -> f32 {
+    let length = (end_hour - start_hour).rem_euclid(HOURS_PER_DAY);
+    return (hour - start_hour).rem_euclid(HOURS_PER_DAY) / length.max(f32::EPSILON);
+}
+
+/// Places a directional light along its path across the sky, shining toward the center
+/// of the world. Returns the elevation of the light, from 0 at the horizon to 1
+/// overhead, and 0 while it is down.
+fn place_sky_light(transform: &mut Transform, progress: f32) -> f32 {
+    let direction = sky_direction(progress.min(1.0));
+    *transform =
+        Transform::from_translation(direction * SKY_LIGHT_DISTANCE).looking_at(Vec3::ZERO, Vec3::Y);
+    if progress > 1.0 {
+        return 0.0;
+    }
+    return direction.y.max(0.0);
+}
+
+/// Initial system to add the sun and moon lights. They are placed by the time of day
+/// once the game runs.
+pub fn add_sky_lights(mut commands: Commands) {
+    commands.spawn((
+        DirectionalLightBundle {
+            directional_light: DirectionalLight {
+                shadows_enabled: true,
+                ..default()
+            },
+            cascade_shadow_config: CascadeShadowConfigBuilder {
+                first_cascade_far_bound: 15.0,
+                maximum_distance: 80.0,
+                ..default()
+            }
+            .into(),
+            ..default()
+        },
+        Sun,
+    ));
+    commands.spawn((
+        DirectionalLightBundle {
+            directional_light: DirectionalLight {
+                color: color_from_temperature(MOON_TEMPERATURE),
+                shadows_enabled: false,
+                ..default()
+            },
             ..default()
         },
-        transform: Transform::from_xyz(50., 50., 50.),
-        ..default()
-    });
+        Moon,
+    ));
+}
+
+/// A system that moves the sun and moon across the sky with the time of day. The sun
+/// warms up toward the horizon and fades out as it sets, the moon lights the night, and
+/// the ambient light and sky color follow the daylight. Clouded weather dims the light and
+/// greys the sky. Nothing changes while the clock and weather stay the same.
+pub fn update_sky_lights(
+    clock: Res<GameClock>,
+    mut suns: Query<(&mut DirectionalLight, &mut Transform), (With<Sun>, Without<Moon>)>,
+    mut moons: Query<(&mut DirectionalLight, &mut Transform), (With<Moon>, Without<Sun>)>,
+    mut ambient_light: ResMut<AmbientLight>,
+    mut clear_color: ResMut<ClearColor>,
+    weather: Res<Weather>,
+) {
+    if !clock.is_changed() && !weather.is_changed() {
+        return;
+    }
+    let sunlight = weather.today.sunlight();
+    let (sunrise, sunset) = clock.season.daylight_hours();
+    let mut sun_elevation = 0.0;
+    for (mut light, mut transform) in suns.iter_mut() {
+        sun_elevation = place_sky_light(
+            &mut transform,
+            progress_between(clock.hour, sunrise, sunset),
+        );
+        light.illuminance = SUN_NOON_ILLUMINANCE * sun_elevation * sunlight;
+        light.color = color_from_temperature(
+            SUN_HORIZON_TEMPERATURE
+                + (SUN_ZENITH_TEMPERATURE - SUN_HORIZON_TEMPERATURE) * sun_elevation.sqrt(),
+        );
+    }
+    for (mut light, mut transform) in moons.iter_mut() {
+        let moon_elevation = place_sky_light(
+            &mut transform,
+            progress_between(clock.hour, sunset, sunrise),
+        );
+        light.illuminance = MOON_ZENITH_ILLUMINANCE * moon_elevation * sunlight;
+    }
+    // The day is fully lit once the sun is a quarter of the way up.
+    let daylight = (sun_elevation * 4.0).min(1.0);
+    ambient_light.color = mix_colors(
+        color_from_temperature(MOON_TEMPERATURE),
+        Color::WHITE,
+        daylight,
+    );
+    // Clouds block less of the scattered light than of the direct sun.
+    ambient_light.brightness = (NIGHT_AMBIENT_BRIGHTNESS
+        + (DAY_AMBIENT_BRIGHTNESS - NIGHT_AMBIENT_BRIGHTNESS) * daylight)
+        * (0.5 + 0.5 * sunlight);
+    let day_sky = mix_colors(
+        Color::rgb(0.5, 0.53, 0.57),
+        Color::rgb(0.53, 0.75, 0.95),
+        sunlight,
+    );
+    clear_color.0 = mix_colors(Color::rgb(0.02, 0.03, 0.08), day_sky, daylight);
 }
@@ -1,51 +1,401 @@
+// The crate uses explicit returns and explicit field names in struct initialization, and
+// Bevy systems often need queries with complex types and many parameters.
+#![allow(
+    clippy::needless_return,
+    clippy::redundant_field_names,
+    clippy::type_complexity,
+    clippy::too_many_arguments
+)]
+
 mod characters;
+mod collision;
+mod crafting;
+mod farming;
+mod game_state;
+mod game_time;
 mod game_world;
+mod interaction;
+mod items;
 mod lighting;
+mod navigation;
+mod random;
+mod save;
+mod storage;
+mod tools;
+mod ui;
+mod weather;
 
 use bevy::{
     app::App,
-    prelude::{default, DefaultPlugins, PluginGroup, Vec3},
+    asset::{AddAsset, AssetPlugin},
+    prelude::{default, DefaultPlugins, IntoSystemConfig, PluginGroup},
     window::{Window, WindowPlugin},
 };
+use characters::animation::{
+    control::{
+        animate_procedural_parts, link_animation_players, play_animation_clips,
+        update_animation_states, update_character_activities,
+    },
+    start_up::add_animation_clip_library,
+};
+use characters::appearance::{
+    control::{assemble_character_parts, tint_part_scenes},
+    start_up::{add_character_part_catalog, add_procedural_part_meshes},
+};
+use characters::control::{
+    move_character_controllers, turn_characters_to_face_heading, update_movement_velocities,
+};
+use characters::npc::{
+    control::{act_out_schedule_activities, follow_daily_schedules, talk_to_npcs},
+    start_up::{load_npc_catalog, spawn_non_player_characters},
+};
 use characters::player::{
-    camera::control::generate_move_player_camera_system,
-    entity::{
-        control::{gamepad_connection_events, generate_move_player_system},
-        start_up::generate_add_player_system,
+    camera::{
+        control::follow_players_with_orbit_cameras,
+        occlusion::{detect_occluding_objects, fade_occluding_objects},
+        split_screen::{layout_split_screen_viewports, SplitScreenSettings},
+    },
+    control::move_players_and_cameras,
+    entity::control::{
+        despawn_disconnected_players, gamepad_connection_events, keyboard_mouse_join,
+    },
+    input::{
+        bindings::{capture_rebinding, load_input_bindings, start_rebinding, RebindButtonAction},
+        control::update_action_states,
+        controls_menu::{open_controls_menus, use_controls_menus},
     },
+    profile::control::{choose_profiles, save_player_profiles, start_choosing_profiles},
+    profile::customization::customize_appearances,
+    profile::persistence::load_saved_profile_ids,
 };
-use game_world::add_ground_plane;
-use lighting::add_light;
-
-#[derive(Debug)]
-struct Player {
-    id: u8,
-    spawn_location: Vec3,
-}
+use characters::profession::{
+    components::{AssignProfessionEvent, ProfessionRoles},
+    control::{
+        assign_professions, assign_profile_professions, choose_professions, open_profession_menus,
+        refill_roles_of_departed_players,
+    },
+    start_up::load_profession_registry,
+};
+use crafting::control::{advance_crafting_jobs, open_crafting_menus, use_crafting_menus};
+use crafting::start_up::{load_recipe_registry, spawn_workstations};
+use farming::components::{CropDefinition, FarmActionEvent};
+use farming::control::{
+    apply_farm_actions, grow_crops, refresh_reloaded_crops, update_crop_looks, update_soil_patches,
+    water_farm_in_rain,
+};
+use farming::loader::CropDefinitionLoader;
+use farming::start_up::{add_farm_plot, load_crop_definitions, load_farm_grid};
+use game_state::{game_is_running, toggle_pause, GamePaused};
+use game_time::{advance_game_clock, load_game_clock, GameClockEvent};
+use game_world::{add_ground_plane, add_scenery, add_world_bounds, load_world_locations};
+use interaction::components::InteractionEvent;
+use interaction::control::{
+    despawn_orphaned_focus_markers, dispatch_interactions, spawn_interaction_focuses,
+    update_focus_markers, update_interaction_focuses, update_interaction_prompts,
+};
+use interaction::handlers::{
+    pick_up_items, sit_on_seats, stand_up_from_seats, swing_doors, toggle_doors,
+};
+use interaction::start_up::{add_focus_marker_assets, spawn_interactables};
+use items::control::{
+    carry_selected_crops, give_starting_items, open_inventory_menus, select_hotbar_slots,
+    spawn_hotbar_displays, update_hotbar_displays, use_inventory_menus,
+};
+use items::start_up::{load_item_registry, spawn_chests};
+use lighting::{add_sky_lights, update_sky_lights};
+use navigation::{
+    control::{plan_navigation_paths, steer_toward_navigation_targets, update_navigation_grid},
+    start_up::add_navigation_grid,
+};
+use save::control::{
+    apply_saved_state_to_joining_players, autosave, load_autosave_on_start_up, load_game,
+    quick_save_and_load, save_game, AutosaveTimer, LoadGameEvent, SaveGameEvent, SavedPlayers,
+};
+use tools::control::{finish_tool_uses, start_tool_uses};
+use tools::start_up::{load_tool_registry, spawn_resource_nodes};
+use ui::{
+    player_overlay::{
+        despawn_orphaned_player_overlays, expire_player_messages, layout_player_overlays,
+        show_player_messages, spawn_player_overlays, PlayerMessageEvent,
+    },
+    start_up::{add_ui_camera, load_ui_font},
+};
+use weather::control::{update_daily_weather, update_precipitation, update_weather_fog};
+use weather::start_up::{add_precipitation_assets, load_weather};
 
-/// Creates and runs the game application based on the bevy engine crate.
+/// Creates and runs the game application based on the bevy engine crate. Players are
+/// not added here, they are spawned when a gamepad connects or the keyboard join key is
+/// pressed.
 fn main() {
-    let player = Player {
-        id: 0,
-        spawn_location: Vec3 {
-            x: 0.0,
-            y: 0.0,
-            z: 0.0,
-        },
-    };
     App::new()
-        .add_plugins(DefaultPlugins.set(WindowPlugin {
-            primary_window: Some(Window {
-                title: "Farmsim Game".into(),
-                ..default()
-            }),
-            ..default()
-        }))
-        .add_startup_system(generate_add_player_system(player.id, player.spawn_location))
+        .add_plugins(
+            DefaultPlugins
+                .set(WindowPlugin {
+                    primary_window: Some(Window {
+                        title: "Farmsim Game".into(),
+                        ..default()
+                    }),
+                    ..default()
+                })
+                // Reload changed asset files, like crop definitions, while the game runs.
+                .set(AssetPlugin {
+                    watch_for_changes: true,
+                    ..default()
+                }),
+        )
+        .init_resource::<SplitScreenSettings>()
+        .init_resource::<GamePaused>()
+        .insert_resource(load_game_clock())
+        .insert_resource(load_weather())
+        .insert_resource(load_farm_grid())
+        .insert_resource(load_item_registry())
+        .insert_resource(load_tool_registry())
+        .insert_resource(load_recipe_registry())
+        .insert_resource(load_world_locations())
+        .insert_resource(load_npc_catalog())
+        .insert_resource(load_profession_registry())
+        .init_resource::<ProfessionRoles>()
+        .insert_resource(load_input_bindings())
+        .insert_resource(load_saved_profile_ids())
+        .init_resource::<SavedPlayers>()
+        .init_resource::<AutosaveTimer>()
+        .add_event::<RebindButtonAction>()
+        .add_event::<SaveGameEvent>()
+        .add_event::<LoadGameEvent>()
+        .add_event::<AssignProfessionEvent>()
+        .add_event::<GameClockEvent>()
+        .add_event::<FarmActionEvent>()
+        .add_event::<InteractionEvent>()
+        .add_event::<PlayerMessageEvent>()
+        .add_asset::<CropDefinition>()
+        .init_asset_loader::<CropDefinitionLoader>()
         .add_startup_system(add_ground_plane)
-        .add_startup_system(add_light)
+        .add_startup_system(add_scenery)
+        .add_startup_system(add_world_bounds)
+        .add_startup_system(add_sky_lights)
+        .add_startup_system(add_precipitation_assets)
+        .add_startup_system(add_farm_plot)
+        .add_startup_system(load_crop_definitions)
+        .add_startup_system(spawn_chests)
+        .add_startup_system(spawn_resource_nodes)
+        .add_startup_system(add_focus_marker_assets)
+        .add_startup_system(spawn_interactables)
+        .add_startup_system(spawn_workstations)
+        .add_startup_system(load_ui_font)
+        .add_startup_system(add_character_part_catalog)
+        .add_startup_system(add_procedural_part_meshes)
+        .add_startup_system(add_animation_clip_library)
+        .add_startup_system(add_navigation_grid)
+        .add_startup_system(spawn_non_player_characters)
+        .add_startup_system(add_ui_camera)
+        .add_startup_system(load_autosave_on_start_up)
         .add_system(gamepad_connection_events)
-        .add_system(generate_move_player_system(player.id))
-        .add_system(generate_move_player_camera_system(player.id))
+        .add_system(keyboard_mouse_join)
+        .add_system(despawn_disconnected_players)
+        .add_system(start_rebinding)
+        .add_system(capture_rebinding.after(start_rebinding))
+        .add_system(update_action_states.after(capture_rebinding))
+        .add_system(toggle_pause.after(update_action_states))
+        .add_system(open_controls_menus.after(toggle_pause))
+        .add_system(use_controls_menus.after(open_controls_menus))
+        .add_system(
+            move_players_and_cameras
+                .after(update_action_states)
+                .run_if(game_is_running),
+        )
+        .add_system(advance_game_clock.run_if(game_is_running))
+        .add_system(
+            grow_crops
+                .after(advance_game_clock)
+                .before(update_daily_weather)
+                .run_if(game_is_running),
+        )
+        .add_system(update_daily_weather.after(advance_game_clock))
+        .add_system(
+            water_farm_in_rain
+                .after(update_daily_weather)
+                .after(grow_crops)
+                .run_if(game_is_running),
+        )
+        .add_system(
+            start_tool_uses
+                .after(update_action_states)
+                .before(update_character_activities)
+                .run_if(game_is_running),
+        )
+        .add_system(
+            finish_tool_uses
+                .after(update_character_activities)
+                .run_if(game_is_running),
+        )
+        .add_system(
+            apply_farm_actions
+                .after(finish_tool_uses)
+                .after(grow_crops)
+                .run_if(game_is_running),
+        )
+        .add_system(
+            update_soil_patches
+                .after(apply_farm_actions)
+                .after(water_farm_in_rain),
+        )
+        .add_system(refresh_reloaded_crops)
+        .add_system(give_starting_items.after(choose_profiles))
+        .add_system(select_hotbar_slots.after(update_action_states))
+        .add_system(spawn_hotbar_displays.after(spawn_player_overlays))
+        .add_system(
+            update_hotbar_displays
+                .after(spawn_hotbar_displays)
+                .after(select_hotbar_slots)
+                .after(apply_farm_actions)
+                .after(finish_tool_uses)
+                .after(use_inventory_menus),
+        )
+        .add_system(spawn_interaction_focuses.after(spawn_player_overlays))
+        .add_system(update_interaction_focuses.after(move_character_controllers))
+        .add_system(
+            dispatch_interactions
+                .after(update_interaction_focuses)
+                .after(update_action_states)
+                .run_if(game_is_running),
+        )
+        .add_system(open_inventory_menus.after(dispatch_interactions))
+        .add_system(toggle_doors.after(dispatch_interactions))
+        .add_system(swing_doors.after(toggle_doors).run_if(game_is_running))
+        .add_system(sit_on_seats.after(dispatch_interactions))
+        .add_system(
+            stand_up_from_seats
+                .after(sit_on_seats)
+                .before(update_animation_states),
+        )
+        .add_system(pick_up_items.after(dispatch_interactions))
+        .add_system(
+            carry_selected_crops
+                .after(select_hotbar_slots)
+                .after(use_inventory_menus)
+                .after(pick_up_items)
+                .after(apply_farm_actions)
+                .after(stand_up_from_seats)
+                .before(update_animation_states),
+        )
+        .add_system(talk_to_npcs.after(dispatch_interactions))
+        .add_system(open_crafting_menus.after(dispatch_interactions))
+        .add_system(use_crafting_menus.after(update_action_states))
+        .add_system(advance_crafting_jobs.run_if(game_is_running))
+        .add_system(
+            update_interaction_prompts
+                .after(update_interaction_focuses)
+                .after(toggle_doors)
+                .after(pick_up_items)
+                .after(advance_crafting_jobs),
+        )
+        .add_system(update_focus_markers.after(update_interaction_focuses))
+        .add_system(despawn_orphaned_focus_markers)
+        .add_system(
+            show_player_messages
+                .after(talk_to_npcs)
+                .after(pick_up_items),
+        )
+        .add_system(expire_player_messages.after(show_player_messages))
+        .add_system(use_inventory_menus.after(update_action_states))
+        .add_system(
+            update_crop_looks
+                .after(apply_farm_actions)
+                .after(grow_crops)
+                .after(refresh_reloaded_crops),
+        )
+        .add_system(update_sky_lights.after(update_daily_weather))
+        .add_system(update_weather_fog.after(update_sky_lights))
+        .add_system(update_precipitation.run_if(game_is_running))
+        .add_system(
+            follow_daily_schedules
+                .after(update_daily_weather)
+                .run_if(game_is_running),
+        )
+        .add_system(update_navigation_grid)
+        .add_system(
+            plan_navigation_paths
+                .after(update_navigation_grid)
+                .after(follow_daily_schedules)
+                .run_if(game_is_running),
+        )
+        .add_system(
+            steer_toward_navigation_targets
+                .after(plan_navigation_paths)
+                .run_if(game_is_running),
+        )
+        .add_system(
+            act_out_schedule_activities
+                .after(steer_toward_navigation_targets)
+                .before(update_animation_states)
+                .run_if(game_is_running),
+        )
+        .add_system(
+            update_movement_velocities
+                .after(move_players_and_cameras)
+                .after(steer_toward_navigation_targets)
+                .run_if(game_is_running),
+        )
+        .add_system(
+            move_character_controllers
+                .after(update_movement_velocities)
+                .run_if(game_is_running),
+        )
+        .add_system(
+            turn_characters_to_face_heading
+                .after(move_players_and_cameras)
+                .run_if(game_is_running),
+        )
+        .add_system(follow_players_with_orbit_cameras.after(move_character_controllers))
+        .add_system(detect_occluding_objects.after(follow_players_with_orbit_cameras))
+        .add_system(fade_occluding_objects.after(detect_occluding_objects))
+        .add_system(spawn_player_overlays)
+        .add_system(layout_player_overlays.after(layout_split_screen_viewports))
+        .add_system(despawn_orphaned_player_overlays)
+        .add_system(start_choosing_profiles)
+        .add_system(choose_profiles.after(update_action_states))
+        .add_system(customize_appearances.after(choose_profiles))
+        .add_system(assemble_character_parts.after(customize_appearances))
+        .add_system(open_profession_menus.after(update_action_states))
+        .add_system(choose_professions.after(update_action_states))
+        .add_system(assign_profile_professions)
+        .add_system(
+            assign_professions
+                .after(choose_professions)
+                .after(assign_profile_professions)
+                .after(give_starting_items)
+                .after(apply_saved_state_to_joining_players),
+        )
+        .add_system(refill_roles_of_departed_players.after(despawn_disconnected_players))
+        .add_system(tint_part_scenes)
+        .add_system(update_character_activities.run_if(game_is_running))
+        .add_system(
+            update_animation_states
+                .after(update_character_activities)
+                .after(move_character_controllers),
+        )
+        .add_system(link_animation_players.after(assemble_character_parts))
+        .add_system(
+            play_animation_clips
+                .after(update_animation_states)
+                .after(link_animation_players),
+        )
+        .add_system(
+            animate_procedural_parts
+                .after(update_animation_states)
+                .run_if(game_is_running),
+        )
+        .add_system(
+            save_player_profiles
+                .after(choose_profiles)
+                .after(assign_professions)
+                .after(autosave),
+        )
+        .add_system(layout_split_screen_viewports)
+        .add_system(quick_save_and_load.after(update_action_states))
+        .add_system(autosave.run_if(game_is_running))
+        .add_system(save_game.after(quick_save_and_load).after(autosave))
+        .add_system(load_game.after(quick_save_and_load))
+        .add_system(apply_saved_state_to_joining_players.after(load_game))
         .run();
 }
@@ -0,0 +1,8 @@
+/// navigation module for the crate. A walkable grid built from the static world
+/// colliders and the path queries and steering that characters use to find their way
+/// around it.
+pub(crate) mod components;
+pub(crate) mod control;
+pub(crate) mod grid;
+pub(crate) mod path;
+pub(crate) mod start_up;
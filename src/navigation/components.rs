@@ -0,0 +1,55 @@
+use bevy::prelude::{Component, Vec3};
+
+/// A component with where a character that is not controlled by a player is heading.
+/// The character steers toward the position until it is within the arrival radius.
+#[derive(Component)]
+pub struct NavigationTarget {
+    pub position: Option<Vec3>,
+    pub arrival_radius: f32,
+    /// How strongly the character tries to move toward the position, like how far a
+    /// stick is pushed.
+    pub intent_strength: f32,
+    pub arrived: bool,
+}
+
+impl Default for NavigationTarget {
+    fn default() -> Self {
+        return NavigationTarget {
+            position: None,
+            arrival_radius: 0.5,
+            intent_strength: 0.5,
+            arrived: false,
+        };
+    }
+}
+
+/// A component that makes a character with a navigation target follow a path around
+/// the world on the navigation grid, and steer around the other characters on the way.
+/// Characters without it head straight for their target.
+#[derive(Component)]
+pub struct NavigationAgent {
+    /// The waypoints left to walk to, ending at the target.
+    pub path: Vec<Vec3>,
+    /// The target and navigation grid version the path was planned for.
+    pub planned_target: Option<Vec3>,
+    pub planned_grid_version: u64,
+    /// How close the character has to get to a waypoint before heading to the next one.
+    pub waypoint_radius: f32,
+    /// How far from the other characters the character starts steering away from them.
+    pub avoidance_distance: f32,
+    /// How strongly the character steers away from the other characters.
+    pub avoidance_weight: f32,
+}
+
+impl Default for NavigationAgent {
+    fn default() -> Self {
+        return NavigationAgent {
+            path: Vec::new(),
+            planned_target: None,
+            planned_grid_version: 0,
+            waypoint_radius: 0.4,
+            avoidance_distance: 1.0,
+            avoidance_weight: 1.5,
+        };
+    }
+}
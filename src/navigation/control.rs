@@ -0,0 +1,191 @@
+use bevy::prelude::{
+    info, Changed, Entity, GlobalTransform, Or, Query, RemovedComponents, Res, ResMut, Transform,
+    Vec3, With,
+};
+
+use crate::characters::components::{CharacterController, MovementIntent};
+use crate::collision::components::{StaticCollider, WorldCollider};
+
+use super::components::{NavigationAgent, NavigationTarget};
+use super::grid::NavigationGrid;
+use super::path::find_path;
+
+/// Returns the offset between the positions on the ground plane.
+fn ground_offset(from: Vec3, to: Vec3) -> Vec3 {
+    return Vec3::new(to.x - from.x, 0.0, to.z - from.z);
+}
+
+/// A system that keeps the navigation grid in step with the world colliders. The whole
+/// grid is built the first time, and after that only the cells around the colliders
+/// that were added, moved or removed are sampled again, like when a building or fence
+/// is placed.
+pub fn update_navigation_grid(
+    mut grid: ResMut<NavigationGrid>,
+    colliders: Query<(Entity, &StaticCollider, &GlobalTransform)>,
+    changed_colliders: Query<
+        Entity,
+        (
+            With<StaticCollider>,
+            Or<(Changed<StaticCollider>, Changed<GlobalTransform>)>,
+        ),
+    >,
+    mut removed_colliders: RemovedComponents<StaticCollider>,
+) {
+    let changed: Vec<Entity> = changed_colliders
+        .iter()
+        .chain(removed_colliders.iter())
+        .collect();
+    if grid.built && changed.is_empty() {
+        return;
+    }
+    let world_colliders: Vec<(Entity, WorldCollider)> = colliders
+        .iter()
+        .map(|(entity, collider, transform)| {
+            (
+                entity,
+                WorldCollider::from_static_collider(collider, transform),
+            )
+        })
+        .collect();
+    if grid.built {
+        grid.rebuild_changed(&changed, &world_colliders);
+    } else {
+        grid.rebuild_all(&world_colliders);
+        info!(
+            "Built the navigation grid of {} by {} cells",
+            grid.columns, grid.rows
+        );
+    }
+}
+
+/// A system that plans the path of every navigation agent whose target changed, or
+/// whose path may be blocked since the navigation grid changed. Agents whose target can
+/// not be reached on the grid head straight for it.
+pub fn plan_navigation_paths(
+    grid: Res<NavigationGrid>,
+    mut agents: Query<(&Transform, &NavigationTarget, &mut NavigationAgent)>,
+) {
+    if !grid.built {
+        return;
+    }
+    for (transform, target, mut agent) in agents.iter_mut() {
+        let Some(target_position) = target.position else {
+            if agent.planned_target.is_some() {
+                agent.path.clear();
+                agent.planned_target = None;
+            }
+            continue;
+        };
+        if agent.planned_target == Some(target_position)
+            && agent.planned_grid_version == grid.version
+        {
+            continue;
+        }
+        let foot_position = transform.translation - Vec3::Y * grid.agent_shape.half_height;
+        agent.path = find_path(&grid, foot_position, target_position)
+            .unwrap_or_else(|| vec![target_position]);
+        agent.planned_target = Some(target_position);
+        agent.planned_grid_version = grid.version;
+    }
+}
+
+/// Returns the direction that steers a character away from the characters around it.
+/// Characters closer than the avoidance distance push it away, harder the closer they
+/// are, and a character in front of it also makes it sidestep to its right so two
+/// characters walking into each other pass instead of pushing head on.
+fn avoidance_steering(
+    character_entity: Entity,
+    position: Vec3,
+    radius: f32,
+    desired_direction: Vec3,
+    agent: &NavigationAgent,
+    neighbors: &[(Entity, Vec3, f32)],
+) -> Vec3 {
+    let mut steering = Vec3::ZERO;
+    for (neighbor_entity, neighbor_position, neighbor_radius) in neighbors {
+        if *neighbor_entity == character_entity {
+            continue;
+        }
+        let offset = ground_offset(*neighbor_position, position);
+        let distance = offset.length();
+        let range = radius + neighbor_radius + agent.avoidance_distance;
+        if distance >= range || distance <= f32::EPSILON {
+            continue;
+        }
+        let away = offset / distance;
+        let closeness = 1.0 - distance / range;
+        steering += away * closeness;
+        if desired_direction.dot(-away) > 0.5 {
+            let right = Vec3::new(-desired_direction.z, 0.0, desired_direction.x);
+            steering += right * closeness;
+        }
+    }
+    return steering * agent.avoidance_weight;
+}
+
+/// A system that sets the movement intent of every character with a navigation target.
+/// Navigation agents walk the waypoints of their path and steer around the other
+/// characters, and other characters head straight for their target. The character
+/// stops once it is within the arrival radius of the target, measured on the ground
+/// plane.
+pub fn steer_toward_navigation_targets(
+    mut characters: Query<(
+        Entity,
+        &mut NavigationTarget,
+        Option<&mut NavigationAgent>,
+        &Transform,
+        &mut MovementIntent,
+    )>,
+    bodies: Query<(Entity, &Transform, &CharacterController)>,
+) {
+    let neighbors: Vec<(Entity, Vec3, f32)> = bodies
+        .iter()
+        .map(|(entity, transform, controller)| {
+            (entity, transform.translation, controller.capsule.radius)
+        })
+        .collect();
+    for (character_entity, mut target, agent, transform, mut movement_intent) in
+        characters.iter_mut()
+    {
+        movement_intent.sprint = false;
+        let Some(target_position) = target.position else {
+            movement_intent.direction = Vec3::ZERO;
+            continue;
+        };
+        let position = transform.translation;
+        let arrived = ground_offset(position, target_position).length() <= target.arrival_radius;
+        if target.arrived != arrived {
+            target.arrived = arrived;
+        }
+        if arrived {
+            movement_intent.direction = Vec3::ZERO;
+            continue;
+        }
+        let Some(mut agent) = agent else {
+            movement_intent.direction =
+                ground_offset(position, target_position).normalize() * target.intent_strength;
+            continue;
+        };
+        while agent.path.len() > 1
+            && ground_offset(position, agent.path[0]).length() <= agent.waypoint_radius
+        {
+            agent.path.remove(0);
+        }
+        let waypoint = agent.path.first().copied().unwrap_or(target_position);
+        let desired_direction = ground_offset(position, waypoint).normalize_or_zero();
+        let radius = neighbors
+            .iter()
+            .find(|(entity, _, _)| *entity == character_entity)
+            .map_or(0.0, |(_, _, radius)| *radius);
+        let steering = avoidance_steering(
+            character_entity,
+            position,
+            radius,
+            desired_direction,
+            &agent,
+            &neighbors,
+        );
+        movement_intent.direction =
+            (desired_direction + steering).normalize_or_zero() * target.intent_strength;
+    }
+}
@@ -0,0 +1,282 @@
+use std::collections::HashMap;
+
+use bevy::prelude::{Entity, IVec2, Resource, Vec2, Vec3};
+
+use crate::collision::components::WorldCollider;
+use crate::collision::contact::sphere_contact;
+use crate::collision::kinematic::{CapsuleShape, KinematicSettings};
+use crate::collision::ray_cast::ray_cast_collider;
+
+/// How far above a surface the shape of the agent is placed when checking if it fits,
+/// so it does not touch the surface it stands on.
+const CLEARANCE_LIFT: f32 = 0.05;
+/// How deep the shape of the agent can be in a collider and still fit, so touching a
+/// wall does not block a cell.
+const CLEARANCE_TOLERANCE: f32 = 0.01;
+
+/// A cell of the navigation grid.
+#[derive(Clone, Copy, Default)]
+pub struct NavigationCell {
+    pub walkable: bool,
+    /// The height of the surface the agent stands on in the cell.
+    pub height: f32,
+}
+
+/// A Bevy Engine resource with the walkable grid of the world. Every cell has the height
+/// of the lowest surface in it that the agent shape fits on, so the grid follows the
+/// ground, ramps and raised floors. The cells are sampled for a single agent shape, and
+/// the shape is what keeps the paths a radius away from walls.
+#[derive(Resource)]
+pub struct NavigationGrid {
+    /// The corner of the grid with the lowest x and z.
+    pub origin: Vec2,
+    pub cell_size: f32,
+    pub columns: i32,
+    pub rows: i32,
+    /// The shape of the agents the grid is built for, with the center at its height mid
+    /// point.
+    pub agent_shape: CapsuleShape,
+    pub agent_settings: KinematicSettings,
+    /// The height the surfaces are searched down from.
+    pub probe_height: f32,
+    cells: Vec<NavigationCell>,
+    /// The bounds of every collider the grid was last built with, so the cells under a
+    /// collider are sampled again when it moves or is removed.
+    obstacle_bounds: HashMap<Entity, (Vec3, Vec3)>,
+    /// Goes up every time cells change, so agents know to plan their paths again.
+    pub version: u64,
+    pub built: bool,
+}
+
+impl NavigationGrid {
+    /// Creates a grid with no walkable cells centered on the center and covering a
+    /// square of the size.
+    pub fn new(
+        center: Vec2,
+        size: f32,
+        cell_size: f32,
+        agent_shape: CapsuleShape,
+        agent_settings: KinematicSettings,
+        probe_height: f32,
+    ) -> Self {
+        let cell_count = (size / cell_size).ceil() as i32;
+        return NavigationGrid {
+            origin: center - Vec2::splat(cell_count as f32 * cell_size / 2.0),
+            cell_size: cell_size,
+            columns: cell_count,
+            rows: cell_count,
+            agent_shape: agent_shape,
+            agent_settings: agent_settings,
+            probe_height: probe_height,
+            cells: vec![NavigationCell::default(); (cell_count * cell_count) as usize],
+            obstacle_bounds: HashMap::new(),
+            version: 0,
+            built: false,
+        };
+    }
+
+    /// Returns true when the cell is inside the grid.
+    pub fn contains(&self, cell: IVec2) -> bool {
+        return cell.x >= 0 && cell.y >= 0 && cell.x < self.columns && cell.y < self.rows;
+    }
+
+    /// Returns the cell the position is in. The cell can be outside the grid.
+    pub fn cell_at(&self, position: Vec3) -> IVec2 {
+        let local = (Vec2::new(position.x, position.z) - self.origin) / self.cell_size;
+        return local.floor().as_ivec2();
+    }
+
+    /// Returns the cell, or None when it is outside the grid.
+    pub fn cell(&self, cell: IVec2) -> Option<NavigationCell> {
+        if !self.contains(cell) {
+            return None;
+        }
+        return Some(self.cells[(cell.y * self.columns + cell.x) as usize]);
+    }
+
+    /// Returns true when the cell is inside the grid and walkable.
+    pub fn is_walkable(&self, cell: IVec2) -> bool {
+        return self.cell(cell).is_some_and(|cell| cell.walkable);
+    }
+
+    /// Returns the position on the surface at the center of the cell.
+    pub fn cell_position(&self, cell: IVec2) -> Vec3 {
+        let center = self.origin + (cell.as_vec2() + Vec2::splat(0.5)) * self.cell_size;
+        let height = self.cell(cell).map_or(0.0, |cell| cell.height);
+        return Vec3::new(center.x, height, center.y);
+    }
+
+    /// Returns true when an agent can walk straight from one cell to its neighbor. Both
+    /// cells have to be walkable and the surfaces close enough in height to step
+    /// between, and a diagonal move must not cut the corner of a blocked cell.
+    pub fn can_move_between(&self, from: IVec2, to: IVec2) -> bool {
+        let (Some(from_cell), Some(to_cell)) = (self.cell(from), self.cell(to)) else {
+            return false;
+        };
+        if !from_cell.walkable || !to_cell.walkable {
+            return false;
+        }
+        if (from_cell.height - to_cell.height).abs() > self.agent_settings.step_height {
+            return false;
+        }
+        let offset = to - from;
+        if offset.x != 0 && offset.y != 0 {
+            for corner in [
+                from + IVec2::new(offset.x, 0),
+                from + IVec2::new(0, offset.y),
+            ] {
+                let Some(corner_cell) = self.cell(corner) else {
+                    return false;
+                };
+                if !corner_cell.walkable
+                    || (from_cell.height - corner_cell.height).abs()
+                        > self.agent_settings.step_height
+                {
+                    return false;
+                }
+            }
+        }
+        return true;
+    }
+
+    /// Returns true when the agent can walk in a straight line between the positions
+    /// without leaving the walkable cells or climbing more than a step between them.
+    pub fn has_line_of_sight(&self, from: Vec3, to: Vec3) -> bool {
+        let ground_offset = Vec2::new(to.x - from.x, to.z - from.z);
+        let sample_count = (ground_offset.length() / (self.cell_size * 0.25)).ceil() as i32;
+        let mut previous_cell = self.cell_at(from);
+        if !self.is_walkable(previous_cell) {
+            return false;
+        }
+        for sample in 1..=sample_count {
+            let blend = sample as f32 / sample_count as f32;
+            let cell = self.cell_at(from.lerp(to, blend));
+            if cell == previous_cell {
+                continue;
+            }
+            let step = cell - previous_cell;
+            if step.x.abs() > 1 || step.y.abs() > 1 || !self.can_move_between(previous_cell, cell) {
+                return false;
+            }
+            previous_cell = cell;
+        }
+        return true;
+    }
+
+    /// Returns true when the agent shape standing on the surface at the foot position
+    /// does not overlap any collider.
+    fn has_clearance(&self, foot_position: Vec3, colliders: &[WorldCollider]) -> bool {
+        let center = foot_position + Vec3::Y * (self.agent_shape.half_height + CLEARANCE_LIFT);
+        return self
+            .agent_shape
+            .sphere_centers(center)
+            .into_iter()
+            .all(|sphere_center| {
+                colliders.iter().all(|collider| {
+                    sphere_contact(sphere_center, self.agent_shape.radius, collider)
+                        .is_none_or(|contact| contact.depth <= CLEARANCE_TOLERANCE)
+                })
+            });
+    }
+
+    /// Samples a cell by searching down for the surfaces in it. The cell is walkable on
+    /// the lowest surface that is flat enough and that the agent shape fits on.
+    fn sample_cell(&self, cell: IVec2, colliders: &[WorldCollider]) -> NavigationCell {
+        let column_top =
+            self.cell_position(cell) * Vec3::new(1.0, 0.0, 1.0) + Vec3::Y * self.probe_height;
+        let mut surfaces: Vec<(f32, Vec3)> = colliders
+            .iter()
+            .filter_map(|collider| {
+                return ray_cast_collider(
+                    column_top,
+                    Vec3::NEG_Y,
+                    2.0 * self.probe_height,
+                    collider,
+                );
+            })
+            .collect();
+        surfaces.sort_by(|first, second| second.0.total_cmp(&first.0));
+        for (distance, normal) in surfaces {
+            if !self.agent_settings.is_walkable(normal) {
+                continue;
+            }
+            let foot_position = column_top - Vec3::Y * distance;
+            if self.has_clearance(foot_position, colliders) {
+                return NavigationCell {
+                    walkable: true,
+                    height: foot_position.y,
+                };
+            }
+        }
+        return NavigationCell::default();
+    }
+
+    /// Samples the cells overlapping the area between the corners again. The area is
+    /// grown by the agent radius since the agent shape reaches into the cells around a
+    /// collider. Returns true when any cell changed.
+    fn rebuild_area(&mut self, minimum: Vec3, maximum: Vec3, colliders: &[WorldCollider]) -> bool {
+        let margin = Vec3::splat(self.agent_shape.radius + self.cell_size);
+        let first = self.cell_at(minimum - margin).max(IVec2::ZERO);
+        let last = self
+            .cell_at(maximum + margin)
+            .min(IVec2::new(self.columns - 1, self.rows - 1));
+        let mut changed = false;
+        for row in first.y..=last.y {
+            for column in first.x..=last.x {
+                let cell = IVec2::new(column, row);
+                let sampled = self.sample_cell(cell, colliders);
+                let index = (row * self.columns + column) as usize;
+                let old = self.cells[index];
+                if old.walkable != sampled.walkable || (old.height - sampled.height).abs() > 0.01 {
+                    self.cells[index] = sampled;
+                    changed = true;
+                }
+            }
+        }
+        return changed;
+    }
+
+    /// Builds every cell of the grid from the colliders.
+    pub fn rebuild_all(&mut self, colliders: &[(Entity, WorldCollider)]) {
+        let world_colliders: Vec<WorldCollider> =
+            colliders.iter().map(|(_, collider)| *collider).collect();
+        for row in 0..self.rows {
+            for column in 0..self.columns {
+                let cell = IVec2::new(column, row);
+                self.cells[(row * self.columns + column) as usize] =
+                    self.sample_cell(cell, &world_colliders);
+            }
+        }
+        self.obstacle_bounds = colliders
+            .iter()
+            .map(|(entity, collider)| (*entity, collider.bounds()))
+            .collect();
+        self.built = true;
+        self.version += 1;
+    }
+
+    /// Samples again only the cells around the colliders that were added, moved or
+    /// removed, both where they were and where they are now.
+    pub fn rebuild_changed(&mut self, changed: &[Entity], colliders: &[(Entity, WorldCollider)]) {
+        let world_colliders: Vec<WorldCollider> =
+            colliders.iter().map(|(_, collider)| *collider).collect();
+        let mut areas: Vec<(Vec3, Vec3)> = Vec::new();
+        for entity in changed {
+            if let Some(old_bounds) = self.obstacle_bounds.remove(entity) {
+                areas.push(old_bounds);
+            }
+            if let Some((_, collider)) = colliders.iter().find(|(other, _)| other == entity) {
+                let new_bounds = collider.bounds();
+                self.obstacle_bounds.insert(*entity, new_bounds);
+                areas.push(new_bounds);
+            }
+        }
+        let mut changed_cells = false;
+        for (minimum, maximum) in areas {
+            changed_cells |= self.rebuild_area(minimum, maximum, &world_colliders);
+        }
+        if changed_cells {
+            self.version += 1;
+        }
+    }
+}
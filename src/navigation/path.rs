@@ -0,0 +1,332 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+
+use bevy::prelude::{IVec2, Vec3};
+
+use super::grid::NavigationGrid;
+
+/// How many cells around a blocked cell are searched for a walkable cell to start or end
+/// a path on.
+const NEAREST_WALKABLE_SEARCH_RADIUS: i32 = 6;
+/// The most cells a single path query visits before giving up.
+const MAX_VISITED_CELLS: usize = 40_000;
+
+/// A cell waiting to be visited by the A* search, ordered so the heap pops the cell with
+/// the lowest estimated total cost first.
+struct OpenCell {
+    cell: IVec2,
+    estimated_cost: f32,
+}
+
+impl PartialEq for OpenCell {
+    fn eq(&self, other: &Self) -> bool {
+        return self.estimated_cost == other.estimated_cost;
+    }
+}
+
+impl Eq for OpenCell {}
+
+impl PartialOrd for OpenCell {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        return Some(self.cmp(other));
+    }
+}
+
+impl Ord for OpenCell {
+    fn cmp(&self, other: &Self) -> Ordering {
+        return other.estimated_cost.total_cmp(&self.estimated_cost);
+    }
+}
+
+/// Returns the octile distance between the cells, the cost of the shortest path between
+/// them on an open grid with diagonal moves.
+fn octile_distance(from: IVec2, to: IVec2) -> f32 {
+    let offset = (to - from).abs();
+    let diagonal = offset.x.min(offset.y) as f32;
+    let straight = (offset.x - offset.y).abs() as f32;
+    return diagonal * std::f32::consts::SQRT_2 + straight;
+}
+
+/// Returns the walkable cell closest to the cell, searching in growing rings around it.
+fn nearest_walkable_cell(grid: &NavigationGrid, cell: IVec2) -> Option<IVec2> {
+    if grid.is_walkable(cell) {
+        return Some(cell);
+    }
+    for radius in 1..=NEAREST_WALKABLE_SEARCH_RADIUS {
+        let mut closest: Option<(IVec2, f32)> = None;
+        for y in -radius..=radius {
+            for x in -radius..=radius {
+                if x.abs() != radius && y.abs() != radius {
+                    continue;
+                }
+                let candidate = cell + IVec2::new(x, y);
+                let distance = IVec2::new(x, y).as_vec2().length();
+                if grid.is_walkable(candidate)
+                    && closest.is_none_or(|(_, closest_distance)| distance < closest_distance)
+                {
+                    closest = Some((candidate, distance));
+                }
+            }
+        }
+        if let Some((candidate, _)) = closest {
+            return Some(candidate);
+        }
+    }
+    return None;
+}
+
+/// Finds the cells of the shortest path between the cells with A*, including both ends.
+fn find_cell_path(grid: &NavigationGrid, start: IVec2, goal: IVec2) -> Option<Vec<IVec2>> {
+    let mut open = BinaryHeap::new();
+    let mut came_from: HashMap<IVec2, IVec2> = HashMap::new();
+    let mut costs: HashMap<IVec2, f32> = HashMap::from([(start, 0.0)]);
+    open.push(OpenCell {
+        cell: start,
+        estimated_cost: octile_distance(start, goal),
+    });
+    let mut visited = 0;
+    while let Some(OpenCell { cell, .. }) = open.pop() {
+        if cell == goal {
+            let mut path = vec![goal];
+            let mut current = goal;
+            while let Some(previous) = came_from.get(&current) {
+                path.push(*previous);
+                current = *previous;
+            }
+            path.reverse();
+            return Some(path);
+        }
+        visited += 1;
+        if visited > MAX_VISITED_CELLS {
+            return None;
+        }
+        let cost = costs[&cell];
+        for y in -1..=1 {
+            for x in -1..=1 {
+                let neighbor = cell + IVec2::new(x, y);
+                if neighbor == cell || !grid.can_move_between(cell, neighbor) {
+                    continue;
+                }
+                let neighbor_cost = cost + octile_distance(cell, neighbor);
+                if costs
+                    .get(&neighbor)
+                    .is_some_and(|known_cost| *known_cost <= neighbor_cost)
+                {
+                    continue;
+                }
+                costs.insert(neighbor, neighbor_cost);
+                came_from.insert(neighbor, cell);
+                open.push(OpenCell {
+                    cell: neighbor,
+                    estimated_cost: neighbor_cost + octile_distance(neighbor, goal),
+                });
+            }
+        }
+    }
+    return None;
+}
+
+/// Removes the waypoints the agent can skip by walking straight from an earlier waypoint
+/// to a later one, so the path does not zigzag along the grid.
+pub fn smooth_path(grid: &NavigationGrid, waypoints: &[Vec3]) -> Vec<Vec3> {
+    if waypoints.len() <= 2 {
+        return waypoints.to_vec();
+    }
+    let mut smoothed = vec![waypoints[0]];
+    let mut anchor = 0;
+    while anchor < waypoints.len() - 1 {
+        // The farthest waypoint in sight is kept, and the ones in between are skipped.
+        let mut next = anchor + 1;
+        for candidate in (anchor + 2..waypoints.len()).rev() {
+            if grid.has_line_of_sight(waypoints[anchor], waypoints[candidate]) {
+                next = candidate;
+                break;
+            }
+        }
+        smoothed.push(waypoints[next]);
+        anchor = next;
+    }
+    return smoothed;
+}
+
+/// Finds a smoothed path between the positions on the grid. The path starts after the
+/// start position and ends at the goal, or at the closest walkable point to the goal
+/// when the goal is blocked. Returns None when the goal can not be reached.
+pub fn find_path(grid: &NavigationGrid, start: Vec3, goal: Vec3) -> Option<Vec<Vec3>> {
+    let start_cell = nearest_walkable_cell(grid, grid.cell_at(start))?;
+    let goal_cell = nearest_walkable_cell(grid, grid.cell_at(goal))?;
+    let cells = find_cell_path(grid, start_cell, goal_cell)?;
+    let mut waypoints: Vec<Vec3> = cells.iter().map(|cell| grid.cell_position(*cell)).collect();
+    if goal_cell == grid.cell_at(goal) {
+        if let Some(last) = waypoints.last_mut() {
+            *last = Vec3::new(goal.x, last.y, goal.z);
+        }
+    }
+    if start_cell == grid.cell_at(start) {
+        waypoints[0] = Vec3::new(start.x, waypoints[0].y, start.z);
+    }
+    let mut smoothed = smooth_path(grid, &waypoints);
+    smoothed.remove(0);
+    return Some(smoothed);
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::{Entity, Quat, Vec2, Vec3};
+
+    use crate::collision::components::{ColliderShape, WorldCollider};
+    use crate::collision::kinematic::{CapsuleShape, KinematicSettings};
+
+    use super::super::grid::NavigationGrid;
+    use super::{find_path, smooth_path};
+
+    fn cuboid(center: Vec3, half_extents: Vec3) -> WorldCollider {
+        return WorldCollider {
+            shape: ColliderShape::Cuboid {
+                half_extents: half_extents,
+            },
+            center: center,
+            rotation: Quat::IDENTITY,
+        };
+    }
+
+    /// A flat ground with its top at a height of zero.
+    fn ground() -> WorldCollider {
+        return cuboid(Vec3::new(0.0, -0.5, 0.0), Vec3::new(20.0, 0.5, 20.0));
+    }
+
+    /// A wall across the x axis at x 0, from the bottom edge of the grid up to z 2.
+    fn wall() -> WorldCollider {
+        return cuboid(Vec3::new(0.0, 1.0, -2.0), Vec3::new(0.25, 1.0, 4.0));
+    }
+
+    /// Builds a grid of 20 by 20 cells of half a unit, centered on the origin.
+    fn build_grid(colliders: &[WorldCollider]) -> NavigationGrid {
+        let mut grid = NavigationGrid::new(
+            Vec2::ZERO,
+            10.0,
+            0.5,
+            CapsuleShape {
+                radius: 0.3,
+                half_height: 0.9,
+            },
+            KinematicSettings {
+                max_slope_angle: std::f32::consts::FRAC_PI_4,
+                step_height: 0.35,
+                snap_distance: 0.3,
+            },
+            5.0,
+        );
+        let colliders: Vec<(Entity, WorldCollider)> = colliders
+            .iter()
+            .enumerate()
+            .map(|(index, collider)| (Entity::from_raw(index as u32), *collider))
+            .collect();
+        grid.rebuild_all(&colliders);
+        return grid;
+    }
+
+    fn assert_walkable_path(grid: &NavigationGrid, start: Vec3, path: &[Vec3]) {
+        let mut previous = start;
+        for waypoint in path {
+            assert!(
+                grid.has_line_of_sight(previous, *waypoint),
+                "no line of sight from {} to {}",
+                previous,
+                waypoint
+            );
+            previous = *waypoint;
+        }
+    }
+
+    #[test]
+    fn walks_straight_to_the_goal_on_open_ground() {
+        let grid = build_grid(&[ground()]);
+        let goal = Vec3::new(3.2, 0.0, 1.1);
+        let path = find_path(&grid, Vec3::new(-3.1, 0.0, -0.4), goal).unwrap();
+        assert_eq!(path.len(), 1);
+        assert!(path[0].distance(goal) < 1e-4);
+    }
+
+    #[test]
+    fn goes_around_a_wall() {
+        let grid = build_grid(&[ground(), wall()]);
+        let start = Vec3::new(-2.0, 0.0, -2.0);
+        let goal = Vec3::new(2.0, 0.0, -2.0);
+        let path = find_path(&grid, start, goal).unwrap();
+        assert!(path.len() > 1);
+        assert!(path.last().unwrap().distance(goal) < 1e-4);
+        assert!(path.iter().any(|waypoint| waypoint.z > 2.0));
+        assert_walkable_path(&grid, start, &path);
+    }
+
+    #[test]
+    fn ends_next_to_a_blocked_goal() {
+        // The pillar reaches above the probe height, so its top is not walkable either.
+        let pillar = cuboid(Vec3::new(2.0, 3.0, 2.0), Vec3::new(0.5, 3.0, 0.5));
+        let grid = build_grid(&[ground(), pillar]);
+        let goal = Vec3::new(2.0, 0.0, 2.0);
+        assert!(!grid.is_walkable(grid.cell_at(goal)));
+        let path = find_path(&grid, Vec3::new(-2.0, 0.0, -2.0), goal).unwrap();
+        let end = *path.last().unwrap();
+        assert!(grid.is_walkable(grid.cell_at(end)));
+        assert!(end.distance(goal) < 1.5);
+    }
+
+    #[test]
+    fn finds_no_path_into_a_closed_room() {
+        let walls = [
+            cuboid(Vec3::new(3.0, 1.0, 1.5), Vec3::new(1.75, 1.0, 0.25)),
+            cuboid(Vec3::new(3.0, 1.0, 4.5), Vec3::new(1.75, 1.0, 0.25)),
+            cuboid(Vec3::new(1.5, 1.0, 3.0), Vec3::new(0.25, 1.0, 1.75)),
+            cuboid(Vec3::new(4.5, 1.0, 3.0), Vec3::new(0.25, 1.0, 1.75)),
+        ];
+        let mut colliders = vec![ground()];
+        colliders.extend(walls);
+        let grid = build_grid(&colliders);
+        let goal = Vec3::new(3.0, 0.0, 3.0);
+        assert!(grid.is_walkable(grid.cell_at(goal)));
+        assert!(find_path(&grid, Vec3::new(-2.0, 0.0, -2.0), goal).is_none());
+    }
+
+    #[test]
+    fn smooths_a_zigzag_into_a_straight_line() {
+        let grid = build_grid(&[ground()]);
+        let waypoints = [
+            Vec3::new(-2.0, 0.0, 0.0),
+            Vec3::new(-1.0, 0.0, 0.5),
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.5),
+            Vec3::new(2.0, 0.0, 0.0),
+        ];
+        assert_eq!(
+            smooth_path(&grid, &waypoints),
+            vec![waypoints[0], waypoints[4]]
+        );
+    }
+
+    #[test]
+    fn keeps_the_corners_around_a_wall() {
+        let grid = build_grid(&[ground(), wall()]);
+        let waypoints = [
+            Vec3::new(-2.0, 0.0, -2.0),
+            Vec3::new(-1.0, 0.0, 1.0),
+            Vec3::new(-0.5, 0.0, 3.0),
+            Vec3::new(0.5, 0.0, 3.0),
+            Vec3::new(1.0, 0.0, 1.0),
+            Vec3::new(2.0, 0.0, -2.0),
+        ];
+        let smoothed = smooth_path(&grid, &waypoints);
+        assert_eq!(smoothed.first(), waypoints.first());
+        assert_eq!(smoothed.last(), waypoints.last());
+        assert!(smoothed.len() > 2 && smoothed.len() < waypoints.len());
+        assert_walkable_path(&grid, smoothed[0], &smoothed[1..]);
+    }
+
+    #[test]
+    fn leaves_short_paths_as_they_are() {
+        let grid = build_grid(&[ground()]);
+        let waypoints = [Vec3::new(-2.0, 0.0, 0.0), Vec3::new(2.0, 0.0, 0.0)];
+        assert_eq!(smooth_path(&grid, &waypoints), waypoints.to_vec());
+    }
+}
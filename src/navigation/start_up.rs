@@ -0,0 +1,33 @@
+use std::f32::consts::PI;
+
+use bevy::prelude::{Commands, Vec2};
+
+use crate::collision::kinematic::{CapsuleShape, KinematicSettings};
+use crate::game_world::GROUND_SIZE;
+
+use super::grid::NavigationGrid;
+
+/// The length of the sides of the cells of the navigation grid.
+const NAVIGATION_CELL_SIZE: f32 = 0.5;
+/// The height the navigation grid searches down for surfaces from.
+const NAVIGATION_PROBE_HEIGHT: f32 = 10.0;
+
+/// Initial system to add the navigation grid over the ground plane. The grid is built
+/// for the character shape and cells are sampled once the world colliders are placed.
+pub fn add_navigation_grid(mut commands: Commands) {
+    commands.insert_resource(NavigationGrid::new(
+        Vec2::ZERO,
+        GROUND_SIZE,
+        NAVIGATION_CELL_SIZE,
+        CapsuleShape {
+            radius: 0.45,
+            half_height: 1.0,
+        },
+        KinematicSettings {
+            max_slope_angle: PI / 4.0,
+            step_height: 0.4,
+            snap_distance: 0.5,
+        },
+        NAVIGATION_PROBE_HEIGHT,
+    ));
+}
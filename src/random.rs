@@ -0,0 +1,50 @@
+/// A small seeded random number generator, SplitMix64, so results that depend on chance
+/// can be reproduced from their seed. It is fast and good enough for gameplay, but not
+/// for anything that needs to be unpredictable.
+#[derive(Clone)]
+pub struct SeededRandom {
+    state: u64,
+}
+
+impl Default for SeededRandom {
+    fn default() -> Self {
+        return SeededRandom::new(0x5eed);
+    }
+}
+
+impl SeededRandom {
+    /// Creates a generator from the seed.
+    pub fn new(seed: u64) -> Self {
+        return SeededRandom { state: seed };
+    }
+
+    /// Creates a generator from the seed mixed with the parts, so every combination of
+    /// parts, like a date, gets its own reproducible sequence.
+    pub fn from_parts(seed: u64, parts: &[u64]) -> Self {
+        let mut random = SeededRandom::new(seed);
+        for part in parts {
+            random.state ^= part.wrapping_mul(0x9e37_79b9_7f4a_7c15);
+            random.next_u64();
+        }
+        return random;
+    }
+
+    /// Returns the next random number.
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut value = self.state;
+        value = (value ^ (value >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        value = (value ^ (value >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        return value ^ (value >> 31);
+    }
+
+    /// Returns a random number from 0 up to 1.
+    pub fn next_f32(&mut self) -> f32 {
+        return (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32;
+    }
+
+    /// Returns a random number from the minimum up to the maximum.
+    pub fn range_f32(&mut self, minimum: f32, maximum: f32) -> f32 {
+        return minimum + (maximum - minimum) * self.next_f32();
+    }
+}
@@ -0,0 +1,4 @@
+/// save module for the crate. Saves the state of the game to versioned save files in
+/// several slots and loads it back.
+pub(crate) mod control;
+pub(crate) mod format;
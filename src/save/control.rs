@@ -0,0 +1,581 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::{
+    info, warn, Added, Commands, DespawnRecursiveExt, Entity, EventReader, EventWriter, Query, Res,
+    ResMut, Resource, Time, Timer, TimerMode, Transform, Vec3, With, Without,
+};
+
+use crate::characters::components::{
+    CharacterController, MovementModel, Name, NonPlayerCharacter, Stamina,
+};
+use crate::characters::npc::components::{DailySchedule, NpcCatalog, NpcId};
+use crate::characters::npc::start_up::spawn_npc;
+use crate::characters::player::input::components::{ActionState, ButtonAction};
+use crate::characters::player::profile::components::ActiveProfile;
+use crate::characters::profession::components::{
+    ProfessionRegistry, ProfessionRoles, QuestLines, RoleHolder,
+};
+use crate::crafting::components::Workstation;
+use crate::farming::components::Crop;
+use crate::farming::control::spawn_crop;
+use crate::farming::grid::{FarmGrid, SoilState};
+use crate::game_time::GameClock;
+use crate::game_world::WorldLocations;
+use crate::items::components::{Chest, Hotbar, HOTBAR_SIZE};
+use crate::items::inventory::Inventory;
+use crate::navigation::components::NavigationTarget;
+
+use super::format::{
+    read_save_file, write_save_file, ChestSave, ClockSave, FarmSave, FarmTileSave, NpcSave,
+    PlayerSave, RoleHolderSave, RoleSave, SaveGame, SaveSlot, WorkstationSave,
+};
+
+/// The seconds of play between autosaves.
+const AUTOSAVE_INTERVAL_SECONDS: f32 = 120.0;
+/// The slot the quick save and quick load actions use.
+const QUICK_SAVE_SLOT: SaveSlot = SaveSlot::Manual(1);
+
+/// An event to save the game to the slot.
+pub struct SaveGameEvent {
+    pub slot: SaveSlot,
+}
+
+/// An event to load the game from the slot.
+pub struct LoadGameEvent {
+    pub slot: SaveSlot,
+}
+
+/// A Bevy Engine resource with the saved state of every player from the last loaded
+/// save by profile id. Players that pick their profile after the game is loaded get
+/// their saved state, and players that have not joined yet are kept in the next save.
+#[derive(Resource, Default)]
+pub struct SavedPlayers(pub BTreeMap<String, PlayerSave>);
+
+/// A Bevy Engine resource with the timer until the next autosave.
+#[derive(Resource)]
+pub struct AutosaveTimer(pub Timer);
+
+impl Default for AutosaveTimer {
+    fn default() -> Self {
+        return AutosaveTimer(Timer::from_seconds(
+            AUTOSAVE_INTERVAL_SECONDS,
+            TimerMode::Repeating,
+        ));
+    }
+}
+
+/// The query of the player state that is saved and loaded.
+type SavablePlayerQuery<'world, 'state> = Query<
+    'world,
+    'state,
+    (
+        &'static ActiveProfile,
+        &'static mut Name,
+        &'static mut Transform,
+        Option<&'static mut Stamina>,
+        Option<&'static mut CharacterController>,
+        Option<&'static mut MovementModel>,
+        Option<&'static mut Inventory>,
+        Option<&'static mut Hotbar>,
+    ),
+    Without<Chest>,
+>;
+
+/// The query of the state of the characters that are not controlled by players that is
+/// saved and loaded.
+type SavableNpcQuery<'world, 'state> = Query<
+    'world,
+    'state,
+    (
+        &'static NpcId,
+        &'static mut Transform,
+        &'static mut DailySchedule,
+        &'static mut NavigationTarget,
+        Option<&'static mut MovementModel>,
+    ),
+    (With<NonPlayerCharacter>, Without<ActiveProfile>),
+>;
+
+/// Puts the saved slots in the inventory. The inventory keeps its own number of slots
+/// when the save has fewer, like saves from before inventories.
+fn apply_inventory_save(saved_inventory: &Inventory, inventory: &mut Inventory) {
+    let size = inventory.slots.len().max(saved_inventory.slots.len());
+    inventory.slots = saved_inventory.slots.clone();
+    inventory.slots.resize(size, None);
+}
+
+/// Applies the saved state of a player to its components. The player starts the loaded
+/// game standing still.
+fn apply_player_save(
+    player_save: &PlayerSave,
+    name: &mut Name,
+    transform: &mut Transform,
+    stamina: Option<&mut Stamina>,
+    character_controller: Option<&mut CharacterController>,
+    movement_model: Option<&mut MovementModel>,
+    inventory: Option<&mut Inventory>,
+    hotbar: Option<&mut Hotbar>,
+) {
+    name.0 = player_save.name.clone();
+    transform.translation = player_save.translation;
+    transform.rotation = player_save.rotation;
+    if let Some(stamina) = stamina {
+        stamina.current = player_save.stamina.clamp(0.0, stamina.max);
+    }
+    if let Some(character_controller) = character_controller {
+        character_controller.vertical_speed = 0.0;
+        character_controller.grounded = false;
+    }
+    if let Some(movement_model) = movement_model {
+        movement_model.velocity = Vec3::ZERO;
+    }
+    if let Some(inventory) = inventory {
+        apply_inventory_save(&player_save.inventory, inventory);
+    }
+    if let Some(hotbar) = hotbar {
+        hotbar.selected = player_save.hotbar_selected.min(HOTBAR_SIZE - 1);
+    }
+}
+
+/// Applies the saved state of a character that is not controlled by a player to its
+/// components. The character starts the loaded game standing still, on the entry of its
+/// schedule and heading where it was when the game was saved.
+fn apply_npc_save(
+    npc_save: &NpcSave,
+    transform: &mut Transform,
+    schedule: &mut DailySchedule,
+    target: &mut NavigationTarget,
+    movement_model: Option<&mut MovementModel>,
+) {
+    transform.translation = npc_save.translation;
+    transform.rotation = npc_save.rotation;
+    schedule.current = npc_save
+        .schedule_entry
+        .filter(|index| *index < schedule.entries.len());
+    target.position = npc_save.target;
+    target.arrived = npc_save.arrived;
+    if let Some(movement_model) = movement_model {
+        movement_model.velocity = Vec3::ZERO;
+    }
+}
+
+/// Returns the saved state of the characters that are not controlled by players.
+fn create_npc_saves(npcs: &SavableNpcQuery) -> Vec<NpcSave> {
+    return npcs
+        .iter()
+        .map(|(npc_id, transform, schedule, target, _)| NpcSave {
+            id: npc_id.0.clone(),
+            translation: transform.translation,
+            rotation: transform.rotation,
+            schedule_entry: schedule.current,
+            target: target.position,
+            arrived: target.arrived,
+        })
+        .collect();
+}
+
+/// Returns the saved farm plot, with the worked tiles and the crops on them.
+fn create_farm_save(grid: &FarmGrid, crops: &Query<&Crop>) -> FarmSave {
+    return FarmSave {
+        tiles: grid
+            .tiles()
+            .filter(|(_, farm_tile)| farm_tile.soil != SoilState::Untilled || farm_tile.watered)
+            .map(|(tile, farm_tile)| FarmTileSave {
+                tile: tile,
+                soil: farm_tile.soil,
+                watered: farm_tile.watered,
+            })
+            .collect(),
+        crops: crops.iter().cloned().collect(),
+    };
+}
+
+/// Returns the saved roles of the professions. The roles held by players are saved by
+/// the profile id of the player.
+fn create_role_saves(
+    roles: &ProfessionRoles,
+    players: &SavablePlayerQuery,
+    npcs: &Query<&QuestLines, With<NonPlayerCharacter>>,
+) -> Vec<RoleSave> {
+    return roles
+        .0
+        .iter()
+        .map(|(profession_id, role)| {
+            let holder = match role.holder {
+                RoleHolder::Vacant => RoleHolderSave::Vacant,
+                RoleHolder::Npc(npc_entity) => RoleHolderSave::Npc {
+                    quest_lines: npcs
+                        .get(npc_entity)
+                        .map_or_else(|_| BTreeMap::new(), |quest_lines| quest_lines.0.clone()),
+                },
+                RoleHolder::Player(player_entity) => match players.get(player_entity) {
+                    Ok((active_profile, ..)) => RoleHolderSave::Player {
+                        profile_id: active_profile.id.clone(),
+                    },
+                    Err(_) => RoleHolderSave::Vacant,
+                },
+            };
+            RoleSave {
+                profession_id: profession_id.clone(),
+                holder: holder,
+                next_npc: role.next_npc,
+            }
+        })
+        .collect();
+}
+
+/// Puts the farm plot back into its saved state. The crops growing now are replaced by
+/// the saved crops.
+fn apply_farm_save(
+    commands: &mut Commands,
+    farm_save: &FarmSave,
+    grid: &mut FarmGrid,
+    crop_entities: &Query<Entity, With<Crop>>,
+) {
+    for crop_entity in crop_entities.iter() {
+        commands.entity(crop_entity).despawn_recursive();
+    }
+    grid.clear();
+    for tile_save in farm_save.tiles.iter() {
+        grid.restore_soil(tile_save.tile, tile_save.soil, tile_save.watered);
+    }
+    for crop in farm_save.crops.iter() {
+        if spawn_crop(commands, grid, crop.clone()).is_none() {
+            warn!(
+                "Could not put the saved {} back on the farm tile {}",
+                crop.crop_id, crop.tile
+            );
+        }
+    }
+}
+
+/// Puts the roles of the professions back into their saved state. Players keep the
+/// role of their profile, and the NPC standing in for a player that has not joined yet
+/// keeps the role until the player joins. The saved NPC of a role joins the community
+/// again with its saved quest progress and state in place of the NPC holding the role
+/// now.
+fn apply_role_saves(
+    commands: &mut Commands,
+    role_saves: &[RoleSave],
+    npc_saves: &[NpcSave],
+    registry: &ProfessionRegistry,
+    catalog: &NpcCatalog,
+    locations: &WorldLocations,
+    roles: &mut ProfessionRoles,
+) {
+    for role_save in role_saves.iter() {
+        let Some(role) = roles.0.get_mut(&role_save.profession_id) else {
+            continue;
+        };
+        role.next_npc = role_save.next_npc;
+        if let RoleHolder::Player(_) = role.holder {
+            continue;
+        }
+        let quest_lines = match &role_save.holder {
+            RoleHolderSave::Player { .. } => continue,
+            RoleHolderSave::Vacant => None,
+            RoleHolderSave::Npc { quest_lines } => Some(quest_lines),
+        };
+        if let RoleHolder::Npc(npc_entity) = role.holder {
+            commands.entity(npc_entity).despawn_recursive();
+        }
+        role.holder = RoleHolder::Vacant;
+        let Some(quest_lines) = quest_lines else {
+            continue;
+        };
+        let Some(profession) = registry.find(&role_save.profession_id) else {
+            continue;
+        };
+        if profession.npcs.is_empty() {
+            continue;
+        }
+        // The NPC holding the role is the last one that joined.
+        let npc_id = &profession.npcs[(role_save.next_npc.max(1) - 1) % profession.npcs.len()];
+        let Some(definition) = catalog.find(npc_id) else {
+            warn!(
+                "There is no NPC {} to fill the role {}",
+                npc_id, role_save.profession_id
+            );
+            continue;
+        };
+        if let Some(npc_entity) = spawn_npc(commands, definition, registry, locations) {
+            let mut npc_commands = commands.entity(npc_entity);
+            npc_commands.insert(QuestLines(quest_lines.clone()));
+            if let Some(npc_save) = npc_saves.iter().find(|npc_save| npc_save.id == *npc_id) {
+                let mut transform = Transform::default();
+                let mut schedule = DailySchedule::new(definition.schedule.clone());
+                let mut target = NavigationTarget::default();
+                apply_npc_save(npc_save, &mut transform, &mut schedule, &mut target, None);
+                npc_commands.insert((transform, schedule, target));
+            }
+            role.holder = RoleHolder::Npc(npc_entity);
+        }
+    }
+}
+
+/// A system that sends the save and load events when any player presses the quick save
+/// or quick load action.
+pub fn quick_save_and_load(
+    action_states: Query<&ActionState>,
+    mut save_events: EventWriter<SaveGameEvent>,
+    mut load_events: EventWriter<LoadGameEvent>,
+) {
+    let just_pressed = |action: ButtonAction| {
+        return action_states
+            .iter()
+            .any(|action_state| action_state.just_pressed(action));
+    };
+    if just_pressed(ButtonAction::QuickSave) {
+        save_events.send(SaveGameEvent {
+            slot: QUICK_SAVE_SLOT,
+        });
+    }
+    if just_pressed(ButtonAction::QuickLoad) {
+        load_events.send(LoadGameEvent {
+            slot: QUICK_SAVE_SLOT,
+        });
+    }
+}
+
+/// A system that sends a save event for the autosave slot every autosave interval of
+/// play.
+pub fn autosave(
+    timer: Res<Time>,
+    mut autosave_timer: ResMut<AutosaveTimer>,
+    mut save_events: EventWriter<SaveGameEvent>,
+) {
+    if autosave_timer.0.tick(timer.delta()).just_finished() {
+        save_events.send(SaveGameEvent {
+            slot: SaveSlot::Autosave,
+        });
+    }
+}
+
+/// Initial system that loads the autosave, if there is one, so the game picks up where
+/// it was put down.
+pub fn load_autosave_on_start_up(mut load_events: EventWriter<LoadGameEvent>) {
+    if SaveSlot::Autosave.path().exists() {
+        load_events.send(LoadGameEvent {
+            slot: SaveSlot::Autosave,
+        });
+    }
+}
+
+/// A system that writes the state of the game to the slot of every save event.
+pub fn save_game(
+    mut save_events: EventReader<SaveGameEvent>,
+    mut saved_players: ResMut<SavedPlayers>,
+    clock: Res<GameClock>,
+    players: SavablePlayerQuery,
+    chests: Query<(&Chest, &Inventory)>,
+    workstations: Query<&Workstation>,
+    grid: Res<FarmGrid>,
+    crops: Query<&Crop>,
+    roles: Res<ProfessionRoles>,
+    npcs: Query<&QuestLines, With<NonPlayerCharacter>>,
+    npc_states: SavableNpcQuery,
+) {
+    for save_event in save_events.iter() {
+        for (active_profile, name, transform, stamina, _, _, inventory, hotbar) in players.iter() {
+            saved_players.0.insert(
+                active_profile.id.clone(),
+                PlayerSave {
+                    profile_id: active_profile.id.clone(),
+                    name: name.0.clone(),
+                    translation: transform.translation,
+                    rotation: transform.rotation,
+                    stamina: stamina.map_or(0.0, |stamina| stamina.current),
+                    inventory: inventory.cloned().unwrap_or_default(),
+                    hotbar_selected: hotbar.map_or(0, |hotbar| hotbar.selected),
+                },
+            );
+        }
+        let save_game = SaveGame {
+            clock: ClockSave {
+                year: clock.year,
+                season: clock.season,
+                day: clock.day,
+                hour: clock.hour,
+            },
+            players: saved_players.0.values().cloned().collect(),
+            chests: chests
+                .iter()
+                .map(|(chest, inventory)| ChestSave {
+                    id: chest.id.clone(),
+                    inventory: inventory.clone(),
+                })
+                .collect(),
+            workstations: workstations
+                .iter()
+                .map(|workstation| WorkstationSave {
+                    id: workstation.id.clone(),
+                    jobs: workstation.queue.clone(),
+                    output: workstation.output.clone(),
+                })
+                .collect(),
+            farm: create_farm_save(&grid, &crops),
+            roles: create_role_saves(&roles, &players, &npcs),
+            npcs: create_npc_saves(&npc_states),
+            ..SaveGame::default()
+        };
+        let path = save_event.slot.path();
+        match write_save_file(&path, &save_game) {
+            Ok(()) => info!("Saved the game to {:?}", save_event.slot),
+            Err(error) => warn!("Could not save the game to {}: {}", path.display(), error),
+        }
+    }
+}
+
+/// A system that reads the save file of the slot of every load event and applies it to
+/// the game. Players that have not joined yet get their saved state when they join.
+pub fn load_game(
+    mut commands: Commands,
+    mut load_events: EventReader<LoadGameEvent>,
+    mut saved_players: ResMut<SavedPlayers>,
+    mut clock: ResMut<GameClock>,
+    mut players: SavablePlayerQuery,
+    mut chests: Query<(&Chest, &mut Inventory), Without<ActiveProfile>>,
+    mut workstations: Query<&mut Workstation>,
+    mut grid: ResMut<FarmGrid>,
+    crop_entities: Query<Entity, With<Crop>>,
+    mut roles: ResMut<ProfessionRoles>,
+    registry: Res<ProfessionRegistry>,
+    catalog: Res<NpcCatalog>,
+    locations: Res<WorldLocations>,
+    mut npc_states: SavableNpcQuery,
+) {
+    for load_event in load_events.iter() {
+        let path = load_event.slot.path();
+        let save_game = match read_save_file(&path) {
+            Ok(save_game) => save_game,
+            Err(error) => {
+                warn!("Could not load the game from {}: {}", path.display(), error);
+                continue;
+            }
+        };
+        clock.year = save_game.clock.year;
+        clock.season = save_game.clock.season;
+        clock.day = save_game.clock.day;
+        clock.hour = save_game.clock.hour;
+        for (chest, mut inventory) in chests.iter_mut() {
+            let chest_save = save_game
+                .chests
+                .iter()
+                .find(|chest_save| chest_save.id == chest.id);
+            if let Some(chest_save) = chest_save {
+                apply_inventory_save(&chest_save.inventory, &mut inventory);
+            }
+        }
+        for mut workstation in workstations.iter_mut() {
+            let workstation_save = save_game
+                .workstations
+                .iter()
+                .find(|workstation_save| workstation_save.id == workstation.id);
+            if let Some(workstation_save) = workstation_save {
+                workstation.queue = workstation_save.jobs.clone();
+                apply_inventory_save(&workstation_save.output, &mut workstation.output);
+            }
+        }
+        apply_farm_save(&mut commands, &save_game.farm, &mut grid, &crop_entities);
+        apply_role_saves(
+            &mut commands,
+            &save_game.roles,
+            &save_game.npcs,
+            &registry,
+            &catalog,
+            &locations,
+            &mut roles,
+        );
+        for (npc_id, mut transform, mut schedule, mut target, movement_model) in
+            npc_states.iter_mut()
+        {
+            let npc_save = save_game
+                .npcs
+                .iter()
+                .find(|npc_save| npc_save.id == npc_id.0);
+            if let Some(npc_save) = npc_save {
+                apply_npc_save(
+                    npc_save,
+                    &mut transform,
+                    &mut schedule,
+                    &mut target,
+                    movement_model.map(|movement_model| movement_model.into_inner()),
+                );
+            }
+        }
+        saved_players.0 = save_game
+            .players
+            .into_iter()
+            .map(|player_save| (player_save.profile_id.clone(), player_save))
+            .collect();
+        for (
+            active_profile,
+            mut name,
+            mut transform,
+            stamina,
+            character_controller,
+            movement_model,
+            inventory,
+            hotbar,
+        ) in players.iter_mut()
+        {
+            if let Some(player_save) = saved_players.0.get(&active_profile.id) {
+                apply_player_save(
+                    player_save,
+                    &mut name,
+                    &mut transform,
+                    stamina.map(|stamina| stamina.into_inner()),
+                    character_controller.map(|controller| controller.into_inner()),
+                    movement_model.map(|movement_model| movement_model.into_inner()),
+                    inventory.map(|inventory| inventory.into_inner()),
+                    hotbar.map(|hotbar| hotbar.into_inner()),
+                );
+            }
+        }
+        info!("Loaded the game from {:?}", load_event.slot);
+    }
+}
+
+/// A system that applies the saved state of the loaded game to players when they pick
+/// their profile.
+pub fn apply_saved_state_to_joining_players(
+    saved_players: Res<SavedPlayers>,
+    mut players: Query<
+        (
+            &ActiveProfile,
+            &mut Name,
+            &mut Transform,
+            Option<&mut Stamina>,
+            Option<&mut CharacterController>,
+            Option<&mut MovementModel>,
+            Option<&mut Inventory>,
+            Option<&mut Hotbar>,
+        ),
+        Added<ActiveProfile>,
+    >,
+) {
+    for (
+        active_profile,
+        mut name,
+        mut transform,
+        stamina,
+        character_controller,
+        movement_model,
+        inventory,
+        hotbar,
+    ) in players.iter_mut()
+    {
+        if let Some(player_save) = saved_players.0.get(&active_profile.id) {
+            apply_player_save(
+                player_save,
+                &mut name,
+                &mut transform,
+                stamina.map(|stamina| stamina.into_inner()),
+                character_controller.map(|controller| controller.into_inner()),
+                movement_model.map(|movement_model| movement_model.into_inner()),
+                inventory.map(|inventory| inventory.into_inner()),
+                hotbar.map(|hotbar| hotbar.into_inner()),
+            );
+        }
+    }
+}
@@ -0,0 +1,583 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::{IVec2, Quat, Vec3};
+use serde::{Deserialize, Serialize};
+
+use crate::characters::player::profile::persistence::profile_id_from_name;
+use crate::crafting::components::CraftingJob;
+use crate::farming::components::Crop;
+use crate::farming::grid::SoilState;
+use crate::game_time::Season;
+use crate::items::inventory::Inventory;
+use crate::storage::{write_ron_file, StorageError};
+
+/// The version of the save format written by this build. Bump it whenever the save
+/// format changes in a way older saves can not be read with, and add a migration from
+/// the previous version to `migrate_save`.
+pub const CURRENT_SAVE_VERSION: u32 = 7;
+
+/// The directory the save files are written to.
+const SAVE_DIRECTORY: &str = "saves";
+
+/// The slots a game can be saved to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SaveSlot {
+    /// A slot the players save to themselves.
+    Manual(u8),
+    /// The slot the game saves to on its own every few minutes.
+    Autosave,
+}
+
+impl SaveSlot {
+    /// Returns the path of the save file of the slot.
+    pub fn path(&self) -> PathBuf {
+        let file_name = match self {
+            SaveSlot::Manual(slot) => format!("slot_{}.ron", slot),
+            SaveSlot::Autosave => "autosave.ron".to_string(),
+        };
+        return PathBuf::from(SAVE_DIRECTORY).join(file_name);
+    }
+}
+
+/// The errors that can happen when saving or loading a game.
+#[derive(Debug)]
+pub enum SaveError {
+    Storage(StorageError),
+    /// The save was written by a newer build of the game, or by a version with no
+    /// migration.
+    UnsupportedVersion(u32),
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            SaveError::Storage(error) => write!(formatter, "{}", error),
+            SaveError::UnsupportedVersion(version) => write!(
+                formatter,
+                "unsupported save version {}, this build reads up to version {}",
+                version, CURRENT_SAVE_VERSION
+            ),
+        };
+    }
+}
+
+impl From<StorageError> for SaveError {
+    fn from(error: StorageError) -> Self {
+        return SaveError::Storage(error);
+    }
+}
+
+/// The saved state of a player character. Players are saved by the id of their profile
+/// so the state follows the person playing, whatever controller they join with.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct PlayerSave {
+    pub profile_id: String,
+    pub name: String,
+    pub translation: Vec3,
+    pub rotation: Quat,
+    pub stamina: f32,
+    pub inventory: Inventory,
+    pub hotbar_selected: usize,
+}
+
+/// The saved contents of a storage chest, by the id of the chest.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ChestSave {
+    pub id: String,
+    pub inventory: Inventory,
+}
+
+/// The saved jobs and crafted items of a workstation, by the id of the workstation.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct WorkstationSave {
+    pub id: String,
+    pub jobs: Vec<CraftingJob>,
+    pub output: Inventory,
+}
+
+/// The saved soil of a farm tile that was worked on.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct FarmTileSave {
+    pub tile: IVec2,
+    pub soil: SoilState,
+    pub watered: bool,
+}
+
+/// The saved farm plot. Only the tiles that are not plain untilled soil are saved, and
+/// the crops are saved with the tiles they are planted on.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct FarmSave {
+    pub tiles: Vec<FarmTileSave>,
+    pub crops: Vec<Crop>,
+}
+
+/// Who filled the role of a profession when the game was saved. The NPC holding a role
+/// is the last NPC of the profession that joined the community.
+#[derive(Clone, Serialize, Deserialize)]
+pub enum RoleHolderSave {
+    Vacant,
+    Npc { quest_lines: BTreeMap<String, u32> },
+    Player { profile_id: String },
+}
+
+/// The saved role of a profession, by the id of the profession.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RoleSave {
+    pub profession_id: String,
+    pub holder: RoleHolderSave,
+    pub next_npc: usize,
+}
+
+/// The saved state of a character that is not controlled by a player, by the id of its
+/// definition. The entry is the index of the entry of its schedule it is following.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct NpcSave {
+    pub id: String,
+    pub translation: Vec3,
+    pub rotation: Quat,
+    pub schedule_entry: Option<usize>,
+    pub target: Option<Vec3>,
+    pub arrived: bool,
+}
+
+/// The saved date and time of the in-game clock.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct ClockSave {
+    pub year: u32,
+    pub season: Season,
+    pub day: u32,
+    pub hour: f32,
+}
+
+impl Default for ClockSave {
+    fn default() -> Self {
+        return ClockSave {
+            year: 1,
+            season: Season::Spring,
+            day: 1,
+            hour: 6.0,
+        };
+    }
+}
+
+/// The state of a game in a save file.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SaveGame {
+    pub version: u32,
+    pub clock: ClockSave,
+    pub players: Vec<PlayerSave>,
+    pub chests: Vec<ChestSave>,
+    pub workstations: Vec<WorkstationSave>,
+    pub farm: FarmSave,
+    pub roles: Vec<RoleSave>,
+    pub npcs: Vec<NpcSave>,
+}
+
+impl Default for SaveGame {
+    fn default() -> Self {
+        return SaveGame {
+            version: CURRENT_SAVE_VERSION,
+            clock: ClockSave::default(),
+            players: Vec::new(),
+            chests: Vec::new(),
+            workstations: Vec::new(),
+            farm: FarmSave::default(),
+            roles: Vec::new(),
+            npcs: Vec::new(),
+        };
+    }
+}
+
+/// The start of every save file, used to find the version before reading the rest.
+#[derive(Deserialize)]
+struct SaveHeader {
+    version: u32,
+}
+
+/// The saved state of a player character in version 1 saves, before players had
+/// profiles. Players were saved by their player id.
+#[derive(Deserialize)]
+struct PlayerSaveV1 {
+    name: String,
+    translation: Vec3,
+    rotation: Quat,
+    stamina: f32,
+}
+
+/// The state of a game in version 1 saves.
+#[derive(Deserialize)]
+struct SaveGameV1 {
+    players: Vec<PlayerSaveV1>,
+}
+
+/// The saved state of a player character in version 2 and 3 saves, before players had
+/// inventories.
+#[derive(Deserialize)]
+struct PlayerSaveV2 {
+    profile_id: String,
+    name: String,
+    translation: Vec3,
+    rotation: Quat,
+    stamina: f32,
+}
+
+/// The state of a game in version 2 saves, before the in-game clock was saved.
+#[derive(Deserialize)]
+struct SaveGameV2 {
+    players: Vec<PlayerSaveV2>,
+}
+
+/// The state of a game in version 3 saves, before inventories and chests were saved.
+#[derive(Deserialize)]
+struct SaveGameV3 {
+    clock: ClockSave,
+    players: Vec<PlayerSaveV2>,
+}
+
+/// The state of a game in version 4 saves, before workstations were saved.
+#[derive(Deserialize)]
+struct SaveGameV4 {
+    clock: ClockSave,
+    players: Vec<PlayerSave>,
+    chests: Vec<ChestSave>,
+}
+
+/// The state of a game in version 5 saves, before the farm and the roles of the
+/// professions were saved.
+#[derive(Deserialize)]
+struct SaveGameV5 {
+    clock: ClockSave,
+    players: Vec<PlayerSave>,
+    chests: Vec<ChestSave>,
+    workstations: Vec<WorkstationSave>,
+}
+
+/// The state of a game in version 6 saves, before the characters that are not controlled
+/// by players were saved.
+#[derive(Deserialize)]
+struct SaveGameV6 {
+    clock: ClockSave,
+    players: Vec<PlayerSave>,
+    chests: Vec<ChestSave>,
+    workstations: Vec<WorkstationSave>,
+    farm: FarmSave,
+    roles: Vec<RoleSave>,
+}
+
+/// Migrates a version 1 save to version 2. The players are matched to the profile with
+/// their name.
+fn migrate_v1_to_v2(save_game: SaveGameV1) -> SaveGameV2 {
+    return SaveGameV2 {
+        players: save_game
+            .players
+            .into_iter()
+            .map(|player_save| PlayerSaveV2 {
+                profile_id: profile_id_from_name(&player_save.name),
+                name: player_save.name,
+                translation: player_save.translation,
+                rotation: player_save.rotation,
+                stamina: player_save.stamina,
+            })
+            .collect(),
+    };
+}
+
+/// Migrates a version 2 save to version 3. The clock starts at the first morning since
+/// older saves did not keep the time.
+fn migrate_v2_to_v3(save_game: SaveGameV2) -> SaveGameV3 {
+    return SaveGameV3 {
+        clock: ClockSave::default(),
+        players: save_game.players,
+    };
+}
+
+/// Migrates a version 3 save to version 4. The players start with empty inventories and
+/// the chests keep what they hold in a new game.
+fn migrate_v3_to_v4(save_game: SaveGameV3) -> SaveGameV4 {
+    return SaveGameV4 {
+        clock: save_game.clock,
+        players: save_game
+            .players
+            .into_iter()
+            .map(|player_save| PlayerSave {
+                profile_id: player_save.profile_id,
+                name: player_save.name,
+                translation: player_save.translation,
+                rotation: player_save.rotation,
+                stamina: player_save.stamina,
+                inventory: Inventory::default(),
+                hotbar_selected: 0,
+            })
+            .collect(),
+        chests: Vec::new(),
+    };
+}
+
+/// Migrates a version 4 save to version 5. The workstations start with nothing queued.
+fn migrate_v4_to_v5(save_game: SaveGameV4) -> SaveGameV5 {
+    return SaveGameV5 {
+        clock: save_game.clock,
+        players: save_game.players,
+        chests: save_game.chests,
+        workstations: Vec::new(),
+    };
+}
+
+/// Migrates a version 5 save to version 6. The farm starts untilled and the roles are
+/// held by whoever holds them in a new game.
+fn migrate_v5_to_v6(save_game: SaveGameV5) -> SaveGameV6 {
+    return SaveGameV6 {
+        clock: save_game.clock,
+        players: save_game.players,
+        chests: save_game.chests,
+        workstations: save_game.workstations,
+        farm: FarmSave::default(),
+        roles: Vec::new(),
+    };
+}
+
+/// Migrates a version 6 save to version 7. The characters that are not controlled by
+/// players start at their homes and pick up their schedule from the saved time.
+fn migrate_v6_to_v7(save_game: SaveGameV6) -> SaveGame {
+    return SaveGame {
+        version: 7,
+        clock: save_game.clock,
+        players: save_game.players,
+        chests: save_game.chests,
+        workstations: save_game.workstations,
+        farm: save_game.farm,
+        roles: save_game.roles,
+        npcs: Vec::new(),
+    };
+}
+
+/// Reads the save file contents written with the version and migrates them up to the
+/// current save format one version at a time.
+fn migrate_save(version: u32, contents: &str) -> Result<SaveGame, SaveError> {
+    return match version {
+        1 => {
+            let save_game: SaveGameV1 = ron::from_str(contents).map_err(StorageError::from)?;
+            Ok(migrate_v6_to_v7(migrate_v5_to_v6(migrate_v4_to_v5(
+                migrate_v3_to_v4(migrate_v2_to_v3(migrate_v1_to_v2(save_game))),
+            ))))
+        }
+        2 => {
+            let save_game: SaveGameV2 = ron::from_str(contents).map_err(StorageError::from)?;
+            Ok(migrate_v6_to_v7(migrate_v5_to_v6(migrate_v4_to_v5(
+                migrate_v3_to_v4(migrate_v2_to_v3(save_game)),
+            ))))
+        }
+        3 => {
+            let save_game: SaveGameV3 = ron::from_str(contents).map_err(StorageError::from)?;
+            Ok(migrate_v6_to_v7(migrate_v5_to_v6(migrate_v4_to_v5(
+                migrate_v3_to_v4(save_game),
+            ))))
+        }
+        4 => {
+            let save_game: SaveGameV4 = ron::from_str(contents).map_err(StorageError::from)?;
+            Ok(migrate_v6_to_v7(migrate_v5_to_v6(migrate_v4_to_v5(
+                save_game,
+            ))))
+        }
+        5 => {
+            let save_game: SaveGameV5 = ron::from_str(contents).map_err(StorageError::from)?;
+            Ok(migrate_v6_to_v7(migrate_v5_to_v6(save_game)))
+        }
+        6 => {
+            let save_game: SaveGameV6 = ron::from_str(contents).map_err(StorageError::from)?;
+            Ok(migrate_v6_to_v7(save_game))
+        }
+        CURRENT_SAVE_VERSION => Ok(ron::from_str(contents).map_err(StorageError::from)?),
+        _ => Err(SaveError::UnsupportedVersion(version)),
+    };
+}
+
+/// Reads a save file, migrating it when it was written with an older save version.
+pub fn read_save_file(path: &Path) -> Result<SaveGame, SaveError> {
+    let contents = fs::read_to_string(path).map_err(StorageError::from)?;
+    let header: SaveHeader = ron::from_str(&contents).map_err(StorageError::from)?;
+    return migrate_save(header.version, &contents);
+}
+
+/// Writes the save game to a save file with the current save version.
+pub fn write_save_file(path: &Path, save_game: &SaveGame) -> Result<(), SaveError> {
+    let save_game = SaveGame {
+        version: CURRENT_SAVE_VERSION,
+        ..save_game.clone()
+    };
+    write_ron_file(path, &save_game)?;
+    return Ok(());
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::prelude::{IVec2, Quat, Vec3};
+
+    use super::{
+        migrate_save, ClockSave, FarmSave, FarmTileSave, NpcSave, RoleHolderSave, RoleSave,
+        SaveGame, CURRENT_SAVE_VERSION,
+    };
+    use crate::farming::components::Crop;
+    use crate::farming::grid::SoilState;
+    use crate::game_time::Season;
+
+    #[test]
+    fn migrates_version_1_saves_matching_players_to_profiles_by_name() {
+        let contents = "(version: 1, players: [(name: \"Ada Lovelace\", \
+            translation: (1.0, 0.0, 2.0), rotation: (0.0, 0.0, 0.0, 1.0), stamina: 40.0)])";
+        let save_game = migrate_save(1, contents).unwrap();
+        assert_eq!(save_game.version, CURRENT_SAVE_VERSION);
+        let player = &save_game.players[0];
+        assert_eq!(player.profile_id, "ada_lovelace");
+        assert_eq!(player.name, "Ada Lovelace");
+        assert_eq!(player.translation, Vec3::new(1.0, 0.0, 2.0));
+        assert_eq!(player.stamina, 40.0);
+        assert!(player.inventory.slots.is_empty());
+        assert_eq!(save_game.clock.hour, ClockSave::default().hour);
+        assert!(save_game.chests.is_empty());
+        assert!(save_game.workstations.is_empty());
+        assert!(save_game.roles.is_empty());
+    }
+
+    #[test]
+    fn migrates_version_2_saves_starting_the_clock_in_the_morning() {
+        let contents = "(version: 2, players: [(profile_id: \"ada\", name: \"Ada\", \
+            translation: (1.0, 0.0, 2.0), rotation: (0.0, 0.0, 0.0, 1.0), stamina: 40.0)])";
+        let save_game = migrate_save(2, contents).unwrap();
+        assert_eq!(save_game.version, CURRENT_SAVE_VERSION);
+        assert_eq!(save_game.players[0].profile_id, "ada");
+        let clock = save_game.clock;
+        let default_clock = ClockSave::default();
+        assert_eq!(
+            (clock.year, clock.season, clock.day, clock.hour),
+            (
+                default_clock.year,
+                default_clock.season,
+                default_clock.day,
+                default_clock.hour
+            )
+        );
+    }
+
+    #[test]
+    fn migrates_version_3_saves_with_empty_inventories() {
+        let contents = "(version: 3, clock: (year: 2, season: Autumn, day: 5, hour: 13.5), \
+            players: [(profile_id: \"ada\", name: \"Ada\", translation: (1.0, 0.0, 2.0), \
+            rotation: (0.0, 0.0, 0.0, 1.0), stamina: 40.0)])";
+        let save_game = migrate_save(3, contents).unwrap();
+        assert_eq!(save_game.version, CURRENT_SAVE_VERSION);
+        assert_eq!(save_game.clock.season, Season::Autumn);
+        assert_eq!(save_game.clock.hour, 13.5);
+        let player = &save_game.players[0];
+        assert!(player.inventory.slots.is_empty());
+        assert_eq!(player.hotbar_selected, 0);
+        assert!(save_game.chests.is_empty());
+    }
+
+    #[test]
+    fn migrates_version_4_saves_keeping_inventories_and_chests() {
+        let contents = "(version: 4, clock: (year: 1, season: Winter, day: 2, hour: 8.0), \
+            players: [(profile_id: \"ada\", name: \"Ada\", translation: (1.0, 0.0, 2.0), \
+            rotation: (0.0, 0.0, 0.0, 1.0), stamina: 40.0, \
+            inventory: (slots: [Some((item: \"turnip\", count: 3)), None]), hotbar_selected: 1)], \
+            chests: [(id: \"shed_chest\", inventory: (slots: [Some((item: \"hoe\", count: 1))]))])";
+        let save_game = migrate_save(4, contents).unwrap();
+        assert_eq!(save_game.version, CURRENT_SAVE_VERSION);
+        let player = &save_game.players[0];
+        assert_eq!(player.inventory.count_of("turnip"), 3);
+        assert_eq!(player.hotbar_selected, 1);
+        assert_eq!(save_game.chests[0].id, "shed_chest");
+        assert_eq!(save_game.chests[0].inventory.count_of("hoe"), 1);
+        assert!(save_game.workstations.is_empty());
+        assert!(save_game.farm.tiles.is_empty());
+    }
+
+    #[test]
+    fn migrates_version_5_saves_with_an_untilled_farm() {
+        let contents = "(version: 5, clock: (year: 2, season: Summer, day: 3, hour: 9.0), \
+            players: [], chests: [], workstations: [(id: \"anvil\", jobs: [], output: (slots: []))])";
+        let save_game = migrate_save(5, contents).unwrap();
+        assert_eq!(save_game.version, CURRENT_SAVE_VERSION);
+        assert_eq!(save_game.clock.year, 2);
+        assert_eq!(save_game.workstations.len(), 1);
+        assert!(save_game.farm.tiles.is_empty());
+        assert!(save_game.farm.crops.is_empty());
+        assert!(save_game.roles.is_empty());
+    }
+
+    #[test]
+    fn migrates_version_6_saves_keeping_the_farm_and_roles() {
+        let contents = "(version: 6, clock: (year: 1, season: Spring, day: 4, hour: 7.0), \
+            players: [], chests: [], workstations: [], \
+            farm: (tiles: [(tile: (1, 2), soil: Tilled, watered: true)], crops: []), \
+            roles: [(profession_id: \"farmer\", holder: Vacant, next_npc: 1)])";
+        let save_game = migrate_save(6, contents).unwrap();
+        assert_eq!(save_game.version, CURRENT_SAVE_VERSION);
+        assert_eq!(save_game.farm.tiles[0].tile, IVec2::new(1, 2));
+        assert_eq!(save_game.roles[0].profession_id, "farmer");
+        assert!(save_game.npcs.is_empty());
+    }
+
+    #[test]
+    fn reads_back_the_saved_farm_roles_and_npcs() {
+        let save_game = SaveGame {
+            farm: FarmSave {
+                tiles: vec![FarmTileSave {
+                    tile: IVec2::new(2, 3),
+                    soil: SoilState::Fertilized,
+                    watered: true,
+                }],
+                crops: vec![Crop {
+                    crop_id: "turnip".to_string(),
+                    tile: IVec2::new(2, 3),
+                    stage: 2,
+                    days_in_stage: 1,
+                    dry_days: 1,
+                    regrowing: true,
+                    withered: false,
+                }],
+            },
+            roles: vec![RoleSave {
+                profession_id: "farmer".to_string(),
+                holder: RoleHolderSave::Player {
+                    profile_id: "ada".to_string(),
+                },
+                next_npc: 2,
+            }],
+            npcs: vec![NpcSave {
+                id: "miller".to_string(),
+                translation: Vec3::new(3.0, 0.0, -4.0),
+                rotation: Quat::from_rotation_y(1.0),
+                schedule_entry: Some(2),
+                target: Some(Vec3::new(5.0, 0.0, 5.0)),
+                arrived: true,
+            }],
+            ..SaveGame::default()
+        };
+        let contents = ron::to_string(&save_game).unwrap();
+        let read_back = migrate_save(CURRENT_SAVE_VERSION, &contents).unwrap();
+        let tile = &read_back.farm.tiles[0];
+        assert_eq!(tile.tile, IVec2::new(2, 3));
+        assert_eq!(tile.soil, SoilState::Fertilized);
+        assert!(tile.watered);
+        let crop = &read_back.farm.crops[0];
+        assert_eq!(crop.crop_id, "turnip");
+        assert_eq!((crop.stage, crop.days_in_stage, crop.dry_days), (2, 1, 1));
+        assert!(crop.regrowing && !crop.withered);
+        let role = &read_back.roles[0];
+        assert_eq!(role.next_npc, 2);
+        assert!(matches!(
+            &role.holder,
+            RoleHolderSave::Player { profile_id } if profile_id == "ada"
+        ));
+        let npc = &read_back.npcs[0];
+        assert_eq!(npc.id, "miller");
+        assert_eq!(npc.translation, Vec3::new(3.0, 0.0, -4.0));
+        assert_eq!(npc.schedule_entry, Some(2));
+        assert_eq!(npc.target, Some(Vec3::new(5.0, 0.0, 5.0)));
+        assert!(npc.arrived);
+    }
+}
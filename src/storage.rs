@@ -0,0 +1,88 @@
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use bevy::prelude::{info, warn};
+use serde::{de::DeserializeOwned, Serialize};
+
+/// The errors that can happen when reading or writing data files.
+#[derive(Debug)]
+pub enum StorageError {
+    Io(io::Error),
+    Deserialize(ron::error::SpannedError),
+    Serialize(ron::Error),
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        return match self {
+            StorageError::Io(error) => write!(formatter, "file error: {}", error),
+            StorageError::Deserialize(error) => write!(formatter, "invalid data: {}", error),
+            StorageError::Serialize(error) => write!(formatter, "could not serialize: {}", error),
+        };
+    }
+}
+
+impl From<io::Error> for StorageError {
+    fn from(error: io::Error) -> Self {
+        return StorageError::Io(error);
+    }
+}
+
+impl From<ron::error::SpannedError> for StorageError {
+    fn from(error: ron::error::SpannedError) -> Self {
+        return StorageError::Deserialize(error);
+    }
+}
+
+impl From<ron::Error> for StorageError {
+    fn from(error: ron::Error) -> Self {
+        return StorageError::Serialize(error);
+    }
+}
+
+/// Reads a value from a RON file.
+pub fn read_ron_file<T: DeserializeOwned>(path: &Path) -> Result<T, StorageError> {
+    let contents = fs::read_to_string(path)?;
+    let value = ron::from_str(&contents)?;
+    return Ok(value);
+}
+
+/// Reads a value from a RON data file, falling back to the default value when the file
+/// does not exist or can not be read. The description of what is loaded is used in the
+/// log messages, like "the items".
+pub fn load_ron_or_default<T: DeserializeOwned + Default>(path: &Path, what: &str) -> T {
+    if !path.exists() {
+        return T::default();
+    }
+    return match read_ron_file(path) {
+        Ok(value) => {
+            info!("Loaded {} from {}", what, path.display());
+            value
+        }
+        Err(error) => {
+            warn!(
+                "Could not load {} at {}, using the default: {}",
+                what,
+                path.display(),
+                error
+            );
+            T::default()
+        }
+    };
+}
+
+/// Writes a value to a RON file, creating the parent directories when they do not exist.
+/// The value is written to a temporary file first and then moved in place so a crash
+/// while writing does not leave a partially written file behind.
+pub fn write_ron_file<T: Serialize>(path: &Path, value: &T) -> Result<(), StorageError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let contents = ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default())?;
+    let temporary_path = path.with_extension("tmp");
+    fs::write(&temporary_path, contents)?;
+    fs::rename(&temporary_path, path)?;
+    return Ok(());
+}
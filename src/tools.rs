@@ -0,0 +1,5 @@
+/// tools module for the crate. The tools players use on the tiles and resource nodes in
+/// front of them, in tiers that reach more tiles and break harder nodes.
+pub(crate) mod components;
+pub(crate) mod control;
+pub(crate) mod start_up;
@@ -0,0 +1,91 @@
+use bevy::prelude::{Component, Entity, IVec2, Resource};
+use serde::{Deserialize, Serialize};
+
+use crate::farming::components::FarmAction;
+use crate::items::inventory::ItemStack;
+
+/// The kinds of tools, each doing its own thing to the world.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ToolKind {
+    /// Tills farm tiles.
+    Hoe,
+    /// Waters tilled farm tiles.
+    WateringCan,
+    /// Chops trees.
+    Axe,
+    /// Breaks rocks.
+    Pickaxe,
+    /// Harvests ripe and withered crops.
+    Scythe,
+}
+
+impl ToolKind {
+    /// Returns true when the tool acts on resource nodes rather than farm tiles.
+    pub fn hits_resource_nodes(&self) -> bool {
+        return matches!(self, ToolKind::Axe | ToolKind::Pickaxe);
+    }
+}
+
+/// The data definition of a tool item. Upgraded tiers of a tool are items of their own
+/// with a higher tier.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ToolDefinition {
+    /// The id of the item that is the tool.
+    pub item: String,
+    pub kind: ToolKind,
+    /// The tier of the tool. It is the damage a hit does to a resource node, and nodes
+    /// need a tool of at least their tier.
+    pub tier: u32,
+    /// How long a use of the tool takes, in seconds.
+    pub use_seconds: f32,
+    /// The stamina a use of the tool takes.
+    pub stamina_cost: f32,
+    /// The farm tiles a use reaches, as the number of tiles across and ahead of the
+    /// player.
+    #[serde(default = "default_tool_area")]
+    pub area: (i32, i32),
+}
+
+fn default_tool_area() -> (i32, i32) {
+    return (1, 1);
+}
+
+/// A Bevy Engine resource with the definitions of every tool, loaded from a data file.
+#[derive(Resource, Default, Serialize, Deserialize)]
+pub struct ToolRegistry {
+    pub tools: Vec<ToolDefinition>,
+}
+
+impl ToolRegistry {
+    /// Returns the definition of the tool that is the item, or None when the item is not
+    /// a tool.
+    pub fn find(&self, item: &str) -> Option<&ToolDefinition> {
+        return self.tools.iter().find(|tool| tool.item == item);
+    }
+}
+
+/// A component for a tree, rock or other resource node in the world that is broken with
+/// a tool and drops items.
+#[derive(Component)]
+pub struct ResourceNode {
+    /// The tool that breaks the node.
+    pub tool: ToolKind,
+    /// The lowest tier of the tool that can break the node.
+    pub min_tier: u32,
+    /// The damage left before the node breaks.
+    pub health: f32,
+    /// The items the player who breaks the node gets.
+    pub drops: Vec<ItemStack>,
+}
+
+/// A component for a character in the middle of using a tool or seed. What the use does
+/// to the world happens when the use is over.
+#[derive(Component)]
+pub struct PendingToolUse {
+    /// The tier of the tool, or zero for a seed.
+    pub tier: u32,
+    /// The farm actions done on the tiles the use reaches.
+    pub farm_actions: Vec<(IVec2, FarmAction)>,
+    /// The resource node the use hits.
+    pub resource_node: Option<Entity>,
+}
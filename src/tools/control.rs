@@ -0,0 +1,303 @@
+use bevy::prelude::{
+    info, Assets, Commands, DespawnRecursiveExt, Entity, EventWriter, GlobalTransform, IVec2,
+    Query, Res, Transform, Vec3, With, Without,
+};
+
+use crate::characters::animation::components::CharacterActivity;
+use crate::characters::components::Stamina;
+use crate::characters::player::entity::components::PlayerCharacter;
+use crate::characters::player::input::components::{ActionState, ButtonAction};
+use crate::crafting::components::CraftingMenu;
+use crate::farming::components::{
+    Crop, CropDefinition, CropRegistry, FarmAction, FarmActionEvent, FERTILIZER_ITEM,
+};
+use crate::farming::grid::{FarmGrid, SoilState};
+use crate::interaction::components::PickupAssets;
+use crate::interaction::start_up::spawn_pickup;
+use crate::items::components::{Hotbar, InventoryMenu, ItemRegistry};
+use crate::items::inventory::{Inventory, ItemStack};
+
+use super::components::{PendingToolUse, ResourceNode, ToolDefinition, ToolKind, ToolRegistry};
+
+/// How far in front of a player the first farm tile they reach is.
+const TILE_REACH: f32 = 0.8;
+/// How far from a player the resource nodes they reach can be.
+const RESOURCE_NODE_REACH: f32 = 2.0;
+/// How far a resource node can be from straight ahead of a player, as the cosine of the
+/// angle, for them to reach it.
+const RESOURCE_NODE_FACING: f32 = 0.5;
+/// How long planting a seed takes, in seconds.
+const PLANT_SECONDS: f32 = 0.4;
+/// How long spreading fertilizer on a tile takes, in seconds.
+const FERTILIZE_SECONDS: f32 = 0.5;
+
+/// Returns the farm tiles reached in front of the character with the area, as the
+/// number of tiles across and ahead. The area lines up with the grid axis the character
+/// faces the most, and starts at the tile just in front of the character.
+fn tiles_in_front(grid: &FarmGrid, transform: &Transform, area: (i32, i32)) -> Vec<IVec2> {
+    let forward = transform.forward();
+    let Some(first_tile) = grid.tile_at(transform.translation + forward * TILE_REACH) else {
+        return Vec::new();
+    };
+    let ahead = if forward.x.abs() >= forward.z.abs() {
+        IVec2::new(forward.x.signum() as i32, 0)
+    } else {
+        IVec2::new(0, forward.z.signum() as i32)
+    };
+    let across = IVec2::new(-ahead.y, ahead.x);
+    let (width, length) = (area.0.max(1), area.1.max(1));
+    let mut tiles = Vec::new();
+    for step in 0..length {
+        for side in 0..width {
+            let tile = first_tile + ahead * step + across * (side - (width - 1) / 2);
+            if grid.contains(tile) {
+                tiles.push(tile);
+            }
+        }
+    }
+    return tiles;
+}
+
+/// Returns the nearest resource node the tool breaks in reach in front of the character.
+fn resource_node_in_front(
+    transform: &Transform,
+    tool: ToolKind,
+    resource_nodes: &Query<(Entity, &ResourceNode, &GlobalTransform)>,
+) -> Option<Entity> {
+    let forward = transform.forward() * Vec3::new(1.0, 0.0, 1.0);
+    let mut nearest: Option<(Entity, f32)> = None;
+    for (entity, node, node_transform) in resource_nodes.iter() {
+        if node.tool != tool {
+            continue;
+        }
+        let offset =
+            (node_transform.translation() - transform.translation) * Vec3::new(1.0, 0.0, 1.0);
+        let distance = offset.length();
+        if distance > RESOURCE_NODE_REACH
+            || offset.normalize_or_zero().dot(forward.normalize_or_zero()) < RESOURCE_NODE_FACING
+        {
+            continue;
+        }
+        if nearest.is_none_or(|(_, nearest_distance)| distance < nearest_distance) {
+            nearest = Some((entity, distance));
+        }
+    }
+    return nearest.map(|(entity, _)| entity);
+}
+
+/// Returns the farm actions the tool does on the tiles. Tiles that the tool would not
+/// change are left out.
+fn tool_farm_actions(
+    tool: &ToolDefinition,
+    tiles: &[IVec2],
+    grid: &FarmGrid,
+    crops: &Query<&Crop>,
+    registry: &CropRegistry,
+    definitions: &Assets<CropDefinition>,
+) -> Vec<(IVec2, FarmAction)> {
+    let mut actions = Vec::new();
+    for tile in tiles.iter().copied() {
+        let Some(farm_tile) = grid.tile(tile) else {
+            continue;
+        };
+        let action = match tool.kind {
+            ToolKind::Hoe if !farm_tile.is_tilled() => FarmAction::Till,
+            ToolKind::WateringCan if farm_tile.is_tilled() && !farm_tile.watered => {
+                FarmAction::Water
+            }
+            ToolKind::Scythe => {
+                let Some(crop) = farm_tile.crop.and_then(|crop| crops.get(crop).ok()) else {
+                    continue;
+                };
+                let ripe = registry
+                    .find(definitions, &crop.crop_id)
+                    .is_some_and(|definition| crop.is_ripe(definition));
+                if !ripe && !crop.withered {
+                    continue;
+                }
+                FarmAction::Harvest
+            }
+            _ => continue,
+        };
+        actions.push((tile, action));
+    }
+    return actions;
+}
+
+/// A system that starts a tool use for every player who presses the use tool button
+/// with a tool, seed or fertilizer selected on their hotbar. The tool acts on the farm tiles or the
+/// resource node in front of the player, and a use that would change nothing or a tool
+/// too weak for the node is not started. Tools take stamina, and a player without enough stamina can not use them.
+pub fn start_tool_uses(
+    mut commands: Commands,
+    tool_registry: Res<ToolRegistry>,
+    item_registry: Res<ItemRegistry>,
+    grid: Res<FarmGrid>,
+    crop_registry: Res<CropRegistry>,
+    definitions: Res<Assets<CropDefinition>>,
+    crops: Query<&Crop>,
+    resource_nodes: Query<(Entity, &ResourceNode, &GlobalTransform)>,
+    mut players: Query<
+        (
+            Entity,
+            &ActionState,
+            &Transform,
+            &Inventory,
+            &Hotbar,
+            &mut CharacterActivity,
+            Option<&mut Stamina>,
+        ),
+        (
+            With<PlayerCharacter>,
+            Without<InventoryMenu>,
+            Without<CraftingMenu>,
+            Without<PendingToolUse>,
+        ),
+    >,
+) {
+    for (player_entity, action_state, transform, inventory, hotbar, mut activity, stamina) in
+        players.iter_mut()
+    {
+        if !action_state.just_pressed(ButtonAction::UseTool) || *activity != CharacterActivity::None
+        {
+            continue;
+        }
+        let Some(stack) = hotbar.selected_stack(inventory) else {
+            continue;
+        };
+        let item_action = if let Some(crop_id) = item_registry.crop_of_seed(&stack.item) {
+            Some((FarmAction::Plant(crop_id.to_string()), PLANT_SECONDS))
+        } else if stack.item == FERTILIZER_ITEM {
+            Some((FarmAction::Fertilize, FERTILIZE_SECONDS))
+        } else {
+            None
+        };
+        if let Some((action, seconds)) = item_action {
+            let Some(tile) = tiles_in_front(&grid, transform, (1, 1)).first().copied() else {
+                continue;
+            };
+            let Some(farm_tile) = grid.tile(tile) else {
+                continue;
+            };
+            let changes_tile = match action {
+                FarmAction::Fertilize => farm_tile.soil == SoilState::Tilled,
+                _ => farm_tile.is_tilled() && farm_tile.crop.is_none(),
+            };
+            if !changes_tile {
+                continue;
+            }
+            commands.entity(player_entity).insert(PendingToolUse {
+                tier: 0,
+                farm_actions: vec![(tile, action)],
+                resource_node: None,
+            });
+            *activity = CharacterActivity::UsingTool {
+                seconds_left: seconds,
+            };
+            continue;
+        }
+        let Some(tool) = tool_registry.find(&stack.item) else {
+            continue;
+        };
+        let pending_use = if tool.kind.hits_resource_nodes() {
+            let Some(resource_node) = resource_node_in_front(transform, tool.kind, &resource_nodes)
+            else {
+                continue;
+            };
+            let Ok((_, node, _)) = resource_nodes.get(resource_node) else {
+                continue;
+            };
+            if tool.tier < node.min_tier {
+                info!("The tool is not strong enough to break this");
+                continue;
+            }
+            PendingToolUse {
+                tier: tool.tier,
+                farm_actions: Vec::new(),
+                resource_node: Some(resource_node),
+            }
+        } else {
+            let tiles = tiles_in_front(&grid, transform, tool.area);
+            let farm_actions =
+                tool_farm_actions(tool, &tiles, &grid, &crops, &crop_registry, &definitions);
+            if farm_actions.is_empty() {
+                continue;
+            }
+            PendingToolUse {
+                tier: tool.tier,
+                farm_actions: farm_actions,
+                resource_node: None,
+            }
+        };
+        if let Some(mut stamina) = stamina {
+            if stamina.current < tool.stamina_cost {
+                info!("Too tired to use the {}", item_registry.name(&tool.item));
+                continue;
+            }
+            stamina.current -= tool.stamina_cost;
+            stamina.seconds_since_use = 0.0;
+        }
+        commands.entity(player_entity).insert(pending_use);
+        *activity = CharacterActivity::UsingTool {
+            seconds_left: tool.use_seconds,
+        };
+    }
+}
+
+/// A system that finishes the tool use of every character whose use is over. The farm
+/// actions of the use are done on their tiles, and a hit resource node takes damage as
+/// high as the tier of the tool. A broken node is removed and its drops go to the
+/// character. The drops that do not fit are left as a pickup where the node was.
+pub fn finish_tool_uses(
+    mut commands: Commands,
+    item_registry: Res<ItemRegistry>,
+    pickup_assets: Res<PickupAssets>,
+    characters: Query<(Entity, &PendingToolUse, &CharacterActivity)>,
+    mut resource_nodes: Query<(&mut ResourceNode, &GlobalTransform)>,
+    mut inventories: Query<&mut Inventory>,
+    mut farm_actions: EventWriter<FarmActionEvent>,
+) {
+    for (character_entity, pending_use, activity) in characters.iter() {
+        if matches!(activity, CharacterActivity::UsingTool { .. }) {
+            continue;
+        }
+        commands.entity(character_entity).remove::<PendingToolUse>();
+        for (tile, action) in pending_use.farm_actions.iter() {
+            farm_actions.send(FarmActionEvent {
+                tile: *tile,
+                action: action.clone(),
+                actor: Some(character_entity),
+            });
+        }
+        let Some(node_entity) = pending_use.resource_node else {
+            continue;
+        };
+        let Ok((mut node, node_transform)) = resource_nodes.get_mut(node_entity) else {
+            continue;
+        };
+        node.health -= pending_use.tier as f32;
+        if node.health > 0.0 {
+            continue;
+        }
+        let mut inventory = inventories.get_mut(character_entity).ok();
+        for stack in node.drops.iter() {
+            let left = match inventory.as_mut() {
+                Some(inventory) => inventory.add(&item_registry, &stack.item, stack.count),
+                None => stack.count,
+            };
+            if left > 0 {
+                spawn_pickup(
+                    &mut commands,
+                    &pickup_assets,
+                    &item_registry,
+                    ItemStack {
+                        item: stack.item.clone(),
+                        count: left,
+                    },
+                    node_transform.translation(),
+                );
+            }
+        }
+        commands.entity(node_entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,167 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use bevy::prelude::{
+    default, shape, warn, Assets, BuildChildren, Color, Commands, Mesh, PbrBundle, ResMut,
+    SpatialBundle, StandardMaterial, Transform, Vec3,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::characters::player::camera::components::FadeWhenOccluding;
+use crate::collision::components::{ColliderShape, StaticCollider};
+use crate::items::inventory::ItemStack;
+use crate::storage::load_ron_or_default;
+
+use super::components::{ResourceNode, ToolKind, ToolRegistry};
+
+/// The file the tool definitions are loaded from.
+const TOOL_REGISTRY_PATH: &str = "assets/items/tools.ron";
+/// The file the resource nodes of the world are loaded from.
+const RESOURCE_NODES_PATH: &str = "assets/world/resource_nodes.ron";
+/// The size of the trunk of a tree node.
+const TRUNK_SIZE: Vec3 = Vec3::new(0.5, 2.4, 0.5);
+/// The radius of the canopy of a tree node.
+const CANOPY_RADIUS: f32 = 1.4;
+
+/// How a kind of resource node looks.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+enum ResourceNodeShape {
+    Tree,
+    Rock { radius: f32 },
+}
+
+/// The data definition of a kind of resource node.
+#[derive(Serialize, Deserialize)]
+struct ResourceNodeKind {
+    tool: ToolKind,
+    #[serde(default = "default_min_tier")]
+    min_tier: u32,
+    health: f32,
+    shape: ResourceNodeShape,
+    #[serde(default)]
+    drops: Vec<ItemStack>,
+}
+
+fn default_min_tier() -> u32 {
+    return 1;
+}
+
+/// Where a resource node stands in the world.
+#[derive(Serialize, Deserialize)]
+struct ResourceNodePlacement {
+    kind: String,
+    position: Vec3,
+}
+
+/// The kinds of resource nodes and where they stand, as they are in their data file.
+#[derive(Default, Serialize, Deserialize)]
+struct ResourceNodesFile {
+    kinds: BTreeMap<String, ResourceNodeKind>,
+    nodes: Vec<ResourceNodePlacement>,
+}
+
+/// Loads the tool definitions from their data file.
+pub fn load_tool_registry() -> ToolRegistry {
+    return load_ron_or_default(Path::new(TOOL_REGISTRY_PATH), "the tools");
+}
+
+/// Loads the resource nodes from their data file.
+fn load_resource_nodes() -> ResourceNodesFile {
+    return load_ron_or_default(Path::new(RESOURCE_NODES_PATH), "the resource nodes");
+}
+
+/// Initial system to add the trees and rocks of the world that are broken with tools.
+pub fn spawn_resource_nodes(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    let file = load_resource_nodes();
+    let trunk_mesh = meshes.add(shape::Box::new(TRUNK_SIZE.x, TRUNK_SIZE.y, TRUNK_SIZE.z).into());
+    let trunk_material = materials.add(Color::hex("#6b4423").unwrap().into());
+    let canopy_mesh = meshes.add(
+        Mesh::try_from(shape::Icosphere {
+            radius: CANOPY_RADIUS,
+            subdivisions: 6,
+        })
+        .unwrap(),
+    );
+    let canopy_material = materials.add(Color::hex("#4a8d3c").unwrap().into());
+    let rock_material = materials.add(Color::rgb(0.52, 0.52, 0.55).into());
+    for placement in file.nodes {
+        let Some(kind) = file.kinds.get(&placement.kind) else {
+            warn!("There is no resource node kind {}", placement.kind);
+            continue;
+        };
+        let node = ResourceNode {
+            tool: kind.tool,
+            min_tier: kind.min_tier,
+            health: kind.health,
+            drops: kind.drops.clone(),
+        };
+        match kind.shape {
+            ResourceNodeShape::Tree => {
+                commands
+                    .spawn((
+                        SpatialBundle::from_transform(Transform::from_translation(
+                            placement.position,
+                        )),
+                        StaticCollider {
+                            shape: ColliderShape::Cuboid {
+                                half_extents: TRUNK_SIZE / 2.0,
+                            },
+                            offset: Vec3::new(0.0, TRUNK_SIZE.y / 2.0, 0.0),
+                        },
+                        node,
+                    ))
+                    .with_children(|parent| {
+                        parent.spawn(PbrBundle {
+                            mesh: trunk_mesh.clone(),
+                            material: trunk_material.clone(),
+                            transform: Transform::from_xyz(0.0, TRUNK_SIZE.y / 2.0, 0.0),
+                            ..default()
+                        });
+                        parent.spawn((
+                            PbrBundle {
+                                mesh: canopy_mesh.clone(),
+                                material: canopy_material.clone(),
+                                transform: Transform::from_xyz(
+                                    0.0,
+                                    TRUNK_SIZE.y + CANOPY_RADIUS * 0.6,
+                                    0.0,
+                                ),
+                                ..default()
+                            },
+                            FadeWhenOccluding::default(),
+                        ));
+                    });
+            }
+            ResourceNodeShape::Rock { radius } => {
+                commands.spawn((
+                    PbrBundle {
+                        mesh: meshes.add(
+                            Mesh::try_from(shape::Icosphere {
+                                radius: radius,
+                                subdivisions: 1,
+                            })
+                            .unwrap(),
+                        ),
+                        material: rock_material.clone(),
+                        transform: Transform::from_translation(
+                            placement.position + Vec3::new(0.0, radius * 0.5, 0.0),
+                        )
+                        .with_scale(Vec3::new(1.0, 0.7, 1.0)),
+                        ..default()
+                    },
+                    StaticCollider {
+                        shape: ColliderShape::Sphere {
+                            radius: radius * 0.85,
+                        },
+                        offset: Vec3::ZERO,
+                    },
+                    node,
+                ));
+            }
+        }
+    }
+}
@@ -0,0 +1,4 @@
+/// ui module for the crate. The on-screen interface drawn over the player viewports.
+pub(crate) mod menu_navigation;
+pub(crate) mod player_overlay;
+pub(crate) mod start_up;
@@ -0,0 +1,51 @@
+use bevy::prelude::{IVec2, Vec2};
+
+/// How far a stick has to be pushed to move through a menu.
+const MENU_STICK_THRESHOLD: f32 = 0.5;
+/// How close to the center a stick has to come back before it moves through a menu
+/// again.
+const MENU_STICK_CENTERED_THRESHOLD: f32 = 0.3;
+
+/// Turns a stick into single steps through a menu, so holding the stick only moves one
+/// step until the stick goes back to the center.
+#[derive(Clone, Copy)]
+pub struct MenuNavigation {
+    stick_centered: bool,
+}
+
+impl Default for MenuNavigation {
+    fn default() -> Self {
+        return MenuNavigation {
+            stick_centered: true,
+        };
+    }
+}
+
+impl MenuNavigation {
+    /// Returns the step the stick moves through the menu this frame, if any. Positive x
+    /// is right and positive y is up. Only the axis the stick is pushed furthest along
+    /// moves.
+    pub fn step(&mut self, stick: Vec2) -> Option<IVec2> {
+        let deflection = stick.x.abs().max(stick.y.abs());
+        if deflection < MENU_STICK_CENTERED_THRESHOLD {
+            self.stick_centered = true;
+            return None;
+        }
+        if !self.stick_centered || deflection <= MENU_STICK_THRESHOLD {
+            return None;
+        }
+        self.stick_centered = false;
+        if stick.x.abs() >= stick.y.abs() {
+            return Some(IVec2::new(stick.x.signum() as i32, 0));
+        }
+        return Some(IVec2::new(0, stick.y.signum() as i32));
+    }
+}
+
+/// Moves the index by the step and wraps it around the number of entries.
+pub fn wrap_index(index: usize, step: i32, count: usize) -> usize {
+    if count == 0 {
+        return 0;
+    }
+    return (index as i32 + step).rem_euclid(count as i32) as usize;
+}
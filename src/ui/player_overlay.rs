@@ -0,0 +1,208 @@
+use bevy::prelude::{
+    default, AlignItems, BuildChildren, Bundle, Camera, Color, Commands, Component,
+    DespawnRecursiveExt, Entity, EventReader, JustifyContent, NodeBundle, PositionType, Query, Res,
+    Size, Style, Text, TextBundle, TextStyle, Time, UiRect, Val, Vec2, Visibility, Window, With,
+    Without,
+};
+use bevy::window::PrimaryWindow;
+
+use crate::characters::player::control::PlayerCameraLink;
+
+use super::start_up::UiFont;
+
+/// How long a message stays on the overlay of a player, in seconds.
+const MESSAGE_SECONDS: f32 = 4.0;
+
+/// A component for the root interface node of a player. It covers the viewport of the
+/// player so the interface of every player is drawn over their part of the screen.
+#[derive(Component)]
+pub struct PlayerOverlay {
+    pub player: Entity,
+}
+
+/// A component on a player that links it to its overlay node. Interface elements for
+/// the player are added as children of the overlay.
+#[derive(Component)]
+pub struct PlayerOverlayLink {
+    pub overlay: Entity,
+}
+
+/// An event to show a short message on the overlay of a player, like what a character
+/// they talk to says. A new message replaces the one the player is shown.
+pub struct PlayerMessageEvent {
+    pub player: Entity,
+    pub text: String,
+}
+
+/// A component for the message node of a player overlay, with the seconds until the
+/// message goes away.
+#[derive(Component)]
+pub struct PlayerMessage {
+    pub player: Entity,
+    pub seconds_left: f32,
+}
+
+/// A system that adds an overlay for every player that has a camera but no overlay.
+pub fn spawn_player_overlays(
+    mut commands: Commands,
+    players: Query<Entity, (With<PlayerCameraLink>, Without<PlayerOverlayLink>)>,
+) {
+    for player_entity in players.iter() {
+        let overlay = commands
+            .spawn((
+                PlayerOverlay {
+                    player: player_entity,
+                },
+                NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    visibility: Visibility::Hidden,
+                    ..default()
+                },
+            ))
+            .id();
+        commands
+            .entity(player_entity)
+            .insert(PlayerOverlayLink { overlay: overlay });
+    }
+}
+
+/// A system that places every player overlay over the viewport of the camera of its
+/// player. Overlays of players whose camera is turned off are hidden.
+pub fn layout_player_overlays(
+    windows: Query<&Window, With<PrimaryWindow>>,
+    players: Query<&PlayerCameraLink>,
+    cameras: Query<&Camera>,
+    mut overlays: Query<(&PlayerOverlay, &mut Style, &mut Visibility)>,
+) {
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+    let scale_factor = window.scale_factor() as f32;
+    for (overlay, mut style, mut visibility) in overlays.iter_mut() {
+        let camera = players
+            .get(overlay.player)
+            .ok()
+            .and_then(|camera_link| cameras.get(camera_link.camera).ok())
+            .filter(|camera| camera.is_active);
+        let Some(camera) = camera else {
+            *visibility = Visibility::Hidden;
+            continue;
+        };
+        let (position, size) = match &camera.viewport {
+            Some(viewport) => (
+                viewport.physical_position.as_vec2() / scale_factor,
+                viewport.physical_size.as_vec2() / scale_factor,
+            ),
+            None => (Vec2::ZERO, Vec2::new(window.width(), window.height())),
+        };
+        let position_rect = UiRect {
+            left: Val::Px(position.x),
+            top: Val::Px(position.y),
+            ..default()
+        };
+        let node_size = Size::new(Val::Px(size.x), Val::Px(size.y));
+        if style.position != position_rect {
+            style.position = position_rect;
+        }
+        if style.size != node_size {
+            style.size = node_size;
+        }
+        if *visibility != Visibility::Inherited {
+            *visibility = Visibility::Inherited;
+        }
+    }
+}
+
+/// A system that despawns the overlays of players that left the game.
+pub fn despawn_orphaned_player_overlays(
+    mut commands: Commands,
+    players: Query<&PlayerOverlayLink>,
+    overlays: Query<(Entity, &PlayerOverlay)>,
+) {
+    for (overlay_entity, overlay) in overlays.iter() {
+        if players.get(overlay.player).is_err() {
+            commands.entity(overlay_entity).despawn_recursive();
+        }
+    }
+}
+
+/// Spawns a node of the interface as a child of the overlay of the player. Returns the
+/// node entity.
+pub fn spawn_in_player_overlay(
+    commands: &mut Commands,
+    overlay_link: &PlayerOverlayLink,
+    node: impl Bundle,
+) -> Entity {
+    let node_entity = commands.spawn(node).id();
+    commands.entity(overlay_link.overlay).add_child(node_entity);
+    return node_entity;
+}
+
+/// A system that shows the messages sent to players at the top of their overlay.
+pub fn show_player_messages(
+    mut commands: Commands,
+    ui_font: Res<UiFont>,
+    mut message_events: EventReader<PlayerMessageEvent>,
+    players: Query<&PlayerOverlayLink>,
+    mut messages: Query<(&mut PlayerMessage, &mut Text)>,
+) {
+    for message_event in message_events.iter() {
+        let shown = messages
+            .iter_mut()
+            .find(|(message, _)| message.player == message_event.player);
+        if let Some((mut message, mut text)) = shown {
+            message.seconds_left = MESSAGE_SECONDS;
+            text.sections[0].value = message_event.text.clone();
+            continue;
+        }
+        let Ok(overlay_link) = players.get(message_event.player) else {
+            continue;
+        };
+        spawn_in_player_overlay(
+            &mut commands,
+            overlay_link,
+            (
+                TextBundle::from_section(
+                    message_event.text.clone(),
+                    TextStyle {
+                        font: ui_font.0.clone(),
+                        font_size: 22.0,
+                        color: Color::WHITE,
+                    },
+                )
+                .with_style(Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        top: Val::Px(48.0),
+                        ..default()
+                    },
+                    ..default()
+                }),
+                PlayerMessage {
+                    player: message_event.player,
+                    seconds_left: MESSAGE_SECONDS,
+                },
+            ),
+        );
+    }
+}
+
+/// A system that counts down the messages on the player overlays and removes the ones
+/// that are over.
+pub fn expire_player_messages(
+    mut commands: Commands,
+    timer: Res<Time>,
+    mut messages: Query<(Entity, &mut PlayerMessage)>,
+) {
+    for (message_entity, mut message) in messages.iter_mut() {
+        message.seconds_left -= timer.delta_seconds();
+        if message.seconds_left <= 0.0 {
+            commands.entity(message_entity).despawn_recursive();
+        }
+    }
+}
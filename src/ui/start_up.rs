@@ -0,0 +1,34 @@
+use bevy::core_pipeline::clear_color::ClearColorConfig;
+use bevy::prelude::{
+    default, AssetServer, Camera, Camera2d, Camera2dBundle, Commands, Font, Handle, Res, Resource,
+};
+
+/// The font used by all the text of the interface.
+const UI_FONT_PATH: &str = "fonts/DejaVuSans.ttf";
+/// The render order of the interface camera, after all the player cameras.
+const UI_CAMERA_ORDER: isize = 100;
+
+/// A Bevy Engine resource with the font used by all the text of the interface.
+#[derive(Resource)]
+pub struct UiFont(pub Handle<Font>);
+
+/// Initial system to load the font of the interface.
+pub fn load_ui_font(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(UiFont(asset_server.load(UI_FONT_PATH)));
+}
+
+/// Initial system to add the camera that draws the interface over the whole window. The
+/// player cameras do not draw the interface, otherwise it would be drawn once for every
+/// player.
+pub fn add_ui_camera(mut commands: Commands) {
+    commands.spawn(Camera2dBundle {
+        camera: Camera {
+            order: UI_CAMERA_ORDER,
+            ..default()
+        },
+        camera_2d: Camera2d {
+            clear_color: ClearColorConfig::None,
+        },
+        ..default()
+    });
+}
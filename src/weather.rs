@@ -0,0 +1,6 @@
+/// weather module for the crate. Rolls the weather of every day from the season with a
+/// seeded random generator and shows it with precipitation and fog.
+pub(crate) mod components;
+pub(crate) mod control;
+pub(crate) mod forecast;
+pub(crate) mod start_up;
@@ -0,0 +1,42 @@
+use bevy::prelude::{Component, Handle, Mesh, Resource, StandardMaterial};
+
+use crate::game_time::Season;
+
+use super::forecast::{WeatherKind, WeatherSettings};
+
+/// A Bevy Engine resource with the weather of the current day and the settings it was
+/// rolled with.
+#[derive(Resource)]
+pub struct Weather {
+    pub settings: WeatherSettings,
+    pub today: WeatherKind,
+    /// The year, season and day the weather was rolled for.
+    pub date: Option<(u32, Season, u32)>,
+}
+
+impl Weather {
+    /// Creates the weather with the settings. The weather of the day is rolled once the
+    /// clock runs.
+    pub fn new(settings: WeatherSettings) -> Self {
+        return Weather {
+            settings: settings,
+            today: WeatherKind::Sun,
+            date: None,
+        };
+    }
+}
+
+/// A component for a rain drop or snow flake falling around the players.
+#[derive(Component)]
+pub struct Precipitation {
+    pub kind: WeatherKind,
+}
+
+/// A Bevy Engine resource with the meshes and materials of the precipitation.
+#[derive(Resource)]
+pub struct PrecipitationAssets {
+    pub rain_mesh: Handle<Mesh>,
+    pub rain_material: Handle<StandardMaterial>,
+    pub snow_mesh: Handle<Mesh>,
+    pub snow_material: Handle<StandardMaterial>,
+}
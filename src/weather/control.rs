@@ -0,0 +1,159 @@
+use bevy::pbr::{FogFalloff, FogSettings, NotShadowCaster};
+use bevy::prelude::{
+    default, info, ClearColor, Color, Commands, DespawnRecursiveExt, DetectChanges, Entity, Local,
+    PbrBundle, Query, Res, ResMut, Time, Transform, Vec3, With, Without,
+};
+
+use crate::characters::player::camera::components::PlayerCamera;
+use crate::characters::player::entity::components::PlayerCharacter;
+use crate::game_time::GameClock;
+use crate::random::SeededRandom;
+
+use super::components::{Precipitation, PrecipitationAssets, Weather};
+use super::forecast::{forecast_weather, WeatherKind};
+
+/// How far around a player the precipitation falls, horizontally and above them.
+const PRECIPITATION_RADIUS: f32 = 14.0;
+const PRECIPITATION_HEIGHT: f32 = 12.0;
+
+/// A system that rolls the weather whenever the date changes, at midnight or when a game
+/// is loaded.
+pub fn update_daily_weather(clock: Res<GameClock>, mut weather: ResMut<Weather>) {
+    let date = (clock.year, clock.season, clock.day);
+    if weather.date == Some(date) {
+        return;
+    }
+    let today = forecast_weather(&weather.settings, clock.year, clock.season, clock.day);
+    weather.date = Some(date);
+    weather.today = today;
+    info!("The weather today is {:?}", today);
+}
+
+/// Returns how many drops or flakes fall around the players in the weather, how fast
+/// they fall and how fast the wind blows them sideways.
+fn precipitation_amount(weather: WeatherKind) -> (usize, f32, f32) {
+    return match weather {
+        WeatherKind::Rain => (400, 14.0, 0.5),
+        WeatherKind::Storm => (800, 18.0, 4.0),
+        WeatherKind::Snow => (400, 1.5, 0.6),
+        WeatherKind::Sun | WeatherKind::Fog => (0, 0.0, 0.0),
+    };
+}
+
+/// Returns a random position in the precipitation volume around one of the focus
+/// points, at the height between the minimum and maximum fractions of the volume.
+fn random_precipitation_position(
+    random: &mut SeededRandom,
+    focuses: &[Vec3],
+    minimum_height: f32,
+    maximum_height: f32,
+) -> Vec3 {
+    let focus = focuses[(random.next_u64() % focuses.len() as u64) as usize];
+    return Vec3::new(
+        focus.x + random.range_f32(-PRECIPITATION_RADIUS, PRECIPITATION_RADIUS),
+        focus.y + PRECIPITATION_HEIGHT * random.range_f32(minimum_height, maximum_height),
+        focus.z + random.range_f32(-PRECIPITATION_RADIUS, PRECIPITATION_RADIUS),
+    );
+}
+
+/// A system that makes rain or snow fall around every player. Drops that reach the
+/// ground or drift away from the players start again above a player, and drops of
+/// another kind of weather are removed.
+pub fn update_precipitation(
+    mut commands: Commands,
+    timer: Res<Time>,
+    weather: Res<Weather>,
+    assets: Res<PrecipitationAssets>,
+    players: Query<&Transform, (With<PlayerCharacter>, Without<Precipitation>)>,
+    mut drops: Query<(Entity, &Precipitation, &mut Transform)>,
+    mut random: Local<SeededRandom>,
+) {
+    let mut focuses: Vec<Vec3> = players
+        .iter()
+        .map(|transform| transform.translation)
+        .collect();
+    if focuses.is_empty() {
+        focuses.push(Vec3::ZERO);
+    }
+    let (wanted_count, fall_speed, wind_speed) = precipitation_amount(weather.today);
+    let delta_seconds = timer.delta_seconds();
+    let mut count = 0;
+    for (entity, drop, mut transform) in drops.iter_mut() {
+        if drop.kind != weather.today || count >= wanted_count {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        count += 1;
+        transform.translation += Vec3::new(wind_speed, -fall_speed, 0.0) * delta_seconds;
+        let near_a_player = focuses.iter().any(|focus| {
+            let offset = transform.translation - *focus;
+            return offset.x.abs() <= PRECIPITATION_RADIUS
+                && offset.z.abs() <= PRECIPITATION_RADIUS;
+        });
+        if transform.translation.y < 0.0 || !near_a_player {
+            transform.translation = random_precipitation_position(&mut random, &focuses, 0.9, 1.0);
+        }
+    }
+    let (mesh, material) = match weather.today {
+        WeatherKind::Snow => (assets.snow_mesh.clone(), assets.snow_material.clone()),
+        _ => (assets.rain_mesh.clone(), assets.rain_material.clone()),
+    };
+    for _ in count..wanted_count {
+        // New drops start anywhere in the volume so the sky fills up right away.
+        let position = random_precipitation_position(&mut random, &focuses, 0.0, 1.0);
+        commands.spawn((
+            PbrBundle {
+                mesh: mesh.clone(),
+                material: material.clone(),
+                transform: Transform::from_translation(position),
+                ..default()
+            },
+            NotShadowCaster,
+            Precipitation {
+                kind: weather.today,
+            },
+        ));
+    }
+}
+
+/// Returns the distances the fog of the weather starts at and becomes solid at, or None
+/// when the weather has no fog.
+fn fog_distances(weather: WeatherKind) -> Option<(f32, f32)> {
+    return match weather {
+        WeatherKind::Sun => None,
+        WeatherKind::Fog => Some((2.0, 25.0)),
+        WeatherKind::Storm => Some((5.0, 40.0)),
+        WeatherKind::Snow => Some((8.0, 55.0)),
+        WeatherKind::Rain => Some((10.0, 70.0)),
+    };
+}
+
+/// A system that adds the fog of the weather to the player cameras. The fog takes the
+/// color of the sky so it darkens with the night.
+pub fn update_weather_fog(
+    mut commands: Commands,
+    weather: Res<Weather>,
+    clear_color: Res<ClearColor>,
+    cameras: Query<(Entity, Option<&FogSettings>), With<PlayerCamera>>,
+) {
+    let fog_changed = weather.is_changed() || clear_color.is_changed();
+    for (camera_entity, fog_settings) in cameras.iter() {
+        match (fog_distances(weather.today), fog_settings) {
+            (None, Some(_)) => {
+                commands.entity(camera_entity).remove::<FogSettings>();
+            }
+            (Some((start, end)), current) if current.is_none() || fog_changed => {
+                commands.entity(camera_entity).insert(FogSettings {
+                    color: clear_color.0,
+                    directional_light_color: Color::NONE,
+                    falloff: FogFalloff::Linear {
+                        start: start,
+                        end: end,
+                    },
+                    ..default()
+                });
+            }
+            _ => {}
+        }
+    }
+}
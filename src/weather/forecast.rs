@@ -0,0 +1,225 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::game_time::Season;
+use crate::random::SeededRandom;
+
+/// The kinds of weather a day can have.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum WeatherKind {
+    #[default]
+    Sun,
+    Rain,
+    Storm,
+    Snow,
+    Fog,
+}
+
+impl WeatherKind {
+    /// Returns true when the weather waters the crops in the open, so the farmers do not
+    /// have to.
+    pub fn waters_crops(self) -> bool {
+        return matches!(self, WeatherKind::Rain | WeatherKind::Storm);
+    }
+
+    /// Returns how much of the sunlight gets through the clouds, from 0 to 1.
+    pub fn sunlight(self) -> f32 {
+        return match self {
+            WeatherKind::Sun => 1.0,
+            WeatherKind::Fog => 0.6,
+            WeatherKind::Snow => 0.55,
+            WeatherKind::Rain => 0.4,
+            WeatherKind::Storm => 0.2,
+        };
+    }
+}
+
+/// How likely a kind of weather is, relative to the other kinds of the season.
+#[derive(Clone, Copy, Serialize, Deserialize)]
+pub struct WeatherChance {
+    pub kind: WeatherKind,
+    pub weight: f32,
+}
+
+/// The settings of the weather. The same seed always rolls the same weather on the same
+/// days.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct WeatherSettings {
+    pub seed: u64,
+    pub chances: BTreeMap<Season, Vec<WeatherChance>>,
+}
+
+impl Default for WeatherSettings {
+    fn default() -> Self {
+        let chances = |weights: [(WeatherKind, f32); 4]| -> Vec<WeatherChance> {
+            return weights
+                .into_iter()
+                .map(|(kind, weight)| WeatherChance {
+                    kind: kind,
+                    weight: weight,
+                })
+                .collect();
+        };
+        return WeatherSettings {
+            seed: 2023,
+            chances: BTreeMap::from([
+                (
+                    Season::Spring,
+                    chances([
+                        (WeatherKind::Sun, 5.0),
+                        (WeatherKind::Rain, 3.0),
+                        (WeatherKind::Storm, 1.0),
+                        (WeatherKind::Fog, 1.0),
+                    ]),
+                ),
+                (
+                    Season::Summer,
+                    chances([
+                        (WeatherKind::Sun, 7.0),
+                        (WeatherKind::Rain, 1.5),
+                        (WeatherKind::Storm, 1.5),
+                        (WeatherKind::Fog, 0.0),
+                    ]),
+                ),
+                (
+                    Season::Autumn,
+                    chances([
+                        (WeatherKind::Sun, 4.0),
+                        (WeatherKind::Rain, 3.0),
+                        (WeatherKind::Storm, 1.0),
+                        (WeatherKind::Fog, 2.0),
+                    ]),
+                ),
+                (
+                    Season::Winter,
+                    chances([
+                        (WeatherKind::Sun, 4.0),
+                        (WeatherKind::Snow, 4.0),
+                        (WeatherKind::Fog, 1.5),
+                        (WeatherKind::Storm, 0.5),
+                    ]),
+                ),
+            ]),
+        };
+    }
+}
+
+/// Returns the weather of the day. The weather only depends on the seed and the date,
+/// so it is the same every time the day is played, even after loading a save. Seasons
+/// without chances are sunny.
+pub fn forecast_weather(
+    settings: &WeatherSettings,
+    year: u32,
+    season: Season,
+    day: u32,
+) -> WeatherKind {
+    let Some(chances) = settings.chances.get(&season) else {
+        return WeatherKind::Sun;
+    };
+    let total_weight: f32 = chances.iter().map(|chance| chance.weight.max(0.0)).sum();
+    if total_weight <= f32::EPSILON {
+        return WeatherKind::Sun;
+    }
+    let mut random =
+        SeededRandom::from_parts(settings.seed, &[year as u64, season as u64, day as u64]);
+    let mut roll = random.next_f32() * total_weight;
+    for chance in chances {
+        roll -= chance.weight.max(0.0);
+        if roll < 0.0 {
+            return chance.kind;
+        }
+    }
+    return chances
+        .last()
+        .map_or(WeatherKind::Sun, |chance| chance.kind);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, HashMap};
+
+    use crate::game_time::Season;
+
+    use super::{forecast_weather, WeatherChance, WeatherKind, WeatherSettings};
+
+    fn settings_with(seed: u64, season: Season, weights: &[(WeatherKind, f32)]) -> WeatherSettings {
+        return WeatherSettings {
+            seed: seed,
+            chances: BTreeMap::from([(
+                season,
+                weights
+                    .iter()
+                    .map(|(kind, weight)| WeatherChance {
+                        kind: *kind,
+                        weight: *weight,
+                    })
+                    .collect(),
+            )]),
+        };
+    }
+
+    #[test]
+    fn forecasts_the_same_weather_for_the_same_seed_and_date() {
+        let settings = WeatherSettings::default();
+        for day in 1..=28 {
+            let first = forecast_weather(&settings, 3, Season::Autumn, day);
+            let second = forecast_weather(&settings.clone(), 3, Season::Autumn, day);
+            assert_eq!(first, second);
+        }
+        let other_seed = WeatherSettings {
+            seed: settings.seed + 1,
+            ..settings.clone()
+        };
+        let differs = (1..=28).any(|day| {
+            forecast_weather(&settings, 3, Season::Autumn, day)
+                != forecast_weather(&other_seed, 3, Season::Autumn, day)
+        });
+        assert!(differs);
+    }
+
+    #[test]
+    fn forecasts_sun_in_seasons_without_chances() {
+        let settings = settings_with(
+            7,
+            Season::Spring,
+            &[(WeatherKind::Rain, 0.0), (WeatherKind::Storm, -2.0)],
+        );
+        for day in 1..=28 {
+            assert_eq!(
+                forecast_weather(&settings, 1, Season::Spring, day),
+                WeatherKind::Sun
+            );
+            assert_eq!(
+                forecast_weather(&settings, 1, Season::Winter, day),
+                WeatherKind::Sun
+            );
+        }
+    }
+
+    #[test]
+    fn rolls_the_kinds_by_their_weights() {
+        let settings = settings_with(
+            11,
+            Season::Summer,
+            &[
+                (WeatherKind::Sun, 3.0),
+                (WeatherKind::Fog, 0.0),
+                (WeatherKind::Rain, 1.0),
+            ],
+        );
+        let days = 4000;
+        let mut counts: HashMap<WeatherKind, u32> = HashMap::new();
+        for index in 0..days {
+            let kind = forecast_weather(&settings, 1 + index / 28, Season::Summer, 1 + index % 28);
+            *counts.entry(kind).or_default() += 1;
+        }
+        assert_eq!(counts.get(&WeatherKind::Fog), None);
+        let rain_share = counts[&WeatherKind::Rain] as f32 / days as f32;
+        assert!(
+            (rain_share - 0.25).abs() < 0.03,
+            "rain share {}",
+            rain_share
+        );
+    }
+}
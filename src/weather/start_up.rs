@@ -0,0 +1,51 @@
+use std::path::Path;
+
+use bevy::prelude::{
+    default, shape, AlphaMode, Assets, Color, Commands, Mesh, ResMut, StandardMaterial,
+};
+
+use crate::storage::load_ron_or_default;
+
+use super::components::{PrecipitationAssets, Weather};
+
+/// The file the weather settings are loaded from.
+const WEATHER_SETTINGS_PATH: &str = "assets/world/weather.ron";
+
+/// Loads the weather with the settings from their data file.
+pub fn load_weather() -> Weather {
+    return Weather::new(load_ron_or_default(
+        Path::new(WEATHER_SETTINGS_PATH),
+        "the weather settings",
+    ));
+}
+
+/// Initial system to add the meshes and materials of the rain drops and snow flakes.
+/// They are unlit so they show in any light.
+pub fn add_precipitation_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.insert_resource(PrecipitationAssets {
+        rain_mesh: meshes.add(shape::Box::new(0.02, 0.45, 0.02).into()),
+        rain_material: materials.add(StandardMaterial {
+            base_color: Color::rgba(0.7, 0.8, 0.95, 0.5),
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            ..default()
+        }),
+        snow_mesh: meshes.add(
+            Mesh::try_from(shape::Icosphere {
+                radius: 0.05,
+                subdivisions: 1,
+            })
+            .unwrap(),
+        ),
+        snow_material: materials.add(StandardMaterial {
+            base_color: Color::rgba(1.0, 1.0, 1.0, 0.9),
+            alpha_mode: AlphaMode::Blend,
+            unlit: true,
+            ..default()
+        }),
+    });
+}